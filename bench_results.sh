@@ -0,0 +1,64 @@
+#!/usr/bin/env bash
+# Export Criterion results as one JSON file, and compare two exports for regressions.
+# Usage: ./bench_results.sh export [out.json]                 (default .bench/<short sha>.json)
+#        ./bench_results.sh compare <baseline.json> [current.json]  (default: export the current results)
+# Env: THRESHOLD=<mean slowdown in percent that fails compare, default 10>
+# Needs jq. Export collects whatever is under target/criterion, so run the benches you care about first.
+set -euo pipefail
+
+THRESHOLD="${THRESHOLD:-10}"
+# Resolve via cargo metadata: respects .cargo/config.toml build.target-dir.
+TARGET_DIR=$(cargo metadata --format-version 1 --no-deps \
+    | sed -n 's/.*"target_directory":"\([^"]*\)".*/\1/p')
+CRITERION_DIR="$TARGET_DIR/criterion"
+
+# {"commit", "date", "benchmarks": {"<criterion full id>": {"mean_ns", "median_ns", "std_dev_ns"}}}
+export_results() {
+    local out="${1:-.bench/$(git rev-parse --short HEAD).json}"
+    mapfile -t DIRS < <(find "$CRITERION_DIR" -path '*/new/benchmark.json' -printf '%h\n' 2>/dev/null | sort)
+    [ "${#DIRS[@]}" -gt 0 ] || { echo "no Criterion results under $CRITERION_DIR; run cargo bench first" >&2; exit 1; }
+    mkdir -p "$(dirname "$out")"
+    for DIR in "${DIRS[@]}"; do
+        jq -n --slurpfile b "$DIR/benchmark.json" --slurpfile e "$DIR/estimates.json" \
+            '{($b[0].full_id): {
+                mean_ns: $e[0].mean.point_estimate,
+                median_ns: $e[0].median.point_estimate,
+                std_dev_ns: $e[0].std_dev.point_estimate}}'
+    done | jq -s --arg commit "$(git rev-parse HEAD)" --arg date "$(date -Iseconds)" \
+        '{commit: $commit, date: $date, benchmarks: add}' > "$out"
+    echo "$out"
+}
+
+# One row per benchmark present in both files; exits 1 if any mean got slower than THRESHOLD.
+compare_results() {
+    local baseline="${1:?Usage: $0 compare <baseline.json> [current.json]}"
+    local current="${2:-}"
+    if [ -z "$current" ]; then
+        CURRENT_TMP=$(mktemp); trap 'rm -f "$CURRENT_TMP"' EXIT
+        current="$CURRENT_TMP"
+        export_results "$current" > /dev/null
+    fi
+    local rows
+    rows=$(jq -r -n --slurpfile b "$baseline" --slurpfile c "$current" --argjson t "$THRESHOLD" '
+        $b[0].benchmarks as $old | $c[0].benchmarks as $new
+        | $new | keys[] | select($old[.]) as $id
+        | (($new[$id].mean_ns / $old[$id].mean_ns - 1) * 100) as $pct
+        | [(if $pct > $t then "REGRESSED" elif $pct < -$t then "improved" else "ok" end),
+           $id, ($old[$id].mean_ns | round), ($new[$id].mean_ns | round),
+           (($pct * 10 | round) / 10 | tostring) + "%"]
+        | @tsv')
+    { printf 'status\tbenchmark\tbaseline_ns\tcurrent_ns\tchange\n'; printf '%s\n' "$rows"; } \
+        | awk -F'\t' '{ printf "%-10s %-60s %12s %12s %8s\n", $1, $2, $3, $4, $5 }'
+    local regressed
+    regressed=$(printf '%s\n' "$rows" | grep -c '^REGRESSED' || true)
+    if [ "$regressed" -gt 0 ]; then
+        echo "$regressed benchmark(s) more than ${THRESHOLD}% slower than $baseline" >&2
+        exit 1
+    fi
+}
+
+case "${1:-}" in
+    export) export_results "${2:-}" ;;
+    compare) shift; compare_results "$@" ;;
+    *) echo "Usage: $0 export [out.json] | compare <baseline.json> [current.json]" >&2; exit 2 ;;
+esac
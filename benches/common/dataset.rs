@@ -28,6 +28,7 @@ impl Dataset {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: false,
+                encoding: Default::default(),
             },
         );
         for i in 0..num_cols {
@@ -36,12 +37,16 @@ impl Dataset {
                 ColumnSchema {
                     dtype: DTypeName::Float32,
                     nullable: false,
+                    encoding: Default::default(),
                 },
             );
         }
         let table_schema = TableSchema {
             key: "key".to_string(),
             columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
         };
         let arrow_schema = Arc::new(Schema::from(&table_schema));
         let batch = generate_batch(&arrow_schema, num_rows);
@@ -30,6 +30,7 @@ fn make_schema() -> (TableSchema, Arc<Schema>) {
         ColumnSchema {
             dtype: DTypeName::Utf8,
             nullable: false,
+            encoding: Default::default(),
         },
     );
     for name in &col_names {
@@ -38,12 +39,16 @@ fn make_schema() -> (TableSchema, Arc<Schema>) {
             ColumnSchema {
                 dtype: DTypeName::Float32,
                 nullable: false,
+                encoding: Default::default(),
             },
         );
     }
     let table_schema = TableSchema {
         key: "key".to_string(),
         columns,
+        key_alias: None,
+        derived: None,
+        entity: None,
     };
     let arrow_schema = Arc::new(Schema::from(&table_schema));
     (table_schema, arrow_schema)
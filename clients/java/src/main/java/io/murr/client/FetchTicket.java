@@ -0,0 +1,66 @@
+package io.murr.client;
+
+import com.fasterxml.jackson.annotation.JsonInclude;
+import com.fasterxml.jackson.annotation.JsonProperty;
+import com.fasterxml.jackson.databind.ObjectMapper;
+import java.io.IOException;
+import java.io.UncheckedIOException;
+import java.util.List;
+
+/**
+ * JSON ticket for Flight {@code DoGet}. Mirrors {@code FetchTicket} in
+ * {@code src/api/flight/ticket.rs}; keep the two in sync. Null fields are left out, so the
+ * server applies its defaults.
+ *
+ * @param version wire format version; null means 1
+ * @param keys strings, or numbers with {@code keyType} {@code "int"}
+ * @param keyType {@code "string"} (default) or {@code "int"}
+ * @param includeKey prepend the key column, in the table's key type
+ * @param onMissing {@code "null"} (default) keeps a null row per missing key, {@code "skip"}
+ *     drops it, {@code "error"} fails the request
+ * @param format result encoding; only {@code "arrow"}
+ * @param debug timing breakdown as JSON in the schema message's app_metadata
+ */
+@JsonInclude(JsonInclude.Include.NON_NULL)
+public record FetchTicket(
+    @JsonProperty("version") Integer version,
+    @JsonProperty("table") String table,
+    @JsonProperty("keys") List<?> keys,
+    @JsonProperty("columns") List<String> columns,
+    @JsonProperty("key_type") String keyType,
+    @JsonProperty("include_key") boolean includeKey,
+    @JsonProperty("on_missing") String onMissing,
+    @JsonProperty("format") String format,
+    @JsonProperty("string_view") boolean stringView,
+    @JsonProperty("ignore_unknown_columns") boolean ignoreUnknownColumns,
+    @JsonProperty("debug") boolean debug) {
+
+  private static final ObjectMapper MAPPER = new ObjectMapper();
+
+  public FetchTicket(String table, List<String> keys, List<String> columns) {
+    this(table, keys, columns, false, false);
+  }
+
+  public FetchTicket(
+      String table,
+      List<String> keys,
+      List<String> columns,
+      boolean stringView,
+      boolean ignoreUnknownColumns) {
+    this(
+        null, table, keys, columns, null, false, null, null, stringView, ignoreUnknownColumns,
+        false);
+  }
+
+  public byte[] toBytes() {
+    try {
+      return MAPPER.writeValueAsBytes(this);
+    } catch (IOException e) {
+      throw new UncheckedIOException(e);
+    }
+  }
+
+  public static FetchTicket fromBytes(byte[] bytes) throws IOException {
+    return MAPPER.readValue(bytes, FetchTicket.class);
+  }
+}
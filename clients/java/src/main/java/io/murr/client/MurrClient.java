@@ -0,0 +1,104 @@
+package io.murr.client;
+
+import java.util.ArrayList;
+import java.util.List;
+import org.apache.arrow.flight.CallHeaders;
+import org.apache.arrow.flight.FlightCallHeaders;
+import org.apache.arrow.flight.FlightClient;
+import org.apache.arrow.flight.FlightDescriptor;
+import org.apache.arrow.flight.FlightStream;
+import org.apache.arrow.flight.HeaderCallOption;
+import org.apache.arrow.flight.Location;
+import org.apache.arrow.flight.Ticket;
+import org.apache.arrow.memory.BufferAllocator;
+import org.apache.arrow.memory.RootAllocator;
+import org.apache.arrow.vector.VectorLoader;
+import org.apache.arrow.vector.VectorSchemaRoot;
+import org.apache.arrow.vector.VectorUnloader;
+import org.apache.arrow.vector.ipc.message.ArrowRecordBatch;
+import org.apache.arrow.vector.types.pojo.Schema;
+
+/**
+ * Thin client for murr's Arrow Flight API.
+ *
+ * <pre>{@code
+ * try (MurrClient client = MurrClient.connect("localhost", 8081);
+ *      VectorSchemaRoot result = client.get("docs", List.of("doc_1"), List.of("score"))) {
+ *   Float4Vector score = (Float4Vector) result.getVector("score");
+ * }
+ * }</pre>
+ */
+public final class MurrClient implements AutoCloseable {
+  private final BufferAllocator allocator;
+  private final FlightClient client;
+  private final HeaderCallOption headers;
+
+  private MurrClient(BufferAllocator allocator, FlightClient client, HeaderCallOption headers) {
+    this.allocator = allocator;
+    this.client = client;
+    this.headers = headers;
+  }
+
+  public static MurrClient connect(String host, int port) {
+    return connect(host, port, null);
+  }
+
+  /** Connects with an optional API token, sent as the {@code authorization} header. */
+  public static MurrClient connect(String host, int port, String token) {
+    BufferAllocator allocator = new RootAllocator();
+    FlightClient client =
+        FlightClient.builder(allocator, Location.forGrpcInsecure(host, port)).build();
+    CallHeaders callHeaders = new FlightCallHeaders();
+    if (token != null) {
+      callHeaders.insert("authorization", token);
+    }
+    return new MurrClient(allocator, client, new HeaderCallOption(callHeaders));
+  }
+
+  /** Arrow schema of a table, including the key column. */
+  public Schema schema(String table) {
+    return client.getSchema(FlightDescriptor.path(table), headers).getSchema();
+  }
+
+  /**
+   * Fetches {@code columns} for {@code keys}: one row per key in request order, nulls for
+   * missing keys. The caller owns and must close the returned root.
+   */
+  public VectorSchemaRoot get(String table, List<String> keys, List<String> columns) {
+    return get(new FetchTicket(table, keys, columns));
+  }
+
+  public VectorSchemaRoot get(FetchTicket ticket) {
+    try (FlightStream stream = client.getStream(new Ticket(ticket.toBytes()), headers)) {
+      VectorSchemaRoot result = VectorSchemaRoot.create(stream.getSchema(), allocator);
+      VectorLoader loader = new VectorLoader(result);
+      List<ArrowRecordBatch> batches = new ArrayList<>();
+      try {
+        while (stream.next()) {
+          batches.add(new VectorUnloader(stream.getRoot()).getRecordBatch());
+        }
+        // murr answers a fetch with a single batch; anything else is a protocol change.
+        if (batches.size() != 1) {
+          throw new IllegalStateException("expected one batch, got " + batches.size());
+        }
+        loader.load(batches.get(0));
+        return result;
+      } catch (RuntimeException e) {
+        result.close();
+        throw e;
+      } finally {
+        batches.forEach(ArrowRecordBatch::close);
+      }
+    } catch (RuntimeException e) {
+      throw e;
+    } catch (Exception e) {
+      throw new IllegalStateException("fetch from '" + ticket.table() + "' failed", e);
+    }
+  }
+
+  @Override
+  public void close() throws InterruptedException {
+    client.close();
+    allocator.close();
+  }
+}
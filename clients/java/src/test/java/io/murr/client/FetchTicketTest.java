@@ -0,0 +1,43 @@
+package io.murr.client;
+
+import static org.junit.jupiter.api.Assertions.assertEquals;
+import static org.junit.jupiter.api.Assertions.assertFalse;
+
+import java.nio.charset.StandardCharsets;
+import java.util.List;
+import org.junit.jupiter.api.Test;
+
+class FetchTicketTest {
+  @Test
+  void serializesServerFieldNames() throws Exception {
+    FetchTicket ticket = new FetchTicket("docs", List.of("a"), List.of("score"));
+    String json = new String(ticket.toBytes(), StandardCharsets.UTF_8);
+    assertEquals(
+        "{\"table\":\"docs\",\"keys\":[\"a\"],\"columns\":[\"score\"],"
+            + "\"include_key\":false,\"string_view\":false,"
+            + "\"ignore_unknown_columns\":false,\"debug\":false}",
+        json);
+    assertEquals(ticket, FetchTicket.fromBytes(ticket.toBytes()));
+  }
+
+  @Test
+  void serializesIntKeysAndOptions() throws Exception {
+    FetchTicket ticket =
+        new FetchTicket(
+            1, "counts", List.of(7L, 8L), List.of("n"), "int", true, "skip", "arrow", false, false,
+            true);
+    String json = new String(ticket.toBytes(), StandardCharsets.UTF_8);
+    assertEquals(
+        "{\"version\":1,\"table\":\"counts\",\"keys\":[7,8],\"columns\":[\"n\"],"
+            + "\"key_type\":\"int\",\"include_key\":true,\"on_missing\":\"skip\","
+            + "\"format\":\"arrow\",\"string_view\":false,"
+            + "\"ignore_unknown_columns\":false,\"debug\":true}",
+        json);
+  }
+
+  @Test
+  void readsTicketsWithoutOptionalFields() throws Exception {
+    byte[] json = "{\"table\":\"docs\",\"keys\":[],\"columns\":[]}".getBytes(StandardCharsets.UTF_8);
+    assertFalse(FetchTicket.fromBytes(json).stringView());
+  }
+}
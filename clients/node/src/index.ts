@@ -0,0 +1,87 @@
+import { dirname, join } from "node:path";
+import { fileURLToPath } from "node:url";
+
+import * as grpc from "@grpc/grpc-js";
+import * as protoLoader from "@grpc/proto-loader";
+import { Schema, Table, tableFromIPC } from "apache-arrow";
+
+import { endOfStream, frameMessage } from "./ipc.js";
+import { FetchTicket, encodeTicket } from "./ticket.js";
+
+export type { FetchTicket } from "./ticket.js";
+
+const PROTO = join(dirname(fileURLToPath(import.meta.url)), "..", "..", "proto", "flight.proto");
+
+interface FlightData {
+  data_header: Buffer;
+  data_body: Buffer;
+}
+
+export interface ConnectOptions {
+  /** Sent as the `authorization` header; also used as the rate-limit client id. */
+  token?: string;
+  /** `online` (default) or `batch`, sent as `x-murr-priority`. */
+  priority?: "online" | "batch";
+}
+
+/** Thin client for murr's Arrow Flight API. */
+export class MurrClient {
+  private readonly client: grpc.Client & Record<string, any>;
+  private readonly metadata: grpc.Metadata;
+
+  constructor(address: string, options: ConnectOptions = {}) {
+    const definition = protoLoader.loadSync(PROTO, { keepCase: true, enums: String });
+    const proto = grpc.loadPackageDefinition(definition) as any;
+    this.client = new proto.arrow.flight.protocol.FlightService(
+      address,
+      grpc.credentials.createInsecure(),
+    );
+    this.metadata = new grpc.Metadata();
+    if (options.token) this.metadata.set("authorization", options.token);
+    if (options.priority) this.metadata.set("x-murr-priority", options.priority);
+  }
+
+  /**
+   * Fetches `columns` for `keys`: one row per key in request order, nulls
+   * for missing keys.
+   */
+  get(table: string, keys: string[], columns: string[]): Promise<Table> {
+    return this.fetch({ table, keys, columns });
+  }
+
+  fetch(ticket: FetchTicket): Promise<Table> {
+    return new Promise((resolve, reject) => {
+      const frames: Buffer[] = [];
+      const call = this.client.DoGet({ ticket: encodeTicket(ticket) }, this.metadata);
+      call.on("data", (data: FlightData) => {
+        frames.push(frameMessage(data.data_header, data.data_body));
+      });
+      call.on("error", reject);
+      call.on("end", () => {
+        try {
+          resolve(tableFromIPC(Buffer.concat([...frames, endOfStream()])));
+        } catch (e) {
+          reject(e);
+        }
+      });
+    });
+  }
+
+  /** Arrow schema of a table, including the key column. */
+  schema(table: string): Promise<Schema> {
+    return new Promise((resolve, reject) => {
+      this.client.GetSchema(
+        { type: "PATH", path: [table] },
+        this.metadata,
+        (err: grpc.ServiceError | null, result: { schema: Buffer }) => {
+          if (err) return reject(err);
+          resolve(tableFromIPC(Buffer.concat([result.schema, endOfStream()])).schema);
+        },
+      );
+    });
+  }
+
+  close(): void {
+    this.client.close();
+  }
+}
@@ -0,0 +1,20 @@
+const CONTINUATION = 0xffffffff;
+
+/**
+ * Frames one Flight message (IPC flatbuffer header + body) as an Arrow IPC
+ * stream message, so the concatenated frames can be read by `tableFromIPC`.
+ */
+export function frameMessage(header: Uint8Array, body: Uint8Array): Buffer {
+  const padding = (8 - ((header.length + 8) % 8)) % 8;
+  const prefix = Buffer.alloc(8);
+  prefix.writeUInt32LE(CONTINUATION, 0);
+  prefix.writeInt32LE(header.length + padding, 4);
+  return Buffer.concat([prefix, header, Buffer.alloc(padding), body]);
+}
+
+/** Marks the end of an IPC stream. */
+export function endOfStream(): Buffer {
+  const eos = Buffer.alloc(8);
+  eos.writeUInt32LE(CONTINUATION, 0);
+  return eos;
+}
@@ -0,0 +1,28 @@
+/**
+ * JSON ticket for Flight `DoGet`. Mirrors `FetchTicket` in
+ * `src/api/flight/ticket.rs`; keep the two in sync.
+ */
+export interface FetchTicket {
+  /** Wire format version; omitted means 1. */
+  version?: number;
+  table: string;
+  keys: string[] | number[];
+  columns: string[];
+  /** `int` when `keys` are numbers. */
+  key_type?: "string" | "int";
+  /** Prepend the key column, in the table's key type, to the result. */
+  include_key?: boolean;
+  /** `null` (default) keeps a null row per missing key; `skip` drops it; `error` fails. */
+  on_missing?: "null" | "skip" | "error";
+  format?: "arrow";
+  /** Return utf8 columns as Utf8View instead of Utf8. */
+  string_view?: boolean;
+  /** Skip requested columns the table doesn't have. */
+  ignore_unknown_columns?: boolean;
+  /** Timing breakdown as JSON in the schema message's app_metadata. */
+  debug?: boolean;
+}
+
+export function encodeTicket(ticket: FetchTicket): Buffer {
+  return Buffer.from(JSON.stringify(ticket), "utf8");
+}
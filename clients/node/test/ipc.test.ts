@@ -0,0 +1,31 @@
+import assert from "node:assert/strict";
+import { test } from "node:test";
+
+import { tableFromArrays, tableFromIPC, tableToIPC } from "apache-arrow";
+
+import { endOfStream, frameMessage } from "../src/ipc.js";
+import { encodeTicket } from "../src/ticket.js";
+
+test("ticket uses the server's field names", () => {
+  const json = encodeTicket({ table: "docs", keys: ["a"], columns: ["score"] }).toString();
+  assert.deepEqual(JSON.parse(json), { table: "docs", keys: ["a"], columns: ["score"] });
+});
+
+test("frames pad the header to 8 bytes", () => {
+  const framed = frameMessage(Buffer.alloc(5), Buffer.from([1, 2]));
+  assert.equal(framed.readUInt32LE(0), 0xffffffff);
+  assert.equal(framed.readInt32LE(4), 8);
+  assert.equal(framed.length, 8 + 8 + 2);
+});
+
+test("a reframed schema message decodes as an IPC stream", () => {
+  const source = tableFromArrays({ score: new Float32Array([1.5]) });
+  const ipc = Buffer.from(tableToIPC(source, "stream"));
+  // The first stream message is the schema; Flight sends its flatbuffer as data_header.
+  const header = ipc.subarray(8, 8 + ipc.readInt32LE(4));
+  const decoded = tableFromIPC(Buffer.concat([frameMessage(header, Buffer.alloc(0)), endOfStream()]));
+  assert.deepEqual(
+    decoded.schema.fields.map((f) => f.name),
+    ["score"],
+  );
+});
@@ -0,0 +1,79 @@
+/*
+ * C bindings for embedding murr. Results use the Arrow C Data Interface:
+ * https://arrow.apache.org/docs/format/CDataInterface.html
+ *
+ * A successful murr_read fills a struct array (one child per requested
+ * column, one row per key). Release it with array->release(array) and
+ * schema->release(schema) as usual.
+ */
+#ifndef MURR_H
+#define MURR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#ifndef ARROW_C_DATA_INTERFACE
+#define ARROW_C_DATA_INTERFACE
+
+#define ARROW_FLAG_DICTIONARY_ORDERED 1
+#define ARROW_FLAG_NULLABLE 2
+#define ARROW_FLAG_MAP_KEYS_SORTED 4
+
+struct ArrowSchema {
+  const char* format;
+  const char* name;
+  const char* metadata;
+  int64_t flags;
+  int64_t n_children;
+  struct ArrowSchema** children;
+  struct ArrowSchema* dictionary;
+  void (*release)(struct ArrowSchema*);
+  void* private_data;
+};
+
+struct ArrowArray {
+  int64_t length;
+  int64_t null_count;
+  int64_t offset;
+  int64_t n_buffers;
+  int64_t n_children;
+  const void** buffers;
+  struct ArrowArray** children;
+  struct ArrowArray* dictionary;
+  void (*release)(struct ArrowArray*);
+  void* private_data;
+};
+
+#endif /* ARROW_C_DATA_INTERFACE */
+
+#define MURR_OK 0
+#define MURR_ERROR 1
+
+typedef struct MurrHandle MurrHandle;
+
+/* Opens (or creates) a store at path. Returns NULL on error. */
+MurrHandle* murr_open(const char* path);
+
+/* Reads columns for keys from table into out_array/out_schema.
+ * Returns MURR_OK or MURR_ERROR. */
+int murr_read(const MurrHandle* handle, const char* table,
+              const char* const* keys, size_t n_keys,
+              const char* const* columns, size_t n_columns,
+              struct ArrowArray* out_array, struct ArrowSchema* out_schema);
+
+/* Message of the last failed call on this thread, or NULL. Valid until the
+ * next murr call on the same thread. */
+const char* murr_last_error(void);
+
+/* Closes a handle. Exported arrays stay valid until released. */
+void murr_close(MurrHandle* handle);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MURR_H */
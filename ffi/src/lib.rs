@@ -0,0 +1,264 @@
+//! C bindings for the embedded read path.
+//!
+//! Results cross the boundary through the Arrow C Data Interface: a read
+//! fills a caller-provided `ArrowArray`/`ArrowSchema` pair with a struct
+//! array, one child per requested column. The consumer owns the exported
+//! structs and frees them through their `release` callbacks, as with any
+//! other Arrow producer. See `include/murr.h` for the C declarations.
+//!
+//! Errors are reported as a non-zero status; `murr_last_error` returns the
+//! message for the calling thread.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::ptr;
+
+use arrow::array::{Array, StructArray};
+use arrow::ffi::{FFI_ArrowArray, FFI_ArrowSchema};
+use murr::Murr;
+use murr::core::MurrError;
+
+pub const MURR_OK: c_int = 0;
+pub const MURR_ERROR: c_int = 1;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Opaque handle returned by `murr_open`.
+pub struct MurrHandle {
+    db: Murr,
+}
+
+/// # Safety
+/// `ptr` must be null or a valid NUL-terminated string.
+unsafe fn to_str<'a>(ptr: *const c_char, what: &str) -> Result<&'a str, MurrError> {
+    if ptr.is_null() {
+        return Err(MurrError::TableError(format!("{what} is null")));
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|_| MurrError::TableError(format!("{what} is not valid UTF-8")))
+}
+
+/// # Safety
+/// `ptr` must be null (with `len == 0`) or point to `len` valid strings.
+unsafe fn to_strs<'a>(
+    ptr: *const *const c_char,
+    len: usize,
+    what: &str,
+) -> Result<Vec<&'a str>, MurrError> {
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    if ptr.is_null() {
+        return Err(MurrError::TableError(format!("{what} is null")));
+    }
+    unsafe { std::slice::from_raw_parts(ptr, len) }
+        .iter()
+        .map(|s| unsafe { to_str(*s, what) })
+        .collect()
+}
+
+/// Opens (or creates) a store at `path`. Returns null on error.
+///
+/// # Safety
+/// `path` must be a valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn murr_open(path: *const c_char) -> *mut MurrHandle {
+    let opened = unsafe { to_str(path, "path") }.and_then(Murr::open);
+    match opened {
+        Ok(db) => Box::into_raw(Box::new(MurrHandle { db })),
+        Err(e) => {
+            set_last_error(e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Reads `columns` for `keys` from `table`. On success, `out_array` and
+/// `out_schema` hold a struct array with one row per key, in request order;
+/// missing keys are null.
+///
+/// # Safety
+/// `handle` must come from `murr_open`; string pointers must be valid
+/// NUL-terminated strings; `out_array`/`out_schema` must point to writable,
+/// uninitialised structs.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn murr_read(
+    handle: *const MurrHandle,
+    table: *const c_char,
+    keys: *const *const c_char,
+    n_keys: usize,
+    columns: *const *const c_char,
+    n_columns: usize,
+    out_array: *mut FFI_ArrowArray,
+    out_schema: *mut FFI_ArrowSchema,
+) -> c_int {
+    let result = (|| -> Result<(FFI_ArrowArray, FFI_ArrowSchema), MurrError> {
+        let handle = unsafe { handle.as_ref() }
+            .ok_or_else(|| MurrError::TableError("handle is null".to_string()))?;
+        if out_array.is_null() || out_schema.is_null() {
+            return Err(MurrError::TableError("output pointer is null".to_string()));
+        }
+        let table = unsafe { to_str(table, "table") }?;
+        let keys = unsafe { to_strs(keys, n_keys, "key") }?;
+        let columns = unsafe { to_strs(columns, n_columns, "column") }?;
+        let batch = handle.db.read(table, &keys, &columns)?;
+        let data = StructArray::from(batch).into_data();
+        let schema = FFI_ArrowSchema::try_from(data.data_type())?;
+        Ok((FFI_ArrowArray::new(&data), schema))
+    })();
+    match result {
+        Ok((array, schema)) => {
+            unsafe {
+                ptr::write_unaligned(out_array, array);
+                ptr::write_unaligned(out_schema, schema);
+            }
+            MURR_OK
+        }
+        Err(e) => {
+            set_last_error(e.to_string());
+            MURR_ERROR
+        }
+    }
+}
+
+/// Message of the last failed call on this thread, or null. Valid until the
+/// next murr call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn murr_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+/// Closes a handle from `murr_open`. Arrays already exported stay valid
+/// until released.
+///
+/// # Safety
+/// `handle` must be null or come from `murr_open`, and not be used again.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn murr_close(handle: *mut MurrHandle) {
+    if !handle.is_null() {
+        drop(unsafe { Box::from_raw(handle) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float32Array, RecordBatch, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::ffi::from_ffi;
+    use murr::core::{ColumnSchema, DTypeName, TableSchema};
+    use std::mem::MaybeUninit;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn read_exports_struct_array() {
+        let dir = TempDir::new().unwrap();
+        let path = CString::new(dir.path().to_str().unwrap()).unwrap();
+        let handle = unsafe { murr_open(path.as_ptr()) };
+        assert!(!handle.is_null());
+
+        let db = unsafe { &(*handle).db };
+        let schema = TableSchema {
+            key: "id".to_string(),
+            columns: [
+                (
+                    "id".to_string(),
+                    ColumnSchema {
+                        dtype: DTypeName::Utf8,
+                        nullable: false,
+                        encoding: Default::default(),
+                    },
+                ),
+                (
+                    "score".to_string(),
+                    ColumnSchema {
+                        dtype: DTypeName::Float32,
+                        nullable: true,
+                        encoding: Default::default(),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            key_alias: None,
+            derived: None,
+            entity: None,
+        };
+        db.create_table("t", schema).unwrap();
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Utf8, false),
+                Field::new("score", DataType::Float32, true),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["a"])),
+                Arc::new(Float32Array::from(vec![1.5])),
+            ],
+        )
+        .unwrap();
+        db.write("t", &batch).unwrap();
+
+        let table = CString::new("t").unwrap();
+        let keys = [CString::new("a").unwrap(), CString::new("zz").unwrap()];
+        let key_ptrs: Vec<*const c_char> = keys.iter().map(|k| k.as_ptr()).collect();
+        let column = CString::new("score").unwrap();
+        let column_ptrs = [column.as_ptr()];
+        let mut array = MaybeUninit::<FFI_ArrowArray>::uninit();
+        let mut schema = MaybeUninit::<FFI_ArrowSchema>::uninit();
+        let status = unsafe {
+            murr_read(
+                handle,
+                table.as_ptr(),
+                key_ptrs.as_ptr(),
+                key_ptrs.len(),
+                column_ptrs.as_ptr(),
+                column_ptrs.len(),
+                array.as_mut_ptr(),
+                schema.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, MURR_OK);
+
+        let (array, schema) = unsafe { (array.assume_init(), schema.assume_init()) };
+        let data = unsafe { from_ffi(array, &schema) }.unwrap();
+        let result = StructArray::from(data);
+        let scores = result
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        assert_eq!(scores.value(0), 1.5);
+        assert!(scores.is_null(1));
+
+        let missing = CString::new("missing").unwrap();
+        let mut array = MaybeUninit::<FFI_ArrowArray>::uninit();
+        let mut schema = MaybeUninit::<FFI_ArrowSchema>::uninit();
+        let status = unsafe {
+            murr_read(
+                handle,
+                missing.as_ptr(),
+                key_ptrs.as_ptr(),
+                key_ptrs.len(),
+                column_ptrs.as_ptr(),
+                column_ptrs.len(),
+                array.as_mut_ptr(),
+                schema.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, MURR_ERROR);
+        let message = unsafe { CStr::from_ptr(murr_last_error()) };
+        assert!(message.to_str().unwrap().contains("missing"));
+
+        unsafe { murr_close(handle) };
+    }
+}
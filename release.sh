@@ -5,11 +5,12 @@ VERSION="${1:?Usage: ./release.sh <version>}"
 
 # Update the single source of truth: workspace version in root Cargo.toml
 sed -i "s/^version = \".*\"/version = \"$VERSION\"/" Cargo.toml
+sed -i "s/^version = \".*\"/version = \"$VERSION\"/" ffi/Cargo.toml
 
 # Validate everything compiles (also updates Cargo.lock)
 cargo check --all-targets
 
-git add Cargo.toml Cargo.lock
+git add Cargo.toml ffi/Cargo.toml Cargo.lock
 git commit -m "release v$VERSION"
 git tag "v$VERSION"
 
@@ -0,0 +1,137 @@
+use arrow_flight::{Action, ActionType};
+use serde::Deserialize;
+use tonic::Status;
+
+use crate::core::TableSchema;
+
+/// Admin operations served by `DoAction`, the Flight side of the HTTP admin
+/// routes. Each action's body is a JSON object naming the table.
+#[derive(Debug, PartialEq)]
+pub enum AdminAction {
+    /// `{"table": ..., "schema": <TableSchema>}`, like `PUT /api/v1/table/{name}`.
+    CreateTable { table: String, schema: TableSchema },
+    /// `{"table": ...}`: a full compaction, like `POST .../rebuild`.
+    Compact { table: String },
+    /// `{"table": ...}`: one result holding the `TableStats` JSON.
+    Stats { table: String },
+    /// `{"table": ...}`, like `DELETE /api/v1/table/{name}`.
+    DropTable { table: String },
+    /// `{"table": ...}`, like `POST .../truncate`.
+    Truncate { table: String },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CreateTableBody {
+    table: String,
+    schema: TableSchema,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TableBody {
+    table: String,
+}
+
+impl AdminAction {
+    pub const TYPES: [(&'static str, &'static str); 5] = [
+        (
+            "create_table",
+            "Create a table. Body: {\"table\": name, \"schema\": TableSchema}",
+        ),
+        ("compact", "Fully compact a table. Body: {\"table\": name}"),
+        ("stats", "Table stats as JSON. Body: {\"table\": name}"),
+        ("drop_table", "Drop a table. Body: {\"table\": name}"),
+        (
+            "truncate_table",
+            "Remove every row of a table. Body: {\"table\": name}",
+        ),
+    ];
+
+    pub fn types() -> Vec<ActionType> {
+        Self::TYPES
+            .iter()
+            .map(|(r#type, description)| ActionType {
+                r#type: r#type.to_string(),
+                description: description.to_string(),
+            })
+            .collect()
+    }
+
+    pub fn parse(action: &Action) -> Result<Self, Status> {
+        let invalid = |e: serde_json::Error| {
+            Status::invalid_argument(format!("invalid {} body: {e}", action.r#type))
+        };
+        match action.r#type.as_str() {
+            "create_table" => {
+                let body: CreateTableBody =
+                    serde_json::from_slice(&action.body).map_err(invalid)?;
+                Ok(AdminAction::CreateTable {
+                    table: body.table,
+                    schema: body.schema,
+                })
+            }
+            "compact" => {
+                let body: TableBody = serde_json::from_slice(&action.body).map_err(invalid)?;
+                Ok(AdminAction::Compact { table: body.table })
+            }
+            "stats" => {
+                let body: TableBody = serde_json::from_slice(&action.body).map_err(invalid)?;
+                Ok(AdminAction::Stats { table: body.table })
+            }
+            "drop_table" => {
+                let body: TableBody = serde_json::from_slice(&action.body).map_err(invalid)?;
+                Ok(AdminAction::DropTable { table: body.table })
+            }
+            "truncate_table" => {
+                let body: TableBody = serde_json::from_slice(&action.body).map_err(invalid)?;
+                Ok(AdminAction::Truncate { table: body.table })
+            }
+            other => Err(Status::unimplemented(format!("unknown action '{other}'"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(r#type: &str, body: &str) -> Action {
+        Action::new(r#type, body.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_parses_table_actions() {
+        assert_eq!(
+            AdminAction::parse(&action("compact", r#"{"table": "t"}"#)).unwrap(),
+            AdminAction::Compact {
+                table: "t".to_string()
+            }
+        );
+        let parsed = AdminAction::parse(&action(
+            "create_table",
+            r#"{"table": "t", "schema": {"key": "id", "columns": {"id": {"dtype": "utf8"}}}}"#,
+        ))
+        .unwrap();
+        assert!(
+            matches!(parsed, AdminAction::CreateTable { table, schema } if table == "t" && schema.key == "id")
+        );
+        assert_eq!(
+            AdminAction::parse(&action("truncate_table", r#"{"table": "t"}"#)).unwrap(),
+            AdminAction::Truncate {
+                table: "t".to_string()
+            }
+        );
+        assert_eq!(AdminAction::types().len(), AdminAction::TYPES.len());
+    }
+
+    #[test]
+    fn test_rejects_unknown_actions_and_bodies() {
+        let err = AdminAction::parse(&action("flush", r#"{"table": "t"}"#)).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unimplemented);
+        let err = AdminAction::parse(&action("stats", r#"{"name": "t"}"#)).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        let err = AdminAction::parse(&action("create_table", r#"{"table": "t"}"#)).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -5,11 +5,16 @@ use crate::core::MurrError;
 impl From<MurrError> for Status {
     fn from(err: MurrError) -> Status {
         match err {
-            MurrError::TableNotFound(msg) => Status::not_found(msg),
+            MurrError::TableNotFound(msg) | MurrError::KeyNotFound(msg) => Status::not_found(msg),
             MurrError::TableAlreadyExists(msg) => Status::already_exists(msg),
             MurrError::TableError(msg) | MurrError::SegmentError(msg) => {
                 Status::invalid_argument(msg)
             }
+            MurrError::ResponseTooLarge(msg) | MurrError::RateLimited(msg) => {
+                Status::resource_exhausted(msg)
+            }
+            MurrError::Overloaded(msg) | MurrError::TableLoading(msg) => Status::unavailable(msg),
+            MurrError::ReadOnly(msg) => Status::permission_denied(msg),
             MurrError::IoError(msg)
             | MurrError::ArrowError(msg)
             | MurrError::ConfigParsingError(msg) => Status::internal(msg),
@@ -1,26 +1,39 @@
+mod action;
 mod error;
 mod ticket;
 
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
-use arrow::datatypes::Schema;
+use arrow::array::{ArrayRef, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, FieldRef, Schema};
 use arrow::ipc::writer::IpcWriteOptions;
+use arrow_flight::decode::{DecodedPayload, FlightDataDecoder};
 use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
 use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
 use arrow_flight::{
-    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightInfo,
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
     HandshakeRequest, HandshakeResponse, PollInfo, PutResult, SchemaAsIpc, SchemaResult, Ticket,
 };
-use futures::stream::{self, Stream, StreamExt};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
 use tonic::transport::Server;
+use tonic::transport::server::Router;
 use tonic::{Request, Response, Status, Streaming};
 
-use crate::core::MurrError;
+use crate::core::{DTypeName, MurrError};
+use crate::io::codec::utf8::to_string_view;
 use crate::io::store::Store;
-use crate::service::MurrService;
+use crate::io::table::ReadEstimate;
+use crate::io::timing;
+use crate::service::{MurrService, PRIORITY_HEADER, Priority};
+use action::AdminAction;
 use log::info;
-use ticket::FetchTicket;
+use ticket::{ExchangeCommand, FetchTicket, OnMissing};
+
+/// How often the health service checks whether background loading is done.
+const READY_POLL: Duration = Duration::from_millis(100);
 
 pub struct MurrFlightService<S: Store> {
     service: Arc<MurrService<S>>,
@@ -41,15 +54,119 @@ impl<S: Store> MurrFlightService<S> {
             .parse()
             .map_err(|e| MurrError::ConfigParsingError(format!("invalid address: {e}")))?;
         info!("Listening for Flight/gRPC requests on {addr}");
-        Server::builder()
-            .tcp_nodelay(true)
-            .add_service(FlightServiceServer::new(self))
+        self.router()
+            .await
             .serve(addr)
             .await
             .map_err(|e| MurrError::IoError(format!("Flight server error: {e}")))?;
 
         Ok(())
     }
+
+    /// The Flight service next to the standard `grpc.health.v1.Health`
+    /// service, for gRPC probes. Health reports the server (`""`) as SERVING
+    /// and the Flight service as NOT_SERVING until background table loading
+    /// is done, like HTTP `/ready`.
+    pub async fn router(self) -> Router {
+        let (health, health_service) = tonic_health::server::health_reporter();
+        let service = self.service.clone();
+        if service.load_progress().ready {
+            health.set_serving::<FlightServiceServer<Self>>().await;
+        } else {
+            health.set_not_serving::<FlightServiceServer<Self>>().await;
+            tokio::spawn(async move {
+                let mut tick = tokio::time::interval(READY_POLL);
+                while !service.load_progress().ready {
+                    tick.tick().await;
+                }
+                health.set_serving::<FlightServiceServer<Self>>().await;
+            });
+        }
+        Server::builder()
+            .tcp_nodelay(true)
+            .add_service(health_service)
+            .add_service(FlightServiceServer::new(self))
+    }
+
+    /// `GetFlightInfo` for a `FetchTicket` sent as the descriptor's `cmd`:
+    /// `total_records` and `total_bytes` estimate the result without reading
+    /// it, and the one endpoint carries the ticket for `DoGet`.
+    async fn ticket_info(
+        &self,
+        descriptor: FlightDescriptor,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let fetch: FetchTicket = serde_json::from_slice(&descriptor.cmd)
+            .map_err(|e| Status::invalid_argument(format!("invalid ticket JSON: {e}")))?;
+        let service = self.service.clone();
+        let estimate = tokio::task::spawn_blocking(move || estimate_ticket(&service, &fetch))
+            .await
+            .map_err(join_to_status)?
+            .map_err(Status::from)?;
+        let endpoint = FlightEndpoint::new().with_ticket(Ticket::new(descriptor.cmd.clone()));
+        let info = FlightInfo::new()
+            .with_descriptor(descriptor)
+            .with_endpoint(endpoint)
+            .with_total_records(estimate.rows as i64)
+            .with_total_bytes(estimate.bytes as i64);
+        Ok(Response::new(info))
+    }
+
+    /// Serves a large ticket `grpc.stream_chunk_keys` keys at a time. The
+    /// encoder only pulls the next chunk once the previous batches are
+    /// handed to gRPC, which stops polling while the client isn't reading,
+    /// so memory stays bounded by one chunk whatever the ticket size.
+    async fn stream_ticket(
+        &self,
+        fetch: FetchTicket,
+        priority: Priority,
+    ) -> Result<Response<BoxStream<FlightData>>, Status> {
+        let grpc = &self.service.config().server.grpc;
+        let keys = fetch.string_keys()?;
+        // A missing key must fail the request before any batch is sent.
+        let keys = if fetch.on_missing == OnMissing::Error {
+            let service = self.service.clone();
+            let table = fetch.table.clone();
+            tokio::task::spawn_blocking(move || {
+                service.run(priority, || {
+                    let refs: Vec<&str> = keys.iter().map(String::as_str).collect();
+                    check_missing(&table, &refs, &service.contains(&table, &refs)?)?;
+                    Ok::<_, MurrError>(keys)
+                })
+            })
+            .await
+            .map_err(join_to_status)??
+        } else {
+            keys
+        };
+
+        let mut keys = keys.into_iter();
+        let chunk_keys = grpc.stream_chunk_keys;
+        let chunks = std::iter::from_fn(move || {
+            let chunk: Vec<String> = keys.by_ref().take(chunk_keys).collect();
+            (!chunk.is_empty()).then_some(chunk)
+        });
+        let fetch = Arc::new(fetch);
+        let service = self.service.clone();
+        let batches = stream::iter(chunks).then(move |chunk| {
+            let service = service.clone();
+            let ticket = fetch.with_keys(chunk);
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    service.run(priority, || fetch_ticket(&service, &ticket))
+                })
+                .await
+                .map_err(join_to_status)
+                .and_then(|result| result.map_err(Status::from))
+                .map_err(FlightError::from)
+            }
+        });
+        let stream = FlightDataEncoderBuilder::new()
+            .with_max_flight_data_size(grpc.max_batch_bytes)
+            .build(batches)
+            .map(|result| result.map_err(|e| e.into()));
+
+        Ok(Response::new(Box::pin(stream)))
+    }
 }
 
 type BoxStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
@@ -68,21 +185,35 @@ impl<S: Store> FlightService for MurrFlightService<S> {
         &self,
         request: Request<Ticket>,
     ) -> Result<Response<Self::DoGetStream>, Status> {
+        self.service.admit(&client_id(&request))?;
+        let priority = request_priority(&request)?;
         let ticket = request.into_inner();
         let fetch: FetchTicket = serde_json::from_slice(&ticket.ticket)
             .map_err(|e| Status::invalid_argument(format!("invalid ticket JSON: {e}")))?;
+        // Debug timings describe one read, so those tickets are never split.
+        if !fetch.debug && fetch.keys.len() > self.service.config().server.grpc.stream_chunk_keys {
+            return self.stream_ticket(fetch, priority).await;
+        }
 
         let service = self.service.clone();
-        let batch = tokio::task::spawn_blocking(move || {
-            let keys: Vec<&str> = fetch.keys.iter().map(String::as_str).collect();
-            let columns: Vec<&str> = fetch.columns.iter().map(String::as_str).collect();
-            service.read(&fetch.table, &keys, &columns)
+        let (batch, timings) = tokio::task::spawn_blocking(move || {
+            service.run(priority, || {
+                let (batch, timings) =
+                    timing::profile(fetch.debug, || fetch_ticket(&service, &fetch));
+                batch.map(|batch| (batch, timings))
+            })
         })
         .await
         .map_err(join_to_status)?
         .map_err(Status::from)?;
 
-        let stream = FlightDataEncoderBuilder::new()
+        let mut encoder = FlightDataEncoderBuilder::new()
+            .with_max_flight_data_size(self.service.config().server.grpc.max_batch_bytes);
+        if let Some(timings) = timings {
+            let json = serde_json::to_vec(&timings).map_err(|e| Status::internal(e.to_string()))?;
+            encoder = encoder.with_metadata(json.into());
+        }
+        let stream = encoder
             .build(stream::once(async { Ok(batch) }))
             .map(|result| result.map_err(|e| e.into()));
 
@@ -94,6 +225,9 @@ impl<S: Store> FlightService for MurrFlightService<S> {
         request: Request<FlightDescriptor>,
     ) -> Result<Response<FlightInfo>, Status> {
         let descriptor = request.into_inner();
+        if !descriptor.cmd.is_empty() {
+            return self.ticket_info(descriptor).await;
+        }
         let table_name = descriptor
             .path
             .first()
@@ -177,33 +311,350 @@ impl<S: Store> FlightService for MurrFlightService<S> {
         Err(Status::unimplemented("poll_flight_info not supported"))
     }
 
+    /// Streaming writes: the first message's descriptor `path` names the
+    /// table, and every record batch that follows is written to it as it
+    /// arrives, with the same checks as an HTTP write. Each batch is
+    /// acknowledged with a `PutResult` whose `app_metadata` is
+    /// `{"rows": <n>}`. A failed batch ends the stream with its error;
+    /// batches acknowledged before it stay written.
     async fn do_put(
         &self,
-        _request: Request<Streaming<FlightData>>,
+        request: Request<Streaming<FlightData>>,
     ) -> Result<Response<Self::DoPutStream>, Status> {
-        Err(Status::unimplemented("do_put not supported"))
+        self.service.admit(&client_id(&request))?;
+        let input = FlightDataDecoder::new(
+            request
+                .into_inner()
+                .map_err(|e| FlightError::Tonic(Box::new(e))),
+        );
+
+        let service = self.service.clone();
+        let results = stream::unfold((input, None::<String>), move |(mut input, mut table)| {
+            let service = service.clone();
+            async move {
+                loop {
+                    let decoded = match input.next().await? {
+                        Ok(decoded) => decoded,
+                        Err(e) => return Some((Err(e.into()), (input, table))),
+                    };
+                    if table.is_none() {
+                        if let Some(descriptor) = &decoded.inner.flight_descriptor {
+                            match descriptor.path.first() {
+                                Some(name) => table = Some(name.clone()),
+                                None => {
+                                    let status =
+                                        Status::invalid_argument("path must contain table name");
+                                    return Some((Err(status), (input, table)));
+                                }
+                            }
+                        }
+                    }
+                    let DecodedPayload::RecordBatch(batch) = decoded.payload else {
+                        continue;
+                    };
+                    let Some(name) = table.clone() else {
+                        let status = Status::invalid_argument(
+                            "put stream has no table: set the descriptor path on the first message",
+                        );
+                        return Some((Err(status), (input, table)));
+                    };
+                    let rows = batch.num_rows();
+                    let result = tokio::task::spawn_blocking(move || service.write(&name, &batch))
+                        .await
+                        .map_err(join_to_status)
+                        .and_then(|result| result.map_err(Status::from))
+                        .map(|()| PutResult {
+                            app_metadata: format!("{{\"rows\":{rows}}}").into(),
+                        });
+                    return Some((result, (input, table)));
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(results)))
     }
 
+    /// Streaming lookups: the first message's descriptor `cmd` carries an
+    /// `ExchangeCommand`, every record batch that follows carries keys in its
+    /// first (utf8) column, and each is answered with one result batch on the
+    /// same stream, in order. A batch whose `app_metadata` holds its own
+    /// `ExchangeCommand` is looked up with that instead, so one stream can
+    /// interleave tables; the metadata is echoed on the result.
     async fn do_exchange(
         &self,
-        _request: Request<Streaming<FlightData>>,
+        request: Request<Streaming<FlightData>>,
     ) -> Result<Response<Self::DoExchangeStream>, Status> {
-        Err(Status::unimplemented("do_exchange not supported"))
+        let client = client_id(&request);
+        self.service.admit(&client)?;
+        let priority = request_priority(&request)?;
+        let input = FlightDataDecoder::new(
+            request
+                .into_inner()
+                .map_err(|e| FlightError::Tonic(Box::new(e))),
+        );
+
+        let service = self.service.clone();
+        let results = stream::unfold(
+            (input, None::<Arc<ExchangeCommand>>),
+            move |(mut input, mut default)| {
+                let service = service.clone();
+                let client = client.clone();
+                async move {
+                    loop {
+                        let decoded = match input.next().await? {
+                            Ok(decoded) => decoded,
+                            Err(e) => return Some((Err(e), (input, default))),
+                        };
+                        if default.is_none() {
+                            if let Some(descriptor) = &decoded.inner.flight_descriptor {
+                                match parse_command(&descriptor.cmd) {
+                                    Ok(parsed) => default = Some(Arc::new(parsed)),
+                                    Err(e) => return Some((Err(e), (input, default))),
+                                }
+                            }
+                        }
+                        let DecodedPayload::RecordBatch(keys) = decoded.payload else {
+                            continue;
+                        };
+                        let metadata = decoded.inner.app_metadata;
+                        let command = if metadata.is_empty() {
+                            match &default {
+                                Some(command) => command.clone(),
+                                None => {
+                                    let status = Status::invalid_argument(
+                                        "exchange batch has no command: set the descriptor cmd or the batch app_metadata",
+                                    );
+                                    return Some((Err(status.into()), (input, default)));
+                                }
+                            }
+                        } else {
+                            match parse_command(&metadata) {
+                                Ok(parsed) => Arc::new(parsed),
+                                Err(e) => return Some((Err(e), (input, default))),
+                            }
+                        };
+                        let result = tokio::task::spawn_blocking(move || {
+                            service.admit(&client)?;
+                            let ticket = command.ticket(exchange_keys(&keys)?);
+                            service.run(priority, || fetch_ticket(&service, &ticket))
+                        })
+                        .await
+                        .map_err(join_to_status)
+                        .and_then(|result| result.map_err(Status::from))
+                        .map(|batch| (metadata, batch))
+                        .map_err(FlightError::from);
+                        return Some((result, (input, default)));
+                    }
+                }
+            },
+        );
+
+        // Each result is encoded on its own, schema message included, because
+        // consecutive results may come from different tables.
+        let max_batch_bytes = self.service.config().server.grpc.max_batch_bytes;
+        let stream = results
+            .flat_map(move |result| match result {
+                Ok((metadata, batch)) => FlightDataEncoderBuilder::new()
+                    .with_max_flight_data_size(max_batch_bytes)
+                    .with_metadata(metadata)
+                    .build(stream::once(async { Ok(batch) }))
+                    .boxed(),
+                Err(e) => stream::once(async { Err(e) }).boxed(),
+            })
+            .map(|result| result.map_err(|e| e.into()));
+
+        Ok(Response::new(Box::pin(stream)))
     }
 
+    /// Admin operations; see `AdminAction`. With `server.admin` set, admin
+    /// operations stay on that listener and every action is refused here.
     async fn do_action(
         &self,
-        _request: Request<Action>,
+        request: Request<Action>,
     ) -> Result<Response<Self::DoActionStream>, Status> {
-        Err(Status::unimplemented("do_action not supported"))
+        if self.service.config().server.admin.is_some() {
+            return Err(Status::permission_denied(
+                "admin actions are served on server.admin",
+            ));
+        }
+        let action = AdminAction::parse(request.get_ref())?;
+        let service = self.service.clone();
+        let results = tokio::task::spawn_blocking(move || match action {
+            AdminAction::CreateTable { table, schema } => {
+                service.create(&table, schema).map(|()| vec![])
+            }
+            AdminAction::Compact { table } => service
+                .run(Priority::Batch, || service.rebuild(&table))
+                .map(|()| vec![]),
+            AdminAction::Stats { table } => {
+                let stats = service.stats(&table)?;
+                let json =
+                    serde_json::to_vec(&stats).map_err(|e| MurrError::IoError(e.to_string()))?;
+                Ok(vec![Ok(arrow_flight::Result::new(json))])
+            }
+            AdminAction::DropTable { table } => service.drop_table(&table).map(|()| vec![]),
+            AdminAction::Truncate { table } => service.truncate(&table).map(|()| vec![]),
+        })
+        .await
+        .map_err(join_to_status)?
+        .map_err(Status::from)?;
+
+        Ok(Response::new(Box::pin(stream::iter(results))))
     }
 
     async fn list_actions(
         &self,
         _request: Request<Empty>,
     ) -> Result<Response<Self::ListActionsStream>, Status> {
-        Err(Status::unimplemented("list_actions not supported"))
+        let types = if self.service.config().server.admin.is_some() {
+            vec![]
+        } else {
+            AdminAction::types()
+        };
+        Ok(Response::new(Box::pin(stream::iter(
+            types.into_iter().map(Ok),
+        ))))
+    }
+}
+
+/// Sizes a ticket's `DoGet` result. `on_missing: skip` can only make it
+/// smaller; `include_key` adds the keys as sent.
+fn estimate_ticket<S: Store>(
+    service: &MurrService<S>,
+    fetch: &FetchTicket,
+) -> Result<ReadEstimate, MurrError> {
+    let keys = fetch.string_keys()?;
+    let columns: Vec<&str> = fetch.columns.iter().map(String::as_str).collect();
+    let mut estimate = service.estimate_read(
+        &fetch.table,
+        keys.len(),
+        &columns,
+        fetch.ignore_unknown_columns,
+    )?;
+    if fetch.include_key {
+        let schema = service.get_schema(&fetch.table)?;
+        estimate.bytes += match schema.columns[&schema.key].dtype {
+            DTypeName::Utf8 => {
+                let values: usize = keys.iter().map(String::len).sum();
+                values + 4 * keys.len() + keys.len().div_ceil(8)
+            }
+            _ => 8 * keys.len(),
+        } as u64;
+    }
+    Ok(estimate)
+}
+
+/// Runs one ticket against the service, applying its per-request options.
+/// `on_missing` checks membership on the table itself; key aliases are only
+/// consulted by the read.
+fn fetch_ticket<S: Store>(
+    service: &MurrService<S>,
+    fetch: &FetchTicket,
+) -> Result<RecordBatch, MurrError> {
+    let keys = fetch.string_keys()?;
+    let mut keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    if fetch.on_missing != OnMissing::Null {
+        let found = service.contains(&fetch.table, &keys)?;
+        if fetch.on_missing == OnMissing::Error {
+            check_missing(&fetch.table, &keys, &found)?;
+        }
+        keys = keys
+            .into_iter()
+            .zip(found)
+            .filter(|(_, found)| *found)
+            .map(|(key, _)| key)
+            .collect();
+    }
+    let columns: Vec<&str> = fetch.columns.iter().map(String::as_str).collect();
+    let mut batch = if fetch.ignore_unknown_columns {
+        service.read_lenient(&fetch.table, &keys, &columns)?
+    } else {
+        service.read(&fetch.table, &keys, &columns)?
+    };
+    if fetch.include_key {
+        let (field, column) = service.key_column(&fetch.table, &keys)?;
+        batch = with_key_column(&batch, field, column)?;
+    }
+    if fetch.string_view {
+        to_string_view(&batch)
+    } else {
+        Ok(batch)
+    }
+}
+
+fn check_missing(table: &str, keys: &[&str], found: &[bool]) -> Result<(), MurrError> {
+    let missing = found.iter().filter(|found| !**found).count();
+    if missing > 0 {
+        return Err(MurrError::KeyNotFound(format!(
+            "{missing} of {} keys not found in '{table}'",
+            keys.len()
+        )));
     }
+    Ok(())
+}
+
+fn parse_command(bytes: &[u8]) -> Result<ExchangeCommand, FlightError> {
+    serde_json::from_slice(bytes)
+        .map_err(|e| Status::invalid_argument(format!("invalid exchange command JSON: {e}")).into())
+}
+
+/// Keys of one `DoExchange` batch: its first column, utf8, int64 or uint64
+/// without nulls. Integer keys are passed on in decimal, as in tickets.
+fn exchange_keys(batch: &RecordBatch) -> Result<Vec<String>, MurrError> {
+    let column = batch
+        .columns()
+        .first()
+        .filter(|c| {
+            matches!(
+                c.data_type(),
+                DataType::Utf8 | DataType::Int64 | DataType::UInt64
+            )
+        })
+        .ok_or_else(|| {
+            MurrError::TableError(
+                "exchange batches need a utf8, int64 or uint64 key column first".to_string(),
+            )
+        })?;
+    let column = arrow::compute::cast(column, &DataType::Utf8)?;
+    let keys = column
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .expect("cast to utf8 returns a StringArray");
+    if keys.null_count() > 0 {
+        return Err(MurrError::TableError(
+            "exchange key column must not contain nulls".to_string(),
+        ));
+    }
+    Ok(keys.iter().flatten().map(str::to_string).collect())
+}
+
+fn with_key_column(
+    batch: &RecordBatch,
+    field: Field,
+    column: ArrayRef,
+) -> Result<RecordBatch, MurrError> {
+    let schema = batch.schema();
+    let mut fields: Vec<FieldRef> = vec![Arc::new(field)];
+    fields.extend(schema.fields().iter().cloned());
+    let mut columns: Vec<ArrayRef> = vec![column];
+    columns.extend(batch.columns().iter().cloned());
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+fn request_priority<T>(request: &Request<T>) -> Result<Priority, MurrError> {
+    match request.metadata().get(PRIORITY_HEADER) {
+        Some(v) => v.to_str().unwrap_or_default().parse(),
+        None => Ok(Priority::default()),
+    }
+}
+
+/// The peer IP, as for HTTP (see `api::http::limit`).
+fn client_id<T>(request: &Request<T>) -> String {
+    request
+        .remote_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_default()
 }
 
 fn join_to_status(e: tokio::task::JoinError) -> Status {
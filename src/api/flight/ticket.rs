@@ -1,10 +1,171 @@
 use serde::{Deserialize, Serialize};
 
+use crate::core::MurrError;
+
+/// Newest ticket version this server understands. Tickets without a
+/// `version` field are version 1.
+pub const TICKET_VERSION: u32 = 1;
+
+/// JSON ticket for `DoGet`. This is a wire format shared with every client:
+/// new fields must be optional with a default that keeps the old behavior,
+/// and existing fields never change meaning. Bump `TICKET_VERSION` only for
+/// changes old servers must reject rather than ignore.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FetchTicket {
+    #[serde(default = "FetchTicket::default_version")]
+    pub version: u32,
+    pub table: String,
+    pub keys: Vec<TicketKey>,
+    pub columns: Vec<String>,
+    /// How `keys` are encoded. Integer keys are passed on in decimal, which
+    /// tables with an int64 or uint64 key parse back to numbers and utf8-key
+    /// tables look up as is.
+    #[serde(default)]
+    pub key_type: KeyType,
+    /// Prepend the key column, in the table's key type, to the result.
+    #[serde(default)]
+    pub include_key: bool,
+    #[serde(default)]
+    pub on_missing: OnMissing,
+    #[serde(default)]
+    pub format: TicketFormat,
+    /// Return utf8 columns as Utf8View (`StringViewArray`) instead of Utf8.
+    #[serde(default)]
+    pub string_view: bool,
+    /// Skip requested columns the table doesn't have; they are listed under
+    /// the `murr.unknown_columns` schema metadata key.
+    #[serde(default)]
+    pub ignore_unknown_columns: bool,
+    /// Attach a read-path timing breakdown (`ReadTimings` JSON) as the
+    /// `app_metadata` of the schema message.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum TicketKey {
+    String(String),
+    Int(i64),
+    /// Integers above `i64::MAX`, for uint64 keys.
+    UInt(u64),
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+    #[default]
+    String,
+    Int,
+}
+
+/// What to do with keys the table doesn't have.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnMissing {
+    /// Keep one row per requested key, nulls for missing ones.
+    #[default]
+    Null,
+    /// Drop rows for missing keys; pair with `include_key` to tell which
+    /// keys came back.
+    Skip,
+    /// Fail the request with NOT_FOUND.
+    Error,
+}
+
+/// Result encoding. Only Arrow IPC today; the field exists so a future
+/// encoding is an explicit opt-in that old servers reject.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketFormat {
+    #[default]
+    Arrow,
+}
+
+impl FetchTicket {
+    fn default_version() -> u32 {
+        1
+    }
+
+    /// Rejects tickets from newer clients and keys that don't match
+    /// `key_type`, then returns the keys in their stored string form.
+    pub fn string_keys(&self) -> Result<Vec<String>, MurrError> {
+        if self.version == 0 || self.version > TICKET_VERSION {
+            return Err(MurrError::TableError(format!(
+                "unsupported ticket version {}, this server understands up to {TICKET_VERSION}",
+                self.version
+            )));
+        }
+        self.keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| match (self.key_type, key) {
+                (KeyType::String, TicketKey::String(s)) => Ok(s.clone()),
+                (KeyType::Int, TicketKey::Int(n)) => Ok(n.to_string()),
+                (KeyType::Int, TicketKey::UInt(n)) => Ok(n.to_string()),
+                (expected, _) => Err(MurrError::TableError(format!(
+                    "key at index {i} does not match key_type {expected:?}"
+                ))),
+            })
+            .collect()
+    }
+
+    /// The same ticket for another set of already-validated string keys,
+    /// e.g. one chunk of a streamed `DoGet`.
+    pub fn with_keys(&self, keys: Vec<String>) -> FetchTicket {
+        FetchTicket {
+            version: self.version,
+            table: self.table.clone(),
+            keys: keys.into_iter().map(TicketKey::String).collect(),
+            columns: self.columns.clone(),
+            key_type: KeyType::String,
+            include_key: self.include_key,
+            on_missing: self.on_missing,
+            format: self.format,
+            string_view: self.string_view,
+            ignore_unknown_columns: self.ignore_unknown_columns,
+            debug: self.debug,
+        }
+    }
+}
+
+/// Descriptor command opening a `DoExchange` stream: a `FetchTicket`
+/// without keys, which arrive as record batches on the stream instead.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExchangeCommand {
+    #[serde(default = "FetchTicket::default_version")]
+    pub version: u32,
     pub table: String,
-    pub keys: Vec<String>,
     pub columns: Vec<String>,
+    #[serde(default)]
+    pub include_key: bool,
+    #[serde(default)]
+    pub on_missing: OnMissing,
+    #[serde(default)]
+    pub format: TicketFormat,
+    #[serde(default)]
+    pub string_view: bool,
+    #[serde(default)]
+    pub ignore_unknown_columns: bool,
+}
+
+impl ExchangeCommand {
+    /// The equivalent `DoGet` ticket for one batch of keys.
+    pub fn ticket(&self, keys: Vec<String>) -> FetchTicket {
+        FetchTicket {
+            version: self.version,
+            table: self.table.clone(),
+            keys: keys.into_iter().map(TicketKey::String).collect(),
+            columns: self.columns.clone(),
+            key_type: KeyType::String,
+            include_key: self.include_key,
+            on_missing: self.on_missing,
+            format: self.format,
+            string_view: self.string_view,
+            ignore_unknown_columns: self.ignore_unknown_columns,
+            debug: false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -13,15 +174,73 @@ mod tests {
 
     #[test]
     fn test_fetch_ticket_round_trip() {
-        let ticket = FetchTicket {
-            table: "features".to_string(),
-            keys: vec!["a".to_string(), "b".to_string()],
-            columns: vec!["score".to_string()],
-        };
+        let mut ticket: FetchTicket = serde_json::from_str(
+            r#"{"version": 1, "table": "features", "keys": ["a", "b"], "columns": ["score"]}"#,
+        )
+        .unwrap();
+        ticket.string_view = true;
+        ticket.on_missing = OnMissing::Skip;
         let bytes = serde_json::to_vec(&ticket).unwrap();
         let decoded: FetchTicket = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(decoded.version, TICKET_VERSION);
         assert_eq!(decoded.table, "features");
-        assert_eq!(decoded.keys, vec!["a", "b"]);
+        assert_eq!(decoded.string_keys().unwrap(), vec!["a", "b"]);
         assert_eq!(decoded.columns, vec!["score"]);
+        assert!(decoded.string_view);
+        assert_eq!(decoded.on_missing, OnMissing::Skip);
+    }
+
+    #[test]
+    fn test_fetch_ticket_defaults_to_utf8() {
+        let json = r#"{"table": "features", "keys": ["a"], "columns": ["score"]}"#;
+        let decoded: FetchTicket = serde_json::from_str(json).unwrap();
+        assert!(!decoded.string_view);
+        assert!(!decoded.ignore_unknown_columns);
+    }
+
+    /// The ticket every client sent before versioning must keep meaning the
+    /// same thing.
+    #[test]
+    fn test_unversioned_ticket_keeps_v1_behavior() {
+        let json = r#"{"table": "t", "keys": ["a"], "columns": ["x"], "string_view": false}"#;
+        let decoded: FetchTicket = serde_json::from_str(json).unwrap();
+        assert_eq!(decoded.version, 1);
+        assert_eq!(decoded.key_type, KeyType::String);
+        assert!(!decoded.include_key);
+        assert_eq!(decoded.on_missing, OnMissing::Null);
+        assert_eq!(decoded.format, TicketFormat::Arrow);
+        assert_eq!(decoded.string_keys().unwrap(), vec!["a"]);
+    }
+
+    #[test]
+    fn test_int_keys_use_decimal_form() {
+        let json =
+            r#"{"version": 1, "table": "t", "keys": [7, -3], "columns": [], "key_type": "int"}"#;
+        let decoded: FetchTicket = serde_json::from_str(json).unwrap();
+        assert_eq!(decoded.string_keys().unwrap(), vec!["7", "-3"]);
+
+        let json =
+            r#"{"table": "t", "keys": [18446744073709551615], "columns": [], "key_type": "int"}"#;
+        let decoded: FetchTicket = serde_json::from_str(json).unwrap();
+        assert_eq!(decoded.string_keys().unwrap(), vec![u64::MAX.to_string()]);
+    }
+
+    #[test]
+    fn test_rejects_mismatched_keys_and_newer_versions() {
+        let json = r#"{"table": "t", "keys": [7], "columns": []}"#;
+        let decoded: FetchTicket = serde_json::from_str(json).unwrap();
+        assert!(decoded.string_keys().is_err());
+
+        let json = r#"{"version": 99, "table": "t", "keys": [], "columns": []}"#;
+        let decoded: FetchTicket = serde_json::from_str(json).unwrap();
+        assert!(decoded.string_keys().is_err());
+    }
+
+    #[test]
+    fn test_rejects_unknown_enum_values() {
+        let json = r#"{"table": "t", "keys": [], "columns": [], "format": "parquet"}"#;
+        assert!(serde_json::from_str::<FetchTicket>(json).is_err());
+        let json = r#"{"table": "t", "keys": [], "columns": [], "on_missing": "ignore"}"#;
+        assert!(serde_json::from_str::<FetchTicket>(json).is_err());
     }
 }
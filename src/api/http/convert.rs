@@ -8,6 +8,7 @@ use serde::Deserialize;
 use serde_json::{Map, Value};
 
 use crate::core::{DTypeName, MurrError, TableSchema};
+use crate::service::UNKNOWN_COLUMNS_METADATA;
 
 /// Newtype to implement From<&RecordBatch> (orphan rule prevents impl for serde_json::Value).
 pub struct FetchResponse(pub Value);
@@ -28,6 +29,11 @@ impl TryFrom<&RecordBatch> for FetchResponse {
 
         let mut outer = Map::new();
         outer.insert("columns".to_string(), Value::Object(columns));
+        if let Some(unknown) = schema.metadata().get(UNKNOWN_COLUMNS_METADATA) {
+            let unknown: Value = serde_json::from_str(unknown)
+                .map_err(|e| MurrError::ArrowError(format!("unknown columns metadata: {e}")))?;
+            outer.insert("unknown_columns".to_string(), unknown);
+        }
         Ok(FetchResponse(Value::Object(outer)))
     }
 }
@@ -42,10 +48,16 @@ impl WriteRequest {
         let mut fields = Vec::new();
         let mut arrays: Vec<Arc<dyn Array>> = Vec::new();
 
+        // Value columns may be left out; the table keeps their stored values.
         for (name, config) in &schema.columns {
-            let values = self.columns.get(name).ok_or_else(|| {
-                MurrError::TableError(format!("missing column '{}' in write payload", name))
-            })?;
+            let Some(values) = self.columns.get(name) else {
+                if *name == schema.key {
+                    return Err(MurrError::TableError(format!(
+                        "missing key column '{name}' in write payload"
+                    )));
+                }
+                continue;
+            };
 
             let codec = config.dtype.codec();
             fields.push(Field::new(name, codec.arrow_dtype(), config.nullable));
@@ -74,6 +86,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: false,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -81,6 +94,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Float32,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -88,11 +102,15 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Float64,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         TableSchema {
             key: "name".to_string(),
             columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
         }
     }
 
@@ -178,6 +196,25 @@ mod tests {
         assert_eq!(weights.value(1), 2.72);
     }
 
+    #[test]
+    fn test_columnar_write_may_leave_out_value_columns() {
+        let mut columns = HashMap::new();
+        columns.insert("name".to_string(), vec![Value::from("alice")]);
+        columns.insert("score".to_string(), vec![Value::from(1.5)]);
+        let batch = WriteRequest { columns }
+            .into_record_batch(&test_table_schema())
+            .unwrap();
+        assert_eq!(batch.num_columns(), 2);
+        assert!(batch.column_by_name("weight").is_none());
+
+        let mut columns = HashMap::new();
+        columns.insert("score".to_string(), vec![Value::from(1.5)]);
+        let err = WriteRequest { columns }
+            .into_record_batch(&test_table_schema())
+            .unwrap_err();
+        assert!(matches!(err, MurrError::TableError(_)));
+    }
+
     #[test]
     fn test_round_trip_batch_to_json_to_batch() {
         let original = test_batch();
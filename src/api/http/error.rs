@@ -16,9 +16,15 @@ impl From<MurrError> for ApiError {
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let status = match &self.0 {
-            MurrError::TableNotFound(_) => StatusCode::NOT_FOUND,
+            MurrError::TableNotFound(_) | MurrError::KeyNotFound(_) => StatusCode::NOT_FOUND,
             MurrError::TableAlreadyExists(_) => StatusCode::CONFLICT,
             MurrError::TableError(_) | MurrError::SegmentError(_) => StatusCode::BAD_REQUEST,
+            MurrError::ResponseTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            MurrError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            MurrError::ReadOnly(_) => StatusCode::FORBIDDEN,
+            MurrError::Overloaded(_) | MurrError::TableLoading(_) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
             MurrError::IoError(_) | MurrError::ArrowError(_) | MurrError::ConfigParsingError(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
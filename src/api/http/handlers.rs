@@ -3,23 +3,32 @@ use std::sync::{Arc, LazyLock};
 
 use arrow::ipc::reader::StreamReader;
 use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
 use axum::Json;
 use axum::body::Bytes;
-use axum::extract::{Path, State};
-use axum::http::{HeaderMap, StatusCode};
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
+use indexmap::IndexMap;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::core::{MurrError, TableSchema};
-use crate::io::store::Store;
-use crate::service::MurrService;
+use crate::core::{Entity, MurrError, SchemaChange, TableSchema};
+use crate::io::store::{Store, TableStats};
+use crate::io::table::ReadEstimate;
+use crate::io::timing::{self, Phase};
+use crate::service::{
+    EntityInfo, LoadProgress, MurrService, PRIORITY_HEADER, Priority, Provisioned, PurgeReport,
+};
 
 use super::convert::{FetchResponse, WriteRequest};
 use super::error::ApiError;
 
 const ARROW_IPC_MIME: &str = "application/vnd.apache.arrow.stream";
 const PARQUET_MIME: &str = "application/vnd.apache.parquet";
+const PROMETHEUS_MIME: &str = "text/plain; version=0.0.4";
+/// Response header carrying the `ReadTimings` JSON of a `debug=true` fetch.
+pub const TIMINGS_HEADER: &str = "x-murr-timings";
 
 static OPENAPI_JSON: LazyLock<serde_json::Value> = LazyLock::new(|| {
     let yaml = include_str!("../../../openapi.yaml");
@@ -34,6 +43,32 @@ pub async fn health() -> &'static str {
     "OK"
 }
 
+/// 200 once background table loading is done, 503 with the same progress body
+/// while it is still running.
+pub async fn ready<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+) -> (StatusCode, Json<LoadProgress>) {
+    let progress = service.load_progress();
+    let status = if progress.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(progress))
+}
+
+/// `server.slo` targets as Prometheus gauges, one series per table. Empty
+/// when no table has a target.
+pub async fn metrics<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+) -> Result<Response, ApiError> {
+    let svc = service.clone();
+    let text = tokio::task::spawn_blocking(move || svc.metrics_text())
+        .await
+        .map_err(join_to_api_error)?;
+    Ok(([(axum::http::header::CONTENT_TYPE, PROMETHEUS_MIME)], text).into_response())
+}
+
 pub async fn list_tables<S: Store>(
     State(service): State<Arc<MurrService<S>>>,
 ) -> Result<Json<std::collections::HashMap<String, TableSchema>>, ApiError> {
@@ -55,57 +90,685 @@ pub async fn get_schema<S: Store>(
     Ok(Json(schema))
 }
 
+#[derive(Deserialize)]
+pub struct CreateTableQuery {
+    /// Change an existing table to match instead of reporting a conflict.
+    #[serde(default)]
+    pub allow_evolution: bool,
+}
+
+/// Idempotent: 201 when created, 200 when the table already has this schema,
+/// and 409 with the differences when it has another one. With
+/// `allow_evolution` the table is altered to match instead, on the batch
+/// pool, and the 200 carries the differences.
 pub async fn create_table<S: Store>(
     State(service): State<Arc<MurrService<S>>>,
     Path(name): Path<String>,
+    Query(query): Query<CreateTableQuery>,
     Json(schema): Json<TableSchema>,
+) -> Result<Response, ApiError> {
+    let svc = service.clone();
+    let table = name.clone();
+    let provisioned = tokio::task::spawn_blocking(move || {
+        if query.allow_evolution {
+            svc.run(Priority::Batch, || svc.provision(&table, schema, true))
+        } else {
+            svc.provision(&table, schema, false)
+        }
+    })
+    .await
+    .map_err(join_to_api_error)??;
+    Ok(match provisioned {
+        Provisioned::Created => StatusCode::CREATED.into_response(),
+        Provisioned::Unchanged => StatusCode::OK.into_response(),
+        Provisioned::Evolved(diff) => Json(serde_json::json!({"diff": diff})).into_response(),
+        Provisioned::Differs(diff) => {
+            let error = MurrError::TableAlreadyExists(format!("{name} with another schema"));
+            let body = serde_json::json!({"error": error.to_string(), "diff": diff});
+            (StatusCode::CONFLICT, Json(body)).into_response()
+        }
+    })
+}
+
+pub async fn list_entities<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+) -> Result<Json<std::collections::HashMap<String, EntityInfo>>, ApiError> {
+    let svc = service.clone();
+    let entities = tokio::task::spawn_blocking(move || svc.list_entities())
+        .await
+        .map_err(join_to_api_error)?;
+    Ok(Json(entities))
+}
+
+#[derive(Deserialize)]
+pub struct PurgeRequest {
+    pub key: RequestKey,
+}
+
+/// Runs on the batch pool: it compacts every table of the entity.
+pub async fn purge_key<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Json(request): Json<PurgeRequest>,
+) -> Result<Json<PurgeReport>, ApiError> {
+    let key = String::from(request.key);
+    let svc = service.clone();
+    let report = tokio::task::spawn_blocking(move || {
+        svc.run(Priority::Batch, || svc.purge_key(&name, &key))
+    })
+    .await
+    .map_err(join_to_api_error)??;
+    Ok(Json(report))
+}
+
+pub async fn create_entity<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Json(entity): Json<Entity>,
 ) -> Result<StatusCode, ApiError> {
     let svc = service.clone();
-    tokio::task::spawn_blocking(move || svc.create(&name, schema))
+    tokio::task::spawn_blocking(move || svc.create_entity(&name, entity))
         .await
         .map_err(join_to_api_error)??;
-    Ok(StatusCode::CREATED)
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A request key, a JSON string or integer. Integer keys are passed on in
+/// decimal, which is how tables with an int64 or uint64 key parse them.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum RequestKey {
+    String(String),
+    Int(i64),
+    UInt(u64),
+}
+
+impl From<RequestKey> for String {
+    fn from(key: RequestKey) -> Self {
+        match key {
+            RequestKey::String(key) => key,
+            RequestKey::Int(key) => key.to_string(),
+            RequestKey::UInt(key) => key.to_string(),
+        }
+    }
+}
+
+fn string_or_number_keys<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let keys = Vec::<RequestKey>::deserialize(deserializer)?;
+    Ok(keys.into_iter().map(String::from).collect())
 }
 
 #[derive(Deserialize)]
 pub struct FetchRequest {
+    #[serde(deserialize_with = "string_or_number_keys")]
     pub keys: Vec<String>,
     pub columns: Vec<String>,
+    #[serde(default)]
+    pub ignore_unknown_columns: bool,
+}
+
+#[derive(Deserialize)]
+pub struct FetchQuery {
+    /// Attach a read-path timing breakdown in `TIMINGS_HEADER`.
+    #[serde(default)]
+    pub debug: bool,
+    /// Return a `ReadEstimate` of the result instead of reading it.
+    #[serde(default)]
+    pub estimate: bool,
 }
 
 pub async fn fetch<S: Store>(
     State(service): State<Arc<MurrService<S>>>,
     Path(name): Path<String>,
+    Query(query): Query<FetchQuery>,
     headers: HeaderMap,
     Json(req): Json<FetchRequest>,
 ) -> Result<Response, ApiError> {
-    let wants_arrow = headers
+    let wants_arrow = accepts_arrow(&headers);
+    let priority = request_priority(&headers)?;
+
+    let svc = service.clone();
+    if query.estimate {
+        let estimate = tokio::task::spawn_blocking(move || {
+            let columns: Vec<&str> = req.columns.iter().map(String::as_str).collect();
+            svc.estimate_read(&name, req.keys.len(), &columns, req.ignore_unknown_columns)
+        })
+        .await
+        .map_err(join_to_api_error)??;
+        return Ok(Json(estimate).into_response());
+    }
+    tokio::task::spawn_blocking(move || {
+        svc.run(priority, || {
+            fetch_response(query.debug, wants_arrow, || {
+                let keys: Vec<&str> = req.keys.iter().map(String::as_str).collect();
+                let columns: Vec<&str> = req.columns.iter().map(String::as_str).collect();
+                if req.ignore_unknown_columns {
+                    svc.read_lenient(&name, &keys, &columns)
+                } else {
+                    svc.read(&name, &keys, &columns)
+                }
+            })
+        })
+    })
+    .await
+    .map_err(join_to_api_error)?
+}
+
+#[derive(Deserialize)]
+pub struct JoinFetchRequest {
+    #[serde(deserialize_with = "string_or_number_keys")]
+    pub keys: Vec<String>,
+    pub tables: IndexMap<String, Vec<String>>,
+}
+
+pub async fn fetch_join<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Query(query): Query<FetchQuery>,
+    headers: HeaderMap,
+    Json(req): Json<JoinFetchRequest>,
+) -> Result<Response, ApiError> {
+    let wants_arrow = accepts_arrow(&headers);
+    let priority = request_priority(&headers)?;
+
+    let svc = service.clone();
+    if query.estimate {
+        // The joined batch is every table's columns side by side.
+        let estimate = tokio::task::spawn_blocking(move || {
+            let mut bytes = 0;
+            for (table, columns) in &req.tables {
+                let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+                bytes += svc
+                    .estimate_read(table, req.keys.len(), &columns, false)?
+                    .bytes;
+            }
+            Ok::<_, MurrError>(ReadEstimate {
+                rows: req.keys.len(),
+                bytes,
+            })
+        })
+        .await
+        .map_err(join_to_api_error)??;
+        return Ok(Json(estimate).into_response());
+    }
+    tokio::task::spawn_blocking(move || {
+        svc.run(priority, || {
+            fetch_response(query.debug, wants_arrow, || {
+                let keys: Vec<&str> = req.keys.iter().map(String::as_str).collect();
+                let columns: Vec<Vec<&str>> = req
+                    .tables
+                    .values()
+                    .map(|cols| cols.iter().map(String::as_str).collect())
+                    .collect();
+                let tables: Vec<(&str, &[&str])> = req
+                    .tables
+                    .keys()
+                    .map(String::as_str)
+                    .zip(columns.iter().map(Vec::as_slice))
+                    .collect();
+                svc.read_join(&keys, &tables)
+            })
+        })
+    })
+    .await
+    .map_err(join_to_api_error)?
+}
+
+fn accepts_arrow(headers: &HeaderMap) -> bool {
+    headers
         .get("accept")
         .and_then(|v| v.to_str().ok())
-        .is_some_and(|v| v.contains(ARROW_IPC_MIME));
+        .is_some_and(|v| v.contains(ARROW_IPC_MIME))
+}
+
+fn request_priority(headers: &HeaderMap) -> Result<Priority, ApiError> {
+    match headers.get(PRIORITY_HEADER) {
+        Some(v) => Ok(v.to_str().unwrap_or_default().parse()?),
+        None => Ok(Priority::default()),
+    }
+}
+
+/// Runs `read` and encodes its batch. With `debug`, the timing breakdown
+/// of both goes into `TIMINGS_HEADER`.
+fn fetch_response(
+    debug: bool,
+    wants_arrow: bool,
+    read: impl FnOnce() -> Result<RecordBatch, MurrError>,
+) -> Result<Response, ApiError> {
+    let (response, timings) = timing::profile(debug, || -> Result<Response, ApiError> {
+        let batch = read()?;
+        timing::timed(Phase::Encode, || encode_batch(&batch, wants_arrow))
+    });
+    let mut response = response?;
+    if let Some(value) = timings
+        .and_then(|t| serde_json::to_vec(&t).ok())
+        .and_then(|json| HeaderValue::from_bytes(&json).ok())
+    {
+        response.headers_mut().insert(TIMINGS_HEADER, value);
+    }
+    Ok(response)
+}
+
+fn encode_batch(batch: &RecordBatch, wants_arrow: bool) -> Result<Response, ApiError> {
+    if wants_arrow {
+        let mut buf = Vec::new();
+        {
+            let mut writer =
+                StreamWriter::try_new(&mut buf, &batch.schema()).map_err(|e| ApiError(e.into()))?;
+            writer.write(batch).map_err(|e| ApiError(e.into()))?;
+            writer.finish().map_err(|e| ApiError(e.into()))?;
+        }
+        Ok(([(axum::http::header::CONTENT_TYPE, ARROW_IPC_MIME)], buf).into_response())
+    } else {
+        let FetchResponse(json) = FetchResponse::try_from(batch).map_err(ApiError)?;
+        Ok(Json(json).into_response())
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ContainsRequest {
+    #[serde(deserialize_with = "string_or_number_keys")]
+    pub keys: Vec<String>,
+}
 
+#[derive(Serialize)]
+pub struct ContainsResponse {
+    pub found: Vec<bool>,
+}
+
+pub async fn contains<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Json(req): Json<ContainsRequest>,
+) -> Result<Json<ContainsResponse>, ApiError> {
     let svc = service.clone();
-    tokio::task::spawn_blocking(move || -> Result<Response, ApiError> {
+    let found = tokio::task::spawn_blocking(move || {
         let keys: Vec<&str> = req.keys.iter().map(String::as_str).collect();
-        let columns: Vec<&str> = req.columns.iter().map(String::as_str).collect();
-        let batch = svc.read(&name, &keys, &columns)?;
-
-        if wants_arrow {
-            let mut buf = Vec::new();
-            {
-                let mut writer = StreamWriter::try_new(&mut buf, &batch.schema())
-                    .map_err(|e| ApiError(e.into()))?;
-                writer.write(&batch).map_err(|e| ApiError(e.into()))?;
-                writer.finish().map_err(|e| ApiError(e.into()))?;
-            }
-            Ok(([(axum::http::header::CONTENT_TYPE, ARROW_IPC_MIME)], buf).into_response())
-        } else {
-            let FetchResponse(json) = FetchResponse::try_from(&batch).map_err(ApiError)?;
-            Ok(Json(json).into_response())
-        }
+        svc.contains(&name, &keys)
     })
     .await
-    .map_err(join_to_api_error)?
+    .map_err(join_to_api_error)??;
+    Ok(Json(ContainsResponse { found }))
+}
+
+pub async fn warmup<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let svc = service.clone();
+    tokio::task::spawn_blocking(move || svc.warmup(&name))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct AdminQuery {
+    /// Report what the operation would do instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// What a rebuild would rewrite, or a drop or truncate would remove,
+/// returned for `dry_run=true`.
+#[derive(Serialize)]
+pub struct RebuildPlan {
+    pub estimated_keys: u64,
+    pub disk_bytes: u64,
+}
+
+/// Runs on the batch pool: rewriting a large table is a bulk job.
+pub async fn rebuild<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Query(query): Query<AdminQuery>,
+) -> Result<Response, ApiError> {
+    let svc = service.clone();
+    if query.dry_run {
+        let stats = tokio::task::spawn_blocking(move || svc.stats(&name))
+            .await
+            .map_err(join_to_api_error)??;
+        return Ok(Json(RebuildPlan {
+            estimated_keys: stats.estimated_keys,
+            disk_bytes: stats.disk_bytes,
+        })
+        .into_response());
+    }
+    tokio::task::spawn_blocking(move || svc.run(Priority::Batch, || svc.rebuild(&name)))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Stats of the table that would go, for `dry_run=true`.
+async fn removal_plan<S: Store>(
+    svc: Arc<MurrService<S>>,
+    name: String,
+) -> Result<Response, ApiError> {
+    let stats = tokio::task::spawn_blocking(move || svc.stats(&name))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(Json(RebuildPlan {
+        estimated_keys: stats.estimated_keys,
+        disk_bytes: stats.disk_bytes,
+    })
+    .into_response())
+}
+
+pub async fn drop_table<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Query(query): Query<AdminQuery>,
+) -> Result<Response, ApiError> {
+    let svc = service.clone();
+    if query.dry_run {
+        return removal_plan(svc, name).await;
+    }
+    tokio::task::spawn_blocking(move || svc.drop_table(&name))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+pub async fn truncate<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Query(query): Query<AdminQuery>,
+) -> Result<Response, ApiError> {
+    let svc = service.clone();
+    if query.dry_run {
+        return removal_plan(svc, name).await;
+    }
+    tokio::task::spawn_blocking(move || svc.truncate(&name))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+#[derive(Serialize)]
+pub struct AlterResponse {
+    /// Schema version after the change; the table's first schema is 0.
+    pub version: u32,
+    pub schema: TableSchema,
+}
+
+/// Runs on the batch pool: every row of the table is rewritten.
+pub async fn alter_table<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Json(change): Json<SchemaChange>,
+) -> Result<Json<AlterResponse>, ApiError> {
+    let svc = service.clone();
+    let response = tokio::task::spawn_blocking(move || {
+        svc.run(Priority::Batch, || {
+            let version = svc.alter_table(&name, &change)?;
+            let schema = svc.get_schema(&name)?;
+            Ok::<_, MurrError>(AlterResponse { version, schema })
+        })
+    })
+    .await
+    .map_err(join_to_api_error)??;
+    Ok(Json(response))
+}
+
+#[derive(Deserialize)]
+pub struct RenameRequest {
+    pub to: String,
+}
+
+pub async fn rename_table<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Json(request): Json<RenameRequest>,
+) -> Result<StatusCode, ApiError> {
+    let svc = service.clone();
+    tokio::task::spawn_blocking(move || svc.rename(&name, &request.to))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_aliases<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+) -> Result<Json<std::collections::HashMap<String, String>>, ApiError> {
+    let svc = service.clone();
+    let aliases = tokio::task::spawn_blocking(move || svc.list_aliases())
+        .await
+        .map_err(join_to_api_error)?;
+    Ok(Json(aliases))
+}
+
+#[derive(Deserialize)]
+pub struct AliasRequest {
+    pub table: String,
+}
+
+#[derive(Serialize)]
+pub struct AliasResponse {
+    /// The table the alias pointed at before, absent for a new alias.
+    pub previous: Option<String>,
+}
+
+pub async fn set_alias<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(alias): Path<String>,
+    Json(request): Json<AliasRequest>,
+) -> Result<Json<AliasResponse>, ApiError> {
+    let svc = service.clone();
+    let previous = tokio::task::spawn_blocking(move || svc.set_alias(&alias, &request.table))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(Json(AliasResponse { previous }))
+}
+
+pub async fn remove_alias<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(alias): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let svc = service.clone();
+    tokio::task::spawn_blocking(move || svc.remove_alias(&alias))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct BackfillRequest {
+    pub column: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Serialize)]
+pub struct BackfillResponse {
+    /// Rows filled, or that would be with `dry_run`.
+    pub rows: usize,
+}
+
+/// Runs on the batch pool: it reads and rewrites every affected row.
+pub async fn backfill<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Query(query): Query<AdminQuery>,
+    Json(request): Json<BackfillRequest>,
+) -> Result<Json<BackfillResponse>, ApiError> {
+    let svc = service.clone();
+    let rows = tokio::task::spawn_blocking(move || {
+        svc.run(Priority::Batch, || {
+            svc.backfill(&name, &request.column, &request.value, query.dry_run)
+        })
+    })
+    .await
+    .map_err(join_to_api_error)??;
+    Ok(Json(BackfillResponse { rows }))
+}
+
+#[derive(Serialize)]
+pub struct RefreshResponse {
+    /// Rows written to the derived table.
+    pub rows: usize,
+}
+
+/// Runs on the batch pool: it recomputes every row of the table.
+pub async fn refresh<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+) -> Result<Json<RefreshResponse>, ApiError> {
+    let svc = service.clone();
+    let rows = tokio::task::spawn_blocking(move || svc.run(Priority::Batch, || svc.refresh(&name)))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(Json(RefreshResponse { rows }))
+}
+
+pub async fn stats<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+) -> Result<Json<TableStats>, ApiError> {
+    let svc = service.clone();
+    let stats = tokio::task::spawn_blocking(move || svc.stats(&name))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(Json(stats))
+}
+
+#[derive(Serialize)]
+pub struct CountResponse {
+    pub count: usize,
+}
+
+pub async fn count<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<CountResponse>, ApiError> {
+    let priority = request_priority(&headers)?;
+    let svc = service.clone();
+    let count = tokio::task::spawn_blocking(move || svc.run(priority, || svc.count(&name)))
+        .await
+        .map_err(join_to_api_error)??;
+    Ok(Json(CountResponse { count }))
+}
+
+#[derive(Deserialize)]
+pub struct KeysQuery {
+    pub cursor: Option<String>,
+    #[serde(default = "KeysQuery::default_limit")]
+    pub limit: usize,
+}
+
+impl KeysQuery {
+    /// Largest page a single request may ask for.
+    pub const MAX_LIMIT: usize = 100_000;
+
+    fn default_limit() -> usize {
+        1000
+    }
+}
+
+#[derive(Serialize)]
+pub struct KeysResponse {
+    pub keys: Vec<String>,
+    pub next_cursor: Option<String>,
+}
+
+pub async fn keys<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<KeysQuery>,
+) -> Result<Json<KeysResponse>, ApiError> {
+    let priority = request_priority(&headers)?;
+    let limit = query.limit;
+    if limit > KeysQuery::MAX_LIMIT {
+        return Err(ApiError(MurrError::TableError(format!(
+            "limit {limit} exceeds the maximum of {}",
+            KeysQuery::MAX_LIMIT
+        ))));
+    }
+    let svc = service.clone();
+    let keys = tokio::task::spawn_blocking(move || {
+        svc.run(priority, || svc.keys(&name, query.cursor.as_deref(), limit))
+    })
+    .await
+    .map_err(join_to_api_error)??;
+    // A short page means the scan reached the end of the table.
+    let next_cursor = if keys.len() == limit && limit > 0 {
+        keys.last().cloned()
+    } else {
+        None
+    };
+    Ok(Json(KeysResponse { keys, next_cursor }))
+}
+
+#[derive(Deserialize)]
+pub struct DeleteKeysRequest {
+    #[serde(deserialize_with = "string_or_number_keys")]
+    pub keys: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct DeleteKeysResponse {
+    /// Keys that had a row; absent keys are ignored.
+    pub deleted: usize,
+}
+
+pub async fn delete_keys<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Json(request): Json<DeleteKeysRequest>,
+) -> Result<Json<DeleteKeysResponse>, ApiError> {
+    let svc = service.clone();
+    let deleted = tokio::task::spawn_blocking(move || {
+        let keys: Vec<&str> = request.keys.iter().map(String::as_str).collect();
+        svc.delete(&name, &keys)
+    })
+    .await
+    .map_err(join_to_api_error)??;
+    Ok(Json(DeleteKeysResponse { deleted }))
+}
+
+#[derive(Serialize)]
+pub struct DeactivateResponse {
+    /// Keys that had a row and are now hidden.
+    pub deactivated: usize,
+}
+
+pub async fn deactivate<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Json(request): Json<DeleteKeysRequest>,
+) -> Result<Json<DeactivateResponse>, ApiError> {
+    let svc = service.clone();
+    let deactivated = tokio::task::spawn_blocking(move || {
+        let keys: Vec<&str> = request.keys.iter().map(String::as_str).collect();
+        svc.deactivate(&name, &keys)
+    })
+    .await
+    .map_err(join_to_api_error)??;
+    Ok(Json(DeactivateResponse { deactivated }))
+}
+
+#[derive(Serialize)]
+pub struct ReactivateResponse {
+    /// Keys that were deactivated and are visible again.
+    pub reactivated: usize,
+}
+
+pub async fn reactivate<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    Path(name): Path<String>,
+    Json(request): Json<DeleteKeysRequest>,
+) -> Result<Json<ReactivateResponse>, ApiError> {
+    let svc = service.clone();
+    let reactivated = tokio::task::spawn_blocking(move || {
+        let keys: Vec<&str> = request.keys.iter().map(String::as_str).collect();
+        svc.reactivate(&name, &keys)
+    })
+    .await
+    .map_err(join_to_api_error)??;
+    Ok(Json(ReactivateResponse { reactivated }))
 }
 
 pub async fn write_table<S: Store>(
@@ -137,7 +800,10 @@ pub async fn write_table<S: Store>(
             let batches: Vec<_> = reader
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|e| ApiError(e.into()))?;
-            arrow::compute::concat_batches(&batches[0].schema(), &batches)
+            let first = batches
+                .first()
+                .ok_or_else(|| ApiError(MurrError::TableError("empty Parquet file".into())))?;
+            arrow::compute::concat_batches(&first.schema(), &batches)
                 .map_err(|e| ApiError(e.into()))?
         } else {
             let write: WriteRequest = serde_json::from_slice(&body)
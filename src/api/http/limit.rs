@@ -0,0 +1,36 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::io::store::Store;
+use crate::service::MurrService;
+
+use super::error::ApiError;
+
+/// Rejects requests over the per-client rate limit with 429 before they reach
+/// a handler. Health and readiness probes and metric scrapes are never
+/// throttled.
+pub async fn rate_limit<S: Store>(
+    State(service): State<Arc<MurrService<S>>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if !matches!(request.uri().path(), "/health" | "/ready" | "/metrics") {
+        service.admit(&client_id(&request))?;
+    }
+    Ok(next.run(request).await)
+}
+
+/// The peer IP. The `Authorization` header isn't validated anywhere, so
+/// keying on it would let a client skip the limit by sending a new value on
+/// every request.
+fn client_id(request: &Request) -> String {
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_default()
+}
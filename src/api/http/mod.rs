@@ -1,7 +1,9 @@
 mod convert;
 mod error;
 mod handlers;
+mod limit;
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use crate::core::MurrError;
@@ -9,6 +11,7 @@ use crate::io::store::Store;
 use crate::service::MurrService;
 use axum::Router;
 use axum::extract::DefaultBodyLimit;
+use axum::middleware;
 use axum::routing::{get, post, put};
 use axum::serve::ListenerExt;
 use log::info;
@@ -22,34 +25,128 @@ impl<S: Store> MurrHttpService<S> {
         Self { service }
     }
 
+    /// Data-plane routes. Without `server.admin` this also carries the admin
+    /// routes, so a single listener serves everything.
     pub fn router(&self) -> Router {
-        Router::new()
+        let router = Router::new()
             .route("/openapi.json", get(handlers::openapi))
             .route("/health", get(handlers::health))
+            .route("/ready", get(handlers::ready::<S>))
+            .route("/metrics", get(handlers::metrics::<S>))
             .route("/api/v1/table", get(handlers::list_tables::<S>))
+            .route("/api/v1/entity", get(handlers::list_entities::<S>))
+            .route("/api/v1/alias", get(handlers::list_aliases::<S>))
+            .route("/api/v1/fetch", post(handlers::fetch_join::<S>))
             .route("/api/v1/table/{name}/schema", get(handlers::get_schema::<S>))
-            .route("/api/v1/table/{name}", put(handlers::create_table::<S>))
             .route("/api/v1/table/{name}/fetch", post(handlers::fetch::<S>))
-            .route("/api/v1/table/{name}/write", put(handlers::write_table::<S>))
+            .route(
+                "/api/v1/table/{name}/contains",
+                post(handlers::contains::<S>),
+            )
+            .route("/api/v1/table/{name}/count", get(handlers::count::<S>))
+            .route(
+                "/api/v1/table/{name}/keys",
+                get(handlers::keys::<S>).delete(handlers::delete_keys::<S>),
+            )
+            .route("/api/v1/table/{name}/write", put(handlers::write_table::<S>));
+        let router = if self.service.config().server.admin.is_none() {
+            router.merge(Self::admin_routes())
+        } else {
+            router
+        };
+        let router = router.layer(DefaultBodyLimit::max(
+            self.service.config().server.http.max_payload_size,
+        ));
+        let router = if self.service.config().server.rate_limit.is_some() {
+            router.layer(middleware::from_fn_with_state(
+                self.service.clone(),
+                limit::rate_limit::<S>,
+            ))
+        } else {
+            router
+        };
+        router.with_state(self.service.clone())
+    }
+
+    /// Routes for the `server.admin` listener: table management and stats,
+    /// plus the probes and `/metrics`, so the port can be health-checked and
+    /// scraped on its own. Not rate limited; access is meant to be restricted
+    /// by network policy.
+    pub fn admin_router(&self) -> Router {
+        Router::new()
+            .route("/health", get(handlers::health))
+            .route("/ready", get(handlers::ready::<S>))
+            .route("/metrics", get(handlers::metrics::<S>))
+            .merge(Self::admin_routes())
             .layer(DefaultBodyLimit::max(
                 self.service.config().server.http.max_payload_size,
             ))
             .with_state(self.service.clone())
     }
 
+    fn admin_routes() -> Router<Arc<MurrService<S>>> {
+        Router::new()
+            .route(
+                "/api/v1/table/{name}",
+                put(handlers::create_table::<S>).delete(handlers::drop_table::<S>),
+            )
+            .route("/api/v1/entity/{name}", put(handlers::create_entity::<S>))
+            .route(
+                "/api/v1/entity/{name}/purge",
+                post(handlers::purge_key::<S>),
+            )
+            .route("/api/v1/table/{name}/stats", get(handlers::stats::<S>))
+            .route("/api/v1/table/{name}/warmup", post(handlers::warmup::<S>))
+            .route("/api/v1/table/{name}/rebuild", post(handlers::rebuild::<S>))
+            .route("/api/v1/table/{name}/backfill", post(handlers::backfill::<S>))
+            .route("/api/v1/table/{name}/refresh", post(handlers::refresh::<S>))
+            .route(
+                "/api/v1/table/{name}/truncate",
+                post(handlers::truncate::<S>),
+            )
+            .route("/api/v1/table/{name}/alter", post(handlers::alter_table::<S>))
+            .route("/api/v1/table/{name}/rename", post(handlers::rename_table::<S>))
+            .route(
+                "/api/v1/alias/{alias}",
+                put(handlers::set_alias::<S>).delete(handlers::remove_alias::<S>),
+            )
+            .route(
+                "/api/v1/table/{name}/deactivate",
+                post(handlers::deactivate::<S>),
+            )
+            .route(
+                "/api/v1/table/{name}/reactivate",
+                post(handlers::reactivate::<S>),
+            )
+    }
+
+    /// Serves `router()` on `server.http`, and `admin_router()` on
+    /// `server.admin` when it is set.
     pub async fn serve(self) -> Result<(), MurrError> {
-        let addr = self.service.config().server.http.addr();
-        let listener = tokio::net::TcpListener::bind(&addr)
-            .await
-            .map_err(|e| MurrError::IoError(format!("binding to {addr}: {e}")))?
-            .tap_io(|stream| {
-                stream.set_nodelay(true).ok();
-            });
-        info!("Listening for HTTP requests on {addr}");
-        axum::serve(listener, self.router())
-            .await
-            .map_err(|e| MurrError::IoError(format!("serving: {e}")))?;
+        let config = &self.service.config().server;
+        let data = serve_on(config.http.addr(), "HTTP", self.router());
+        match &config.admin {
+            Some(admin) => {
+                let admin = serve_on(admin.addr(), "admin HTTP", self.admin_router());
+                tokio::try_join!(data, admin)?;
+            }
+            None => data.await?,
+        }
         info!("HTTP server stopped");
         Ok(())
     }
 }
+
+async fn serve_on(addr: String, what: &str, router: Router) -> Result<(), MurrError> {
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .map_err(|e| MurrError::IoError(format!("binding to {addr}: {e}")))?
+        .tap_io(|stream| {
+            stream.set_nodelay(true).ok();
+        });
+    info!("Listening for {what} requests on {addr}");
+    let app = router.into_make_service_with_connect_info::<SocketAddr>();
+    axum::serve(listener, app)
+        .await
+        .map_err(|e| MurrError::IoError(format!("serving: {e}")))
+}
@@ -1,5 +1,5 @@
 use crate::{
-    conf::{ServerConfig, StorageConfig},
+    conf::{BackendConfig, ServerConfig, StorageConfig},
     core::{
         CliArgs,
         MurrError::{self, ConfigParsingError},
@@ -31,11 +31,161 @@ impl Config {
                 .try_parsing(true),
         );
 
-        builder
+        for (key, value) in &args.overrides {
+            builder = builder
+                .set_override(key, value.as_str())
+                .map_err(|e| ConfigParsingError(format!("--set {key}: {e}")))?;
+        }
+        if args.read_only {
+            builder = builder
+                .set_override("server.read_only", true)
+                .map_err(|e| ConfigParsingError(format!("--read-only: {e}")))?;
+        }
+
+        let config = builder
             .build()
             .map_err(|e| ConfigParsingError(e.to_string()))?
             .try_deserialize::<Config>()
-            .map_err(|e| ConfigParsingError(e.to_string()))
+            .map_err(|e| ConfigParsingError(e.to_string()))?;
+        let problems = config.problems();
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigParsingError(format!(
+                "{} problem(s):\n  {}",
+                problems.len(),
+                problems.join("\n  ")
+            )))
+        }
+    }
+
+    /// The effective configuration as YAML, for `--print-config`.
+    pub fn to_yaml(&self) -> Result<String, MurrError> {
+        serde_yaml_ng::to_string(self).map_err(|e| ConfigParsingError(e.to_string()))
+    }
+
+    /// Settings that parse but can't work together, each prefixed with its
+    /// path in the YAML.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let server = &self.server;
+        let any_host = |host: &str| host == "0.0.0.0";
+        let clash = |(host_a, port_a): (&str, u16), (host_b, port_b): (&str, u16)| {
+            port_a == port_b && (host_a == host_b || any_host(host_a) || any_host(host_b))
+        };
+        let http = (server.http.host.as_str(), server.http.port);
+        let grpc = (server.grpc.host.as_str(), server.grpc.port);
+        if clash(http, grpc) {
+            problems.push(format!(
+                "server.grpc.port: {} is also used by server.http.port",
+                server.grpc.port
+            ));
+        }
+        if let Some(admin) = &server.admin {
+            let listener = (admin.host.as_str(), admin.port);
+            for (other, name) in [(http, "server.http.port"), (grpc, "server.grpc.port")] {
+                if clash(listener, other) {
+                    problems.push(format!(
+                        "server.admin.port: {} is also used by {name}",
+                        admin.port
+                    ));
+                }
+            }
+        }
+        if server.grpc.max_batch_bytes == 0 {
+            problems.push("server.grpc.max_batch_bytes: must be at least 1".to_string());
+        }
+        if server.grpc.stream_chunk_keys == 0 {
+            problems.push("server.grpc.stream_chunk_keys: must be at least 1".to_string());
+        }
+        if server.batch_threads == 0 {
+            problems.push("server.batch_threads: must be at least 1".to_string());
+        }
+        if server.load_threads == 0 {
+            problems.push("server.load_threads: must be at least 1".to_string());
+        }
+        if let Some(limit) = &server.rate_limit {
+            if limit.requests_per_second <= 0.0 {
+                problems
+                    .push("server.rate_limit.requests_per_second: must be positive".to_string());
+            }
+            if limit.burst == 0 {
+                problems.push("server.rate_limit.burst: must be at least 1".to_string());
+            }
+        }
+        if let Some(shedding) = &server.load_shedding
+            && shedding.max_batch_in_flight > shedding.max_in_flight
+        {
+            problems.push(format!(
+                "server.load_shedding.max_batch_in_flight: {} is above max_in_flight ({}), batch requests would be shed last",
+                shedding.max_batch_in_flight, shedding.max_in_flight
+            ));
+        }
+        if let Some(cache) = &server.row_cache
+            && cache.max_rows == 0
+        {
+            problems.push("server.row_cache.max_rows: must be at least 1".to_string());
+        }
+        if let Some(window) = &server.compaction_window
+            && window.start == window.end
+        {
+            problems.push(
+                "server.compaction_window: start and end are equal, the window is empty"
+                    .to_string(),
+            );
+        }
+        if let Some(events) = &server.events {
+            if let Some(url) = &events.webhook
+                && !url.starts_with("http://")
+            {
+                problems.push(format!(
+                    "server.events.webhook: '{url}' is not an http:// URL (https is not supported)"
+                ));
+            }
+            if events.queue_size == 0 {
+                problems.push("server.events.queue_size: must be at least 1".to_string());
+            }
+        }
+        if let Some(slo) = &server.slo {
+            let targets = slo
+                .default
+                .iter()
+                .map(|target| ("server.slo.default".to_string(), target))
+                .chain(
+                    slo.tables
+                        .iter()
+                        .map(|(table, target)| (format!("server.slo.tables.{table}"), target)),
+                );
+            for (path, target) in targets {
+                if target.p99_ms <= 0.0 {
+                    problems.push(format!("{path}.p99_ms: must be positive"));
+                }
+                if target.availability <= 0.0 || target.availability >= 1.0 {
+                    problems.push(format!(
+                        "{path}.availability: {} is not a fraction between 0 and 1 (exclusive), e.g. 0.999",
+                        target.availability
+                    ));
+                }
+            }
+        }
+        let (backend, l0_trigger) = match &self.storage.backend {
+            BackendConfig::Mmap(plain) => ("mmap", plain.l0_compaction_trigger),
+            BackendConfig::Block(block) => ("block", block.l0_compaction_trigger),
+        };
+        if l0_trigger.is_some_and(|trigger| trigger < 1) {
+            problems.push(format!(
+                "storage.{backend}.l0_compaction_trigger: must be at least 1"
+            ));
+        }
+        if let BackendConfig::Block(block) = &self.storage.backend
+            && block.mmap_reads
+            && block.use_direct_reads
+        {
+            problems.push(
+                "storage.block.use_direct_reads: cannot be combined with mmap_reads".to_string(),
+            );
+        }
+        problems
     }
 }
 
@@ -54,7 +204,7 @@ mod tests {
 
     #[test]
     fn test_config_from_args_no_file() {
-        let args = CliArgs { config: None };
+        let args = CliArgs::default();
         let config = Config::from_args(&args).unwrap();
         assert_eq!(config.server.http.port, 8080);
         assert_eq!(config.server.grpc.port, 8081);
@@ -64,8 +214,78 @@ mod tests {
     fn test_config_unknown_field_rejected() {
         let args = CliArgs {
             config: Some("nonexistent.yaml".to_string()),
+            ..CliArgs::default()
         };
         let result = Config::from_args(&args);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_cli_overrides_win() {
+        let args = CliArgs {
+            overrides: vec![
+                ("server.http.port".to_string(), "9090".to_string()),
+                ("server.coalesce_reads".to_string(), "true".to_string()),
+            ],
+            ..CliArgs::default()
+        };
+        let config = Config::from_args(&args).unwrap();
+        assert_eq!(config.server.http.port, 9090);
+        assert!(config.server.coalesce_reads);
+        assert_eq!(config.server.grpc.port, 8081);
+        assert!(!config.server.read_only);
+
+        let args = CliArgs {
+            read_only: true,
+            ..CliArgs::default()
+        };
+        assert!(Config::from_args(&args).unwrap().server.read_only);
+    }
+
+    #[test]
+    fn test_problems_name_yaml_paths() {
+        assert!(Config::default().problems().is_empty());
+
+        let yaml = "
+server:
+  grpc:
+    port: 8080
+  admin:
+    port: 8080
+  batch_threads: 0
+  load_shedding:
+    max_in_flight: 10
+    max_batch_in_flight: 20
+  events:
+    webhook: https://hooks.example.com/murr
+  slo:
+    tables:
+      users: {p99_ms: 5, availability: 99.9}
+storage:
+  path: /tmp/murr
+  block:
+    use_direct_reads: true
+    l0_compaction_trigger: 0
+";
+        let config: Config = serde_yaml_ng::from_str(yaml).unwrap();
+        let problems = config.problems();
+        let paths: Vec<&str> = problems
+            .iter()
+            .map(|p| p.split(':').next().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "server.grpc.port",
+                "server.admin.port",
+                "server.admin.port",
+                "server.batch_threads",
+                "server.load_shedding.max_batch_in_flight",
+                "server.events.webhook",
+                "server.slo.tables.users.availability",
+                "storage.block.l0_compaction_trigger",
+                "storage.block.use_direct_reads",
+            ]
+        );
+    }
 }
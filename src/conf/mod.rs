@@ -4,5 +4,8 @@ mod server;
 mod storage;
 
 pub use config::Config;
-pub use server::ServerConfig;
+pub use server::{
+    AdminConfig, EventKind, EventsConfig, LoadSheddingConfig, MaintenanceWindow, RateLimitConfig,
+    RowCacheConfig, ServerConfig, SloConfig, SloTarget, TimeOfDay,
+};
 pub use storage::{BackendConfig, StorageConfig};
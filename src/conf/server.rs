@@ -1,3 +1,4 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -46,6 +47,14 @@ pub struct GrpcConfig {
     pub host: String,
     #[serde(default = "GrpcConfig::default_port")]
     pub port: u16,
+    /// Target size of one `DoGet`/`DoExchange` message. Larger results are
+    /// split into several record batches, in key order.
+    #[serde(default = "GrpcConfig::default_max_batch_bytes")]
+    pub max_batch_bytes: usize,
+    /// `DoGet` tickets with more keys are read and encoded this many keys at
+    /// a time, as the client consumes the stream, instead of all at once.
+    #[serde(default = "GrpcConfig::default_stream_chunk_keys")]
+    pub stream_chunk_keys: usize,
 }
 
 impl GrpcConfig {
@@ -57,6 +66,14 @@ impl GrpcConfig {
         8081
     }
 
+    fn default_max_batch_bytes() -> usize {
+        2 * 1024 * 1024 // 2 MB, arrow-flight's default
+    }
+
+    fn default_stream_chunk_keys() -> usize {
+        8192
+    }
+
     pub fn addr(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
@@ -67,17 +84,306 @@ impl Default for GrpcConfig {
         Self {
             host: Self::default_host(),
             port: Self::default_port(),
+            max_batch_bytes: Self::default_max_batch_bytes(),
+            stream_chunk_keys: Self::default_stream_chunk_keys(),
         }
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+/// Listener for the admin routes (create, stats, warmup, rebuild).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AdminConfig {
+    #[serde(default = "AdminConfig::default_host")]
+    pub host: String,
+    pub port: u16,
+}
+
+impl AdminConfig {
+    fn default_host() -> String {
+        String::from("127.0.0.1")
+    }
+
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct ServerConfig {
     #[serde(default)]
     pub http: HttpConfig,
     #[serde(default)]
     pub grpc: GrpcConfig,
+    /// Moves the admin routes off the HTTP port onto their own listener, so
+    /// network policy can restrict them. Served on `http` when absent.
+    #[serde(default)]
+    pub admin: Option<AdminConfig>,
+    /// Upper bound on the in-memory size of a fetch result, checked before it
+    /// is encoded for either API.
+    #[serde(default = "ServerConfig::default_max_response_size")]
+    pub max_response_size: usize,
+    /// Worker threads for requests tagged `batch`; online requests never wait
+    /// on this pool.
+    #[serde(default = "ServerConfig::default_batch_threads")]
+    pub batch_threads: usize,
+    /// Longest key, in bytes, accepted on write.
+    #[serde(default = "ServerConfig::default_max_key_size")]
+    pub max_key_size: usize,
+    /// Per-client request limit, keyed by client IP.
+    /// Unlimited when absent.
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Rejects requests early with 503 / UNAVAILABLE once too many are in
+    /// flight. Disabled when absent.
+    #[serde(default)]
+    pub load_shedding: Option<LoadSheddingConfig>,
+    /// Registers tables from the manifest without opening them; each table is
+    /// opened on first access or by an explicit warmup.
+    #[serde(default)]
+    pub lazy_load: bool,
+    /// Defers tables like `lazy_load`, then opens them all on a background
+    /// thread once the APIs are up. Requests for tables not open yet fail
+    /// with 503 / UNAVAILABLE; `/ready` reports progress.
+    #[serde(default)]
+    pub background_load: bool,
+    /// Tables opened concurrently at startup when loading eagerly.
+    #[serde(default = "ServerConfig::default_load_threads")]
+    pub load_threads: usize,
+    /// Merges concurrent reads of the same table, keys and columns into one
+    /// store lookup whose result every caller shares.
+    #[serde(default)]
+    pub coalesce_reads: bool,
+    /// Per-table LRU of hot rows in front of the store. Disabled when absent.
+    #[serde(default)]
+    pub row_cache: Option<RowCacheConfig>,
+    /// Daily window in which RocksDB may compact in the background. Outside
+    /// it automatic compactions are paused; explicit rebuilds still run.
+    /// Compactions follow the storage config when absent.
+    #[serde(default)]
+    pub compaction_window: Option<MaintenanceWindow>,
+    /// Rejects writes, table creation and rebuilds on every API, e.g. on a
+    /// replica or during an incident freeze. Reads are unaffected.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Table lifecycle events (loaded, created, write committed, rebuild
+    /// finished), logged and optionally POSTed to a webhook. Off when absent.
+    #[serde(default)]
+    pub events: Option<EventsConfig>,
+    /// Per-table latency and availability targets, exported as gauges on
+    /// `/metrics` so alert rules can be written once for every table. Off
+    /// when absent.
+    #[serde(default)]
+    pub slo: Option<SloConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub requests_per_second: f64,
+    pub burst: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LoadSheddingConfig {
+    /// Requests in flight (running or queued) at which online requests are shed.
+    pub max_in_flight: usize,
+    /// Lower threshold at which batch requests are already shed.
+    pub max_batch_in_flight: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RowCacheConfig {
+    /// Rows kept per table, including remembered misses.
+    pub max_rows: usize,
+    /// Only reads limited to these columns go through the cache, so wide
+    /// batch reads don't evict the online hot set. Empty means every read.
+    #[serde(default)]
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EventsConfig {
+    /// `http://` URL each event is POSTed to as JSON. Events are only logged
+    /// without it.
+    #[serde(default)]
+    pub webhook: Option<String>,
+    #[serde(default = "EventsConfig::default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Events waiting for the webhook; more are dropped with a warning.
+    #[serde(default = "EventsConfig::default_queue_size")]
+    pub queue_size: usize,
+    /// Only these events are logged and sent. Empty means all of them, e.g.
+    /// `[table_load_failed]` turns the webhook into a load-failure alert.
+    #[serde(default)]
+    pub kinds: Vec<EventKind>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    TableLoaded,
+    TableLoadFailed,
+    TableCreated,
+    TableDropped,
+    TableTruncated,
+    TableAltered,
+    TableRenamed,
+    AliasSet,
+    AliasRemoved,
+    WriteCommitted,
+    KeysDeleted,
+    RebuildFinished,
+    KeyPurged,
+}
+
+impl EventsConfig {
+    fn default_timeout_ms() -> u64 {
+        5000
+    }
+
+    fn default_queue_size() -> usize {
+        1024
+    }
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            webhook: None,
+            timeout_ms: Self::default_timeout_ms(),
+            queue_size: Self::default_queue_size(),
+            kinds: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SloConfig {
+    /// Targets for every table without its own entry in `tables`. Only
+    /// listed tables get targets when absent.
+    #[serde(default)]
+    pub default: Option<SloTarget>,
+    #[serde(default)]
+    pub tables: IndexMap<String, SloTarget>,
+}
+
+impl SloConfig {
+    /// The targets that apply to `table`, if any.
+    pub fn target(&self, table: &str) -> Option<&SloTarget> {
+        self.tables.get(table).or(self.default.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SloTarget {
+    /// 99th percentile read latency, in milliseconds.
+    pub p99_ms: f64,
+    /// Fraction of requests that must succeed, e.g. `0.999`.
+    pub availability: f64,
+}
+
+/// Daily UTC time range, e.g. `start: "01:00"`, `end: "05:30"`. Wraps past
+/// midnight when `end` is before `start`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MaintenanceWindow {
+    pub start: TimeOfDay,
+    pub end: TimeOfDay,
+}
+
+impl MaintenanceWindow {
+    pub fn contains(&self, time: TimeOfDay) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// `HH:MM`, stored as minutes since midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay(u16);
+
+impl TimeOfDay {
+    /// The UTC time of day at `time`.
+    pub fn at(time: std::time::SystemTime) -> Self {
+        let secs = time
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self((secs % 86_400 / 60) as u16)
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let parsed = value
+            .split_once(':')
+            .and_then(|(h, m)| Some((h.parse::<u16>().ok()?, m.parse::<u16>().ok()?)));
+        match parsed {
+            Some((h, m)) if h < 24 && m < 60 => Ok(Self(h * 60 + m)),
+            _ => Err(format!("invalid time of day '{value}', expected HH:MM")),
+        }
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        format!("{:02}:{:02}", time.0 / 60, time.0 % 60)
+    }
+}
+
+impl ServerConfig {
+    fn default_max_response_size() -> usize {
+        1024 * 1024 * 1024 // 1 GB
+    }
+
+    fn default_batch_threads() -> usize {
+        2
+    }
+
+    fn default_max_key_size() -> usize {
+        1024
+    }
+
+    fn default_load_threads() -> usize {
+        4
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            http: HttpConfig::default(),
+            grpc: GrpcConfig::default(),
+            admin: None,
+            max_response_size: Self::default_max_response_size(),
+            batch_threads: Self::default_batch_threads(),
+            max_key_size: Self::default_max_key_size(),
+            rate_limit: None,
+            load_shedding: None,
+            lazy_load: false,
+            background_load: false,
+            load_threads: Self::default_load_threads(),
+            coalesce_reads: false,
+            row_cache: None,
+            compaction_window: None,
+            read_only: false,
+            events: None,
+            slo: None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -98,6 +404,8 @@ mod tests {
         assert_eq!(grpc.host, "0.0.0.0");
         assert_eq!(grpc.port, 8081);
         assert_eq!(grpc.addr(), "0.0.0.0:8081");
+        assert_eq!(grpc.max_batch_bytes, 2 * 1024 * 1024);
+        assert_eq!(grpc.stream_chunk_keys, 8192);
     }
 
     #[test]
@@ -105,5 +413,71 @@ mod tests {
         let server = ServerConfig::default();
         assert_eq!(server.http.port, 8080);
         assert_eq!(server.grpc.port, 8081);
+        assert_eq!(server.max_response_size, 1024 * 1024 * 1024);
+        assert_eq!(server.batch_threads, 2);
+        assert_eq!(server.max_key_size, 1024);
+        assert!(server.rate_limit.is_none());
+        assert!(!server.coalesce_reads);
+    }
+
+    #[test]
+    fn test_rate_limit_yaml() {
+        let yaml = "
+rate_limit:
+  requests_per_second: 50.0
+  burst: 100
+";
+        let server: ServerConfig = serde_yaml_ng::from_str(yaml).unwrap();
+        let limit = server.rate_limit.unwrap();
+        assert_eq!(limit.requests_per_second, 50.0);
+        assert_eq!(limit.burst, 100);
+    }
+
+    #[test]
+    fn test_row_cache_yaml() {
+        let yaml = "
+row_cache:
+  max_rows: 10000
+  columns: [score, label]
+";
+        let server: ServerConfig = serde_yaml_ng::from_str(yaml).unwrap();
+        let cache = server.row_cache.unwrap();
+        assert_eq!(cache.max_rows, 10000);
+        assert_eq!(cache.columns, vec!["score", "label"]);
+    }
+
+    #[test]
+    fn test_compaction_window_wraps_midnight() {
+        let yaml = "
+compaction_window:
+  start: \"22:30\"
+  end: \"04:00\"
+";
+        let server: ServerConfig = serde_yaml_ng::from_str(yaml).unwrap();
+        let window = server.compaction_window.unwrap();
+        let at = |t: &str| window.contains(TimeOfDay::try_from(t.to_string()).unwrap());
+        assert!(at("23:00"));
+        assert!(at("00:00"));
+        assert!(!at("04:00"));
+        assert!(!at("12:00"));
+        assert_eq!(String::from(window.start), "22:30");
+
+        let yaml = "compaction_window: {start: \"25:00\", end: \"04:00\"}";
+        assert!(serde_yaml_ng::from_str::<ServerConfig>(yaml).is_err());
+    }
+
+    #[test]
+    fn test_slo_tables_override_default() {
+        let yaml = "
+slo:
+  default: {p99_ms: 20, availability: 0.999}
+  tables:
+    users: {p99_ms: 5, availability: 0.9999}
+";
+        let server: ServerConfig = serde_yaml_ng::from_str(yaml).unwrap();
+        let slo = server.slo.unwrap();
+        assert_eq!(slo.target("users").unwrap().p99_ms, 5.0);
+        assert_eq!(slo.target("items").unwrap().availability, 0.999);
+        assert!(SloConfig::default().target("users").is_none());
     }
 }
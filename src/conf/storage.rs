@@ -44,6 +44,7 @@ fn default_path() -> PathBuf {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io::store::rocksdb::block::IndexType;
 
     #[test]
     fn default_is_mmap() {
@@ -74,11 +75,15 @@ mmap:
 path: /custom/path
 block:
   block_size: 8192
+  index_type: partitioned
 ";
         let cfg: StorageConfig = serde_yaml_ng::from_str(yaml).unwrap();
         assert_eq!(cfg.path, PathBuf::from("/custom/path"));
         match cfg.backend {
-            BackendConfig::Block(b) => assert_eq!(b.block_size, 8192),
+            BackendConfig::Block(b) => {
+                assert_eq!(b.block_size, 8192);
+                assert_eq!(b.index_type, IndexType::Partitioned);
+            }
             _ => panic!("expected block"),
         }
     }
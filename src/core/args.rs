@@ -1,11 +1,37 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use log::kv::{ToValue, Value};
 
-#[derive(Parser, Debug, PartialEq)]
+#[derive(Parser, Debug, Default, PartialEq)]
 #[command(version, about)]
 pub struct CliArgs {
     #[arg(short, long)]
     pub config: Option<String>,
+    /// Overrides one setting by its YAML path, e.g. `--set
+    /// server.http.port=9090`. Wins over the file and the environment.
+    #[arg(short = 's', long = "set", value_name = "KEY=VALUE", value_parser = parse_override)]
+    pub overrides: Vec<(String, String)>,
+    /// Print the effective configuration as YAML and exit.
+    #[arg(long)]
+    pub print_config: bool,
+    /// Refuse writes, table creation and rebuilds. Same as `--set
+    /// server.read_only=true`.
+    #[arg(long)]
+    pub read_only: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum Command {
+    /// Check a config file, print every problem found and exit non-zero if
+    /// there are any.
+    ValidateConfig { file: String },
+}
+
+fn parse_override(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{arg}'"))
 }
 
 impl ToValue for CliArgs {
@@ -24,8 +50,43 @@ mod tests {
         assert_eq!(
             args,
             CliArgs {
-                config: Some("foo".to_string())
+                config: Some("foo".to_string()),
+                ..CliArgs::default()
             }
         );
     }
+
+    #[test]
+    fn test_override_flags() {
+        let args = CliArgs::parse_from([
+            "self",
+            "--set",
+            "server.http.port=9090",
+            "-s",
+            "storage.path=/data",
+            "--print-config",
+            "--read-only",
+        ]);
+        assert_eq!(
+            args.overrides,
+            vec![
+                ("server.http.port".to_string(), "9090".to_string()),
+                ("storage.path".to_string(), "/data".to_string()),
+            ]
+        );
+        assert!(args.print_config);
+        assert!(args.read_only);
+        assert!(CliArgs::try_parse_from(["self", "--set", "port"]).is_err());
+    }
+
+    #[test]
+    fn test_validate_config_subcommand() {
+        let args = CliArgs::parse_from(["self", "validate-config", "murr.yaml"]);
+        assert_eq!(
+            args.command,
+            Some(Command::ValidateConfig {
+                file: "murr.yaml".to_string()
+            })
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Clone, Error, PartialEq)]
 pub enum MurrError {
     #[error("Cannot parse config: {0}")]
     ConfigParsingError(String),
@@ -16,6 +16,18 @@ pub enum MurrError {
     TableError(String),
     #[error("Segment error: {0}")]
     SegmentError(String),
+    #[error("response too large: {0}")]
+    ResponseTooLarge(String),
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+    #[error("overloaded: {0}")]
+    Overloaded(String),
+    #[error("table is still loading: {0}")]
+    TableLoading(String),
+    #[error("key not found: {0}")]
+    KeyNotFound(String),
+    #[error("server is read-only: {0}")]
+    ReadOnly(String),
 }
 
 impl From<std::io::Error> for MurrError {
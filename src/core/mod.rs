@@ -1,12 +1,18 @@
 mod args;
+mod clock;
 mod dtype;
 mod error;
 mod logger;
 mod schema;
 
-pub use args::CliArgs;
+pub use args::{CliArgs, Command};
+#[allow(unused_imports)]
+pub use clock::{Clock, ManualClock, SystemClock};
 pub use dtype::DType;
 pub use error::MurrError;
 pub use logger::setup_logging;
 #[allow(unused_imports)]
-pub use schema::{ColumnSchema, DTypeName, TableSchema};
+pub use schema::{
+    ColumnChange, ColumnEncoding, ColumnSchema, DTypeName, Derivation, Entity, KeyAlias,
+    SchemaChange, SchemaDiff, TableSchema, UPDATED_AT_COLUMN,
+};
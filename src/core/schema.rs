@@ -1,10 +1,14 @@
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::core::MurrError;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum DTypeName {
     Utf8,
+    /// Opaque bytes, e.g. serialized protobuf. JSON carries them as base64.
+    Binary,
     Bool,
     Int8,
     Int16,
@@ -16,6 +20,9 @@ pub enum DTypeName {
     UInt64,
     Float32,
     Float64,
+    /// Variable-length list of float32, e.g. an embedding.
+    #[serde(rename = "float32_list")]
+    Float32List,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -24,6 +31,29 @@ pub struct ColumnSchema {
     pub dtype: DTypeName,
     #[serde(default = "ColumnSchema::default_nullable")]
     pub nullable: bool,
+    /// How values are laid out in stored rows. Reads always return `dtype`.
+    #[serde(default, skip_serializing_if = "ColumnEncoding::is_dense")]
+    pub encoding: ColumnEncoding,
+}
+
+/// Storage layout of a column's values, chosen per column. Each encoding is
+/// an `io::codec::ColumnCodec`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnEncoding {
+    /// The dtype's own fixed-width (or length-prefixed) representation.
+    #[default]
+    Dense,
+    /// IEEE half precision, `float32` columns only; read back as float32.
+    F16,
+    /// bfloat16 (float32's exponent, 7-bit mantissa), `float32` columns only.
+    Bf16,
+}
+
+impl ColumnEncoding {
+    pub fn is_dense(&self) -> bool {
+        *self == ColumnEncoding::Dense
+    }
 }
 
 impl ColumnSchema {
@@ -32,8 +62,170 @@ impl ColumnSchema {
     }
 }
 
+/// Declaring an int64 column with this name opts a table into write
+/// timestamps: every write sets it to the write time, in milliseconds since
+/// the Unix epoch, and it reads like any other column.
+pub const UPDATED_AT_COLUMN: &str = "_updated_at";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TableSchema {
     pub key: String,
     pub columns: IndexMap<String, ColumnSchema>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_alias: Option<KeyAlias>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derived: Option<Derivation>,
+    /// The registered entity the table's keys identify.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity: Option<String>,
+}
+
+/// What a family of tables is keyed by, e.g. `user` or `item`. Tables that
+/// name the same entity share a key space, so their rows can be joined.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Entity {
+    pub key_dtype: DTypeName,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Resolves keys missing from a table through a mapping table: `column` of
+/// `table` holds the primary key for each alias key (e.g. a legacy ID).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct KeyAlias {
+    pub table: String,
+    pub column: String,
+}
+
+/// Makes a table a function of another one: each row is computed from the
+/// `source` row with the same key, on `refresh` and every `refresh_secs`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Derivation {
+    pub source: String,
+    /// Expression per column, over source column names, numbers, `+ - * /`
+    /// and parentheses, e.g. `"clicks / views"`. The result is cast to the
+    /// column's dtype. Columns not listed copy the same-named source column.
+    #[serde(default)]
+    pub columns: IndexMap<String, String>,
+    /// Recompute this often in the background. Only on request when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_secs: Option<u64>,
+}
+
+/// How a requested schema differs from a table's current one, returned when
+/// a declarative `PUT` of an existing table doesn't match it. Column order is
+/// not compared.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SchemaDiff {
+    /// Columns only in the requested schema.
+    pub added: Vec<String>,
+    /// Columns only in the current schema.
+    pub removed: Vec<String>,
+    pub changed: Vec<ColumnChange>,
+    /// Table settings that differ: `key`, `key_alias`, `derived`, `entity`.
+    pub settings: Vec<String>,
+}
+
+/// Columns to add to or drop from an existing table, served by
+/// `POST /api/v1/table/{name}/alter`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaChange {
+    /// Appended after the current columns. Must be nullable: existing rows
+    /// read null for them.
+    #[serde(default)]
+    pub add_columns: IndexMap<String, ColumnSchema>,
+    #[serde(default)]
+    pub drop_columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ColumnChange {
+    pub column: String,
+    pub from: ColumnSchema,
+    pub to: ColumnSchema,
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.settings.is_empty()
+    }
+}
+
+impl TableSchema {
+    /// The schema after `change`, checked against this one.
+    pub fn apply(&self, change: &SchemaChange) -> Result<TableSchema, MurrError> {
+        if change.add_columns.is_empty() && change.drop_columns.is_empty() {
+            return Err(MurrError::TableError(
+                "schema change adds and drops no columns".to_string(),
+            ));
+        }
+        let mut schema = self.clone();
+        for name in &change.drop_columns {
+            if *name == self.key {
+                return Err(MurrError::TableError(format!(
+                    "cannot drop key column '{name}'"
+                )));
+            }
+            if schema.columns.shift_remove(name).is_none() {
+                return Err(MurrError::TableError(format!(
+                    "cannot drop column '{name}': no such column"
+                )));
+            }
+        }
+        for (name, column) in &change.add_columns {
+            if self.columns.contains_key(name) {
+                return Err(MurrError::TableError(format!(
+                    "cannot add column '{name}': it already exists"
+                )));
+            }
+            if !column.nullable {
+                return Err(MurrError::TableError(format!(
+                    "cannot add column '{name}': added columns must be nullable"
+                )));
+            }
+            schema.columns.insert(name.clone(), column.clone());
+        }
+        Ok(schema)
+    }
+
+    /// What would have to change to turn `self` into `to`.
+    pub fn diff(&self, to: &TableSchema) -> SchemaDiff {
+        let mut diff = SchemaDiff::default();
+        for (name, column) in &to.columns {
+            match self.columns.get(name) {
+                None => diff.added.push(name.clone()),
+                Some(current) if current != column => diff.changed.push(ColumnChange {
+                    column: name.clone(),
+                    from: current.clone(),
+                    to: column.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        diff.removed = self
+            .columns
+            .keys()
+            .filter(|name| !to.columns.contains_key(*name))
+            .cloned()
+            .collect();
+        let settings = [
+            ("key", self.key == to.key),
+            ("key_alias", self.key_alias == to.key_alias),
+            ("derived", self.derived == to.derived),
+            ("entity", self.entity == to.entity),
+        ];
+        diff.settings = settings
+            .into_iter()
+            .filter(|(_, same)| !same)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        diff
+    }
 }
@@ -0,0 +1,307 @@
+//! In-process murr without the HTTP/Flight servers.
+//!
+//! `MurrService` is synchronous, so the embedded handle is already the
+//! blocking API: no async runtime is created or required, calls run on the
+//! caller's thread and return when the store has answered. Only the network
+//! layers in `api` need tokio.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use arrow::record_batch::RecordBatch;
+
+use crate::conf::{Config, StorageConfig};
+use crate::core::{Entity, MurrError, SchemaChange, TableSchema};
+use crate::io::store::TableStats;
+use crate::io::store::rocksdb::RocksDBStore;
+use crate::io::table::ReadEstimate;
+use crate::service::{EntityInfo, MurrService, Provisioned, PurgeReport};
+
+/// An embedded murr instance over a local RocksDB directory.
+///
+/// ```no_run
+/// let db = murr::Murr::open("/tmp/murr")?;
+/// let batch = db.read("docs", &["doc_1", "doc_3"], &["score"])?;
+/// # Ok::<(), murr::core::MurrError>(())
+/// ```
+pub struct Murr {
+    service: Arc<MurrService<RocksDBStore>>,
+}
+
+impl Murr {
+    /// Opens (or creates) a store at `path` with the default backend and
+    /// server settings.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, MurrError> {
+        Self::with_config(Config {
+            storage: StorageConfig {
+                path: path.as_ref().to_path_buf(),
+                backend: Default::default(),
+            },
+            ..Config::default()
+        })
+    }
+
+    /// Opens a store from a full config. `server.http`/`server.grpc` are
+    /// ignored; the service limits (`max_response_size`, `max_key_size`, ...)
+    /// still apply.
+    pub fn with_config(config: Config) -> Result<Self, MurrError> {
+        let store = RocksDBStore::open_from_config(&config.storage)?;
+        let service = Arc::new(MurrService::new(Arc::new(RwLock::new(store)), config)?);
+        service.start_background_load();
+        service.start_maintenance();
+        Ok(Self { service })
+    }
+
+    /// The underlying service, e.g. to serve it over HTTP or Flight as well.
+    pub fn service(&self) -> &Arc<MurrService<RocksDBStore>> {
+        &self.service
+    }
+
+    pub fn create_table(&self, name: &str, schema: TableSchema) -> Result<(), MurrError> {
+        self.service.create(name, schema)
+    }
+
+    /// Creates the table unless it exists; see `MurrService::provision`.
+    pub fn provision_table(
+        &self,
+        name: &str,
+        schema: TableSchema,
+        allow_evolution: bool,
+    ) -> Result<Provisioned, MurrError> {
+        self.service.provision(name, schema, allow_evolution)
+    }
+
+    /// Removes the table with its rows; refused while other tables depend on it.
+    pub fn drop_table(&self, name: &str) -> Result<(), MurrError> {
+        self.service.drop_table(name)
+    }
+
+    /// Adds nullable columns and drops columns, rewriting every row; see
+    /// `MurrService::alter_table`. Returns the new schema version.
+    pub fn alter_table(&self, name: &str, change: &SchemaChange) -> Result<u32, MurrError> {
+        self.service.alter_table(name, change)
+    }
+
+    /// Moves the table to `new_name` without touching its rows; see
+    /// `MurrService::rename`.
+    pub fn rename_table(&self, name: &str, new_name: &str) -> Result<(), MurrError> {
+        self.service.rename(name, new_name)
+    }
+
+    /// Points `alias` at `table` in one step; reads and writes through the
+    /// alias follow it. Returns the table it pointed at before.
+    pub fn set_alias(&self, alias: &str, table: &str) -> Result<Option<String>, MurrError> {
+        self.service.set_alias(alias, table)
+    }
+
+    pub fn remove_alias(&self, alias: &str) -> Result<String, MurrError> {
+        self.service.remove_alias(alias)
+    }
+
+    /// Removes every row of the table and keeps its schema.
+    pub fn truncate(&self, table: &str) -> Result<(), MurrError> {
+        self.service.truncate(table)
+    }
+
+    pub fn create_entity(&self, name: &str, entity: Entity) -> Result<(), MurrError> {
+        self.service.create_entity(name, entity)
+    }
+
+    /// Erases `key` from every table of `entity`, on disk too; see
+    /// `MurrService::purge_key`.
+    pub fn purge_key(&self, entity: &str, key: &str) -> Result<PurgeReport, MurrError> {
+        self.service.purge_key(entity, key)
+    }
+
+    pub fn write(&self, table: &str, batch: &RecordBatch) -> Result<(), MurrError> {
+        self.service.write(table, batch)
+    }
+
+    /// Deletes the rows for `keys`; returns how many existed.
+    pub fn delete(&self, table: &str, keys: &[&str]) -> Result<usize, MurrError> {
+        self.service.delete(table, keys)
+    }
+
+    /// Hides the rows for `keys` until they are written again; returns how
+    /// many existed.
+    pub fn deactivate(&self, table: &str, keys: &[&str]) -> Result<usize, MurrError> {
+        self.service.deactivate(table, keys)
+    }
+
+    /// Restores rows hidden by `deactivate`; returns how many came back.
+    pub fn reactivate(&self, table: &str, keys: &[&str]) -> Result<usize, MurrError> {
+        self.service.reactivate(table, keys)
+    }
+
+    /// One row per key, in request order; missing keys come back as nulls.
+    pub fn read(
+        &self,
+        table: &str,
+        keys: &[&str],
+        columns: &[&str],
+    ) -> Result<RecordBatch, MurrError> {
+        self.service.read(table, keys, columns)
+    }
+
+    /// Like `read`, but skips columns the table doesn't have.
+    pub fn read_lenient(
+        &self,
+        table: &str,
+        keys: &[&str],
+        columns: &[&str],
+    ) -> Result<RecordBatch, MurrError> {
+        self.service.read_lenient(table, keys, columns)
+    }
+
+    /// Rows and approximate bytes `read` would return, without reading.
+    pub fn estimate_read(
+        &self,
+        table: &str,
+        keys: usize,
+        columns: &[&str],
+    ) -> Result<ReadEstimate, MurrError> {
+        self.service.estimate_read(table, keys, columns, false)
+    }
+
+    /// Columns from several tables sharing one key space, side by side.
+    pub fn read_join(
+        &self,
+        keys: &[&str],
+        tables: &[(&str, &[&str])],
+    ) -> Result<RecordBatch, MurrError> {
+        self.service.read_join(keys, tables)
+    }
+
+    pub fn contains(&self, table: &str, keys: &[&str]) -> Result<Vec<bool>, MurrError> {
+        self.service.contains(table, keys)
+    }
+
+    pub fn count(&self, table: &str) -> Result<usize, MurrError> {
+        self.service.count(table)
+    }
+
+    /// One page of keys in byte order, starting after `cursor`.
+    pub fn keys(
+        &self,
+        table: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, MurrError> {
+        self.service.keys(table, cursor, limit)
+    }
+
+    pub fn stats(&self, table: &str) -> Result<TableStats, MurrError> {
+        self.service.stats(table)
+    }
+
+    pub fn warmup(&self, table: &str) -> Result<(), MurrError> {
+        self.service.warmup(table)
+    }
+
+    pub fn rebuild(&self, table: &str) -> Result<(), MurrError> {
+        self.service.rebuild(table)
+    }
+
+    /// Fills the nulls of `column` with `value`; returns the rows filled.
+    pub fn backfill(
+        &self,
+        table: &str,
+        column: &str,
+        value: &serde_json::Value,
+    ) -> Result<usize, MurrError> {
+        self.service.backfill(table, column, value, false)
+    }
+
+    /// Recomputes a derived table from its source; returns the rows written.
+    pub fn refresh(&self, table: &str) -> Result<usize, MurrError> {
+        self.service.refresh(table)
+    }
+
+    pub fn list_tables(&self) -> HashMap<String, TableSchema> {
+        self.service.list_tables()
+    }
+
+    /// Alias to table.
+    pub fn list_aliases(&self) -> HashMap<String, String> {
+        self.service.list_aliases()
+    }
+
+    pub fn list_entities(&self) -> HashMap<String, EntityInfo> {
+        self.service.list_entities()
+    }
+
+    pub fn schema(&self, table: &str) -> Result<TableSchema, MurrError> {
+        self.service.get_schema(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ColumnSchema, DTypeName};
+    use arrow::array::{Float32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use tempfile::TempDir;
+
+    #[test]
+    fn open_write_read_reopen() {
+        let dir = TempDir::new().unwrap();
+        let schema = TableSchema {
+            key: "id".to_string(),
+            columns: [
+                (
+                    "id".to_string(),
+                    ColumnSchema {
+                        dtype: DTypeName::Utf8,
+                        nullable: false,
+                        encoding: Default::default(),
+                    },
+                ),
+                (
+                    "score".to_string(),
+                    ColumnSchema {
+                        dtype: DTypeName::Float32,
+                        nullable: true,
+                        encoding: Default::default(),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            key_alias: None,
+            derived: None,
+            entity: None,
+        };
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Utf8, false),
+                Field::new("score", DataType::Float32, true),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b"])),
+                Arc::new(Float32Array::from(vec![1.0, 2.0])),
+            ],
+        )
+        .unwrap();
+
+        {
+            let db = Murr::open(dir.path()).unwrap();
+            db.create_table("t", schema).unwrap();
+            db.write("t", &batch).unwrap();
+        }
+
+        let db = Murr::open(dir.path()).unwrap();
+        assert!(db.list_tables().contains_key("t"));
+        let result = db.read("t", &["b", "zz"], &["score"]).unwrap();
+        let scores = result
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        assert_eq!(scores.value(0), 2.0);
+        assert!(scores.is_null(1));
+        assert_eq!(db.count("t").unwrap(), 2);
+        assert_eq!(db.contains("t", &["a", "zz"]).unwrap(), vec![true, false]);
+    }
+}
@@ -0,0 +1,153 @@
+//! Opaque byte columns. Stored exactly like utf8 (a slot in the static section
+//! pointing at the payload in the dynamic one) but read back without UTF-8
+//! validation. JSON has no bytes type, so values travel as standard base64.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, BinaryArray, BinaryBuilder},
+    datatypes::DataType,
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde_json::Value;
+
+use crate::{
+    core::{DType, DTypeName, MurrError},
+    io::{
+        codec::{ArrowCodec, ColumnDecoder, ColumnEncoder, JsonCodec, downcast},
+        row::{read::ReadRow, write::WriteRow},
+        schema::SegmentColumnSchema,
+    },
+};
+
+pub struct Binary;
+
+impl DType for Binary {
+    fn name(&self) -> DTypeName {
+        DTypeName::Binary
+    }
+    fn arrow_dtype(&self) -> DataType {
+        DataType::Binary
+    }
+    fn size(&self) -> usize {
+        4
+    }
+}
+
+impl ArrowCodec for Binary {
+    fn make_encoder(&self, col: SegmentColumnSchema, rows: usize) -> Box<dyn ColumnEncoder> {
+        Box::new(BinaryEncoder {
+            column: col,
+            builder: BinaryBuilder::with_capacity(rows, rows * 16),
+        })
+    }
+
+    fn make_decoder(
+        &self,
+        col: SegmentColumnSchema,
+        arr: &dyn Array,
+    ) -> Result<Box<dyn ColumnDecoder>, MurrError> {
+        let typed = downcast::<BinaryArray>(arr, "Binary")?;
+        Ok(Box::new(BinaryDecoder {
+            column: col,
+            array: typed.clone(),
+        }))
+    }
+}
+
+impl JsonCodec for Binary {
+    fn to_json(&self, arr: &dyn Array) -> Result<Vec<Value>, MurrError> {
+        let typed = downcast::<BinaryArray>(arr, "Binary")?;
+        Ok(typed
+            .iter()
+            .map(|v| v.map_or(Value::Null, |bytes| Value::String(STANDARD.encode(bytes))))
+            .collect())
+    }
+
+    fn from_json(&self, vals: &[Value]) -> Result<ArrayRef, MurrError> {
+        let decoded: Vec<Option<Vec<u8>>> = vals
+            .iter()
+            .map(|v| match v {
+                Value::Null => Ok(None),
+                Value::String(s) => STANDARD
+                    .decode(s)
+                    .map(Some)
+                    .map_err(|e| MurrError::TableError(format!("invalid base64: {e}"))),
+                _ => Err(MurrError::TableError(format!(
+                    "expected base64 string, got {v}"
+                ))),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Arc::new(BinaryArray::from_iter(decoded)))
+    }
+}
+
+struct BinaryEncoder {
+    column: SegmentColumnSchema,
+    builder: BinaryBuilder,
+}
+
+impl ColumnEncoder for BinaryEncoder {
+    fn add_row(&mut self, row: &ReadRow) -> Result<(), MurrError> {
+        if row.is_null(&self.column) {
+            self.builder.append_null();
+        } else {
+            self.builder.append_value(row.read_dynamic(&self.column));
+        }
+        Ok(())
+    }
+
+    fn add_empty(&mut self) -> Result<(), MurrError> {
+        self.builder.append_null();
+        Ok(())
+    }
+
+    fn build(&mut self) -> ArrayRef {
+        Arc::new(self.builder.finish())
+    }
+}
+
+struct BinaryDecoder {
+    column: SegmentColumnSchema,
+    array: BinaryArray,
+}
+
+impl ColumnDecoder for BinaryDecoder {
+    fn write_to_row(&self, index: usize, row: &mut WriteRow) {
+        if !self.array.is_null(index) {
+            row.write_dynamic(&self.column, self.array.value(index));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::codec::test_util::{assert_json_roundtrip, assert_row_roundtrip};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::bytes(Some(&b"\x08\x96\x01"[..]))]
+    #[case::not_utf8(Some(&[0xFF, 0xFE, 0x00][..]))]
+    #[case::null(None)]
+    #[case::empty(Some(&b""[..]))]
+    fn row_roundtrip(#[case] v: Option<&[u8]>) {
+        assert_row_roundtrip(DTypeName::Binary, &BinaryArray::from(vec![v]));
+    }
+
+    #[rstest]
+    #[case::bytes(Some(&b"\x08\x96\x01"[..]))]
+    #[case::null(None)]
+    #[case::empty(Some(&b""[..]))]
+    fn json_roundtrip(#[case] v: Option<&[u8]>) {
+        assert_json_roundtrip(DTypeName::Binary, &BinaryArray::from(vec![v]));
+    }
+
+    #[test]
+    fn json_is_base64() {
+        let arr = BinaryArray::from(vec![Some(&[0xFF, 0x00][..])]);
+        assert_eq!(Binary.to_json(&arr).unwrap(), vec![Value::from("/wA=")]);
+        assert!(Binary.from_json(&[Value::from("not base64!")]).is_err());
+        assert!(Binary.from_json(&[Value::from(1)]).is_err());
+    }
+}
@@ -146,6 +146,7 @@ mod tests {
             dtype: DTypeName::Bool,
             name: "b".into(),
             offset: 0,
+            encoding: Default::default(),
         };
         let wrong = Float32Array::from(vec![Some(1.0_f32)]);
         let err = Bool.make_decoder(c, &wrong);
@@ -91,6 +91,7 @@ mod tests {
             dtype: DTypeName::Float32,
             name: "v".into(),
             offset: 0,
+            encoding: Default::default(),
         };
         let schema = SegmentSchema::new(std::slice::from_ref(&c));
         let input = Float32Array::from(vec![Some(f32::NAN)]);
@@ -0,0 +1,246 @@
+//! Variable-length `List<Float32>` columns, for embeddings. A row stores the
+//! vector as its raw `f32` payload in the dynamic section (the same slot +
+//! length layout as utf8), so one column can hold vectors of any dimension.
+//! Elements can't be null; the list itself can.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, Float32Array, Float32Builder, ListArray, ListBuilder},
+    compute::cast,
+    datatypes::{DataType, Field},
+};
+use serde_json::Value;
+
+use crate::{
+    core::{DType, DTypeName, MurrError},
+    io::{
+        codec::{ArrowCodec, ColumnDecoder, ColumnEncoder, JsonCodec, downcast},
+        row::{read::ReadRow, write::WriteRow},
+        schema::SegmentColumnSchema,
+    },
+};
+
+pub struct Float32List;
+
+impl DType for Float32List {
+    fn name(&self) -> DTypeName {
+        DTypeName::Float32List
+    }
+    fn arrow_dtype(&self) -> DataType {
+        DataType::List(Arc::new(Field::new_list_field(DataType::Float32, true)))
+    }
+    fn size(&self) -> usize {
+        4
+    }
+}
+
+impl ArrowCodec for Float32List {
+    fn make_encoder(&self, col: SegmentColumnSchema, rows: usize) -> Box<dyn ColumnEncoder> {
+        Box::new(Encoder {
+            column: col,
+            builder: ListBuilder::with_capacity(Float32Builder::new(), rows),
+        })
+    }
+
+    /// Also takes `FixedSizeList<Float32>`, which is what most clients build
+    /// from a 2-d float array.
+    fn make_decoder(
+        &self,
+        col: SegmentColumnSchema,
+        arr: &dyn Array,
+    ) -> Result<Box<dyn ColumnDecoder>, MurrError> {
+        let cast_list;
+        let arr = match arr.data_type() {
+            DataType::FixedSizeList(field, _) if field.data_type() == &DataType::Float32 => {
+                cast_list = cast(arr, &self.arrow_dtype())?;
+                cast_list.as_ref()
+            }
+            _ => arr,
+        };
+        let array = downcast::<ListArray>(arr, "List<Float32>")?.clone();
+        let values = downcast::<Float32Array>(array.values().as_ref(), "List<Float32>")?.clone();
+        // Null lists (e.g. from a FixedSizeList) may span null placeholders.
+        let holes = values.null_count() > 0
+            && (0..array.len()).any(|i| array.is_valid(i) && array.value(i).null_count() > 0);
+        if holes {
+            return Err(MurrError::SegmentError(format!(
+                "column '{}': list elements can't be null",
+                col.name
+            )));
+        }
+        Ok(Box::new(Decoder {
+            column: col,
+            array,
+            values,
+        }))
+    }
+}
+
+impl JsonCodec for Float32List {
+    fn to_json(&self, arr: &dyn Array) -> Result<Vec<Value>, MurrError> {
+        let typed = downcast::<ListArray>(arr, "List<Float32>")?;
+        (0..typed.len())
+            .map(|i| {
+                if typed.is_null(i) {
+                    return Ok(Value::Null);
+                }
+                let row = typed.value(i);
+                let values = downcast::<Float32Array>(row.as_ref(), "Float32")?;
+                Ok(Value::Array(
+                    values
+                        .values()
+                        .iter()
+                        .map(|v| serde_json::to_value(v).expect("f32 serializes to JSON"))
+                        .collect(),
+                ))
+            })
+            .collect()
+    }
+
+    fn from_json(&self, vals: &[Value]) -> Result<ArrayRef, MurrError> {
+        let mut builder = ListBuilder::with_capacity(Float32Builder::new(), vals.len());
+        for v in vals {
+            match v {
+                Value::Null => builder.append_null(),
+                Value::Array(items) => {
+                    for item in items {
+                        let x = item.as_f64().ok_or_else(|| {
+                            MurrError::TableError(format!("expected number, got {item}"))
+                        })?;
+                        builder.values().append_value(x as f32);
+                    }
+                    builder.append(true);
+                }
+                _ => {
+                    return Err(MurrError::TableError(format!(
+                        "expected array of numbers, got {v}"
+                    )));
+                }
+            }
+        }
+        Ok(Arc::new(builder.finish()))
+    }
+}
+
+struct Encoder {
+    column: SegmentColumnSchema,
+    builder: ListBuilder<Float32Builder>,
+}
+
+impl ColumnEncoder for Encoder {
+    fn add_row(&mut self, row: &ReadRow) -> Result<(), MurrError> {
+        if row.is_null(&self.column) {
+            self.builder.append_null();
+            return Ok(());
+        }
+        let bytes = row.read_dynamic(&self.column);
+        if !bytes.len().is_multiple_of(4) {
+            return Err(MurrError::SegmentError(format!(
+                "column '{}': {} bytes is not a whole number of f32",
+                self.column.name,
+                bytes.len()
+            )));
+        }
+        let values = self.builder.values();
+        for chunk in bytes.chunks_exact(4) {
+            values.append_value(f32::from_ne_bytes(chunk.try_into().unwrap()));
+        }
+        self.builder.append(true);
+        Ok(())
+    }
+
+    fn add_empty(&mut self) -> Result<(), MurrError> {
+        self.builder.append_null();
+        Ok(())
+    }
+
+    fn build(&mut self) -> ArrayRef {
+        Arc::new(self.builder.finish())
+    }
+}
+
+struct Decoder {
+    column: SegmentColumnSchema,
+    array: ListArray,
+    values: Float32Array,
+}
+
+impl ColumnDecoder for Decoder {
+    fn write_to_row(&self, index: usize, row: &mut WriteRow) {
+        if !self.array.is_null(index) {
+            let offsets = self.array.value_offsets();
+            let (start, end) = (offsets[index] as usize, offsets[index + 1] as usize);
+            row.write_dynamic(
+                &self.column,
+                bytemuck::cast_slice(&self.values.values()[start..end]),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::codec::test_util::{assert_json_roundtrip, assert_row_roundtrip};
+    use arrow::array::FixedSizeListArray;
+    use arrow::datatypes::Float32Type;
+    use rstest::rstest;
+
+    fn list(rows: Vec<Option<Vec<Option<f32>>>>) -> ListArray {
+        ListArray::from_iter_primitive::<Float32Type, _, _>(rows)
+    }
+
+    #[rstest]
+    #[case::vectors(vec![Some(vec![Some(0.5), Some(-1.0), Some(3.25)]), Some(vec![Some(1e-3)])])]
+    #[case::null(vec![None, Some(vec![Some(2.0)])])]
+    #[case::empty(vec![Some(vec![]), None])]
+    fn row_roundtrip(#[case] rows: Vec<Option<Vec<Option<f32>>>>) {
+        assert_row_roundtrip(DTypeName::Float32List, &list(rows));
+    }
+
+    #[rstest]
+    #[case::vectors(vec![Some(vec![Some(0.5), Some(-1.0)]), Some(vec![Some(4.0)])])]
+    #[case::null(vec![None, Some(vec![])])]
+    fn json_roundtrip(#[case] rows: Vec<Option<Vec<Option<f32>>>>) {
+        assert_json_roundtrip(DTypeName::Float32List, &list(rows));
+    }
+
+    #[test]
+    fn decoder_takes_fixed_size_lists() {
+        let fixed = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+            vec![Some(vec![Some(1.0), Some(2.0)]), None],
+            2,
+        );
+        let c = SegmentColumnSchema {
+            index: 0,
+            dtype: DTypeName::Float32List,
+            name: "embedding".into(),
+            offset: 0,
+            encoding: Default::default(),
+        };
+        assert!(Float32List.make_decoder(c, &fixed).is_ok());
+    }
+
+    #[test]
+    fn rejects_null_elements() {
+        let c = SegmentColumnSchema {
+            index: 0,
+            dtype: DTypeName::Float32List,
+            name: "embedding".into(),
+            offset: 0,
+            encoding: Default::default(),
+        };
+        let holes = list(vec![Some(vec![Some(1.0), None])]);
+        assert!(matches!(
+            Float32List.make_decoder(c, &holes),
+            Err(MurrError::SegmentError(_))
+        ));
+        assert!(
+            Float32List
+                .from_json(&[serde_json::json!([1.0, null])])
+                .is_err()
+        );
+        assert!(Float32List.from_json(&[serde_json::json!(1.0)]).is_err());
+    }
+}
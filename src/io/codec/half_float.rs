@@ -0,0 +1,176 @@
+//! `f16` / `bf16` storage for `Float32` columns: two bytes per value in the
+//! row, widened back to `Float32` on read. Halves the row size of
+//! embedding-heavy tables; `f16` keeps more mantissa (~3 decimal digits,
+//! range ±65504), `bf16` keeps the full f32 exponent range with ~2 digits.
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, Float32Array, Float32Builder};
+use half::{bf16, f16};
+
+use crate::{
+    core::MurrError,
+    io::{
+        codec::{ColumnCodec, ColumnDecoder, ColumnEncoder, downcast},
+        row::{read::ReadRow, write::WriteRow},
+        schema::SegmentColumnSchema,
+    },
+};
+
+pub trait HalfFloat: Send + Sync + 'static {
+    fn from_f32(value: f32) -> u16;
+    fn to_f32(bits: u16) -> f32;
+}
+
+impl HalfFloat for f16 {
+    fn from_f32(value: f32) -> u16 {
+        f16::from_f32(value).to_bits()
+    }
+    fn to_f32(bits: u16) -> f32 {
+        f16::from_bits(bits).to_f32()
+    }
+}
+
+impl HalfFloat for bf16 {
+    fn from_f32(value: f32) -> u16 {
+        bf16::from_f32(value).to_bits()
+    }
+    fn to_f32(bits: u16) -> f32 {
+        bf16::from_bits(bits).to_f32()
+    }
+}
+
+/// `ColumnEncoding::F16` / `ColumnEncoding::Bf16`.
+pub struct Half<H: HalfFloat>(PhantomData<H>);
+
+impl<H: HalfFloat> Default for Half<H> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<H: HalfFloat> ColumnCodec for Half<H> {
+    fn size(&self) -> usize {
+        2
+    }
+    fn make_encoder(&self, col: SegmentColumnSchema, rows: usize) -> Box<dyn ColumnEncoder> {
+        Box::new(Encoder::<H> {
+            column: col,
+            builder: Float32Builder::with_capacity(rows),
+            half: PhantomData,
+        })
+    }
+    fn make_decoder(
+        &self,
+        col: SegmentColumnSchema,
+        arr: &dyn Array,
+    ) -> Result<Box<dyn ColumnDecoder>, MurrError> {
+        let array = downcast::<Float32Array>(arr, "Float32")?.clone();
+        Ok(Box::new(Decoder::<H> {
+            column: col,
+            array,
+            half: PhantomData,
+        }))
+    }
+}
+
+struct Encoder<H: HalfFloat> {
+    column: SegmentColumnSchema,
+    builder: Float32Builder,
+    half: PhantomData<H>,
+}
+
+impl<H: HalfFloat> ColumnEncoder for Encoder<H> {
+    fn add_row(&mut self, row: &ReadRow) -> Result<(), MurrError> {
+        if row.is_null(&self.column) {
+            self.builder.append_null();
+        } else {
+            let bits = row.read_static::<u16>(&self.column);
+            self.builder.append_value(H::to_f32(bits));
+        }
+        Ok(())
+    }
+
+    fn add_empty(&mut self) -> Result<(), MurrError> {
+        self.builder.append_null();
+        Ok(())
+    }
+
+    fn build(&mut self) -> ArrayRef {
+        Arc::new(self.builder.finish())
+    }
+}
+
+struct Decoder<H: HalfFloat> {
+    column: SegmentColumnSchema,
+    array: Float32Array,
+    half: PhantomData<H>,
+}
+
+impl<H: HalfFloat> ColumnDecoder for Decoder<H> {
+    fn write_to_row(&self, index: usize, row: &mut WriteRow) {
+        if !self.array.is_null(index) {
+            row.write_static(&self.column, H::from_f32(self.array.value(index)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ColumnEncoding, DTypeName};
+    use crate::io::schema::SegmentSchema;
+    use rstest::rstest;
+
+    fn roundtrip(encoding: ColumnEncoding, input: &Float32Array) -> Float32Array {
+        let c = SegmentColumnSchema {
+            index: 0,
+            dtype: DTypeName::Float32,
+            name: "v".into(),
+            offset: 0,
+            encoding,
+        };
+        let schema = SegmentSchema::new(std::slice::from_ref(&c));
+        assert_eq!(schema.capacity, 2);
+        let codec = c.codec();
+        let dec = codec.make_decoder(c.clone(), input).unwrap();
+        let mut enc = codec.make_encoder(c, input.len());
+        for i in 0..input.len() {
+            let mut w = WriteRow::new(&schema, "");
+            dec.write_to_row(i, &mut w);
+            enc.add_row(&ReadRow::new(&schema, &w.bytes)).unwrap();
+        }
+        enc.build()
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap()
+            .clone()
+    }
+
+    #[rstest]
+    #[case::f16(ColumnEncoding::F16, 1e-3)]
+    #[case::bf16(ColumnEncoding::Bf16, 1e-2)]
+    fn stores_two_bytes_and_reads_float32(#[case] encoding: ColumnEncoding, #[case] tol: f32) {
+        let input = Float32Array::from(vec![Some(0.125), None, Some(-0.3333), Some(1.0)]);
+        let out = roundtrip(encoding, &input);
+        assert!(out.is_null(1));
+        for i in [0, 2, 3] {
+            let (want, got) = (input.value(i), out.value(i));
+            assert!((want - got).abs() <= want.abs() * tol, "{want} vs {got}");
+        }
+        assert_eq!(out.value(0), 0.125);
+    }
+
+    #[test]
+    fn f16_saturates_to_infinity_bf16_keeps_range() {
+        let input = Float32Array::from(vec![1e6]);
+        assert!(
+            roundtrip(ColumnEncoding::F16, &input)
+                .value(0)
+                .is_infinite()
+        );
+        let wide = roundtrip(ColumnEncoding::Bf16, &input).value(0);
+        assert!((wide - 1e6).abs() < 1e4);
+    }
+}
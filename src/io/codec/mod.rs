@@ -1,6 +1,9 @@
+pub mod binary;
 pub mod bool_;
 pub mod float32;
+pub mod float32_list;
 pub mod float64;
+pub mod half_float;
 pub mod int16;
 pub mod int32;
 pub mod int64;
@@ -16,10 +19,11 @@ pub mod utf8;
 pub(crate) mod test_util;
 
 use arrow::array::{Array, ArrayRef};
+use half::{bf16, f16};
 use serde_json::Value;
 
 use crate::{
-    core::{DType, DTypeName, MurrError},
+    core::{ColumnEncoding, DType, DTypeName, MurrError},
     io::{
         row::{read::ReadRow, write::WriteRow},
         schema::SegmentColumnSchema,
@@ -50,6 +54,75 @@ pub trait ColumnDecoder: Send + Sync {
     fn write_to_row(&self, index: usize, row: &mut WriteRow);
 }
 
+/// Storage encoding of one column inside a row: how many static bytes it
+/// takes and how its Arrow values move in and out of them. Reads always
+/// produce the column's `dtype`, whatever the encoding, so encodings can be
+/// changed per column without touching `Table` or the API layers.
+///
+/// Adding an encoding means one `ColumnEncoding` variant, one impl of this
+/// trait and one arm in `SegmentColumnSchema::codec`.
+pub trait ColumnCodec: Send + Sync {
+    /// Bytes the column takes in the row's static section.
+    fn size(&self) -> usize;
+    /// Stored rows → Arrow array, used on read.
+    fn make_encoder(&self, col: SegmentColumnSchema, rows: usize) -> Box<dyn ColumnEncoder>;
+    /// Arrow array → stored rows, used on write.
+    fn make_decoder(
+        &self,
+        col: SegmentColumnSchema,
+        arr: &dyn Array,
+    ) -> Result<Box<dyn ColumnDecoder>, MurrError>;
+}
+
+/// `ColumnEncoding::Dense`: the dtype's own row representation.
+pub struct Dense(Box<dyn Codec>);
+
+impl ColumnCodec for Dense {
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+    fn make_encoder(&self, col: SegmentColumnSchema, rows: usize) -> Box<dyn ColumnEncoder> {
+        self.0.make_encoder(col, rows)
+    }
+    fn make_decoder(
+        &self,
+        col: SegmentColumnSchema,
+        arr: &dyn Array,
+    ) -> Result<Box<dyn ColumnDecoder>, MurrError> {
+        self.0.make_decoder(col, arr)
+    }
+}
+
+impl SegmentColumnSchema {
+    pub fn codec(&self) -> Box<dyn ColumnCodec> {
+        match self.encoding {
+            ColumnEncoding::Dense => Box::new(Dense(self.dtype.codec())),
+            ColumnEncoding::F16 => Box::new(half_float::Half::<f16>::default()),
+            ColumnEncoding::Bf16 => Box::new(half_float::Half::<bf16>::default()),
+        }
+    }
+}
+
+/// Rejects encodings that can't store `dtype`. Checked before a table is
+/// created or opened, so `SegmentColumnSchema::codec` can assume a valid pair.
+pub fn check_encoding(
+    column: &str,
+    dtype: DTypeName,
+    encoding: ColumnEncoding,
+) -> Result<(), MurrError> {
+    let supported = match encoding {
+        ColumnEncoding::Dense => true,
+        ColumnEncoding::F16 | ColumnEncoding::Bf16 => dtype == DTypeName::Float32,
+    };
+    if supported {
+        Ok(())
+    } else {
+        Err(MurrError::TableError(format!(
+            "column '{column}': encoding {encoding:?} is not supported for {dtype:?}"
+        )))
+    }
+}
+
 /// Aggregate trait exposing the three per-dtype roles through one trait object.
 /// A blanket impl wires every concrete per-type struct that already implements
 /// `DType + ArrowCodec + JsonCodec`.
@@ -60,6 +133,7 @@ impl DTypeName {
     pub fn codec(self) -> Box<dyn Codec> {
         match self {
             DTypeName::Utf8 => Box::new(utf8::Utf8),
+            DTypeName::Binary => Box::new(binary::Binary),
             DTypeName::Bool => Box::new(bool_::Bool),
             DTypeName::Int8 => Box::new(int8::Int8),
             DTypeName::Int16 => Box::new(int16::Int16),
@@ -71,6 +145,7 @@ impl DTypeName {
             DTypeName::UInt64 => Box::new(uint64::UInt64),
             DTypeName::Float32 => Box::new(float32::Float32),
             DTypeName::Float64 => Box::new(float64::Float64),
+            DTypeName::Float32List => Box::new(float32_list::Float32List),
         }
     }
 }
@@ -99,9 +174,33 @@ mod tests {
             dtype: DTypeName::Float32,
             name: "x".into(),
             offset: 0,
+            encoding: Default::default(),
         };
         let wrong: ArrayRef = Arc::new(StringArray::from(vec!["nope"]));
         let err = c.dtype.codec().make_decoder(c.clone(), wrong.as_ref());
         assert!(matches!(err, Err(MurrError::SegmentError(_))));
     }
+
+    #[test]
+    fn column_encoding_defaults_to_dense() {
+        let col: crate::core::ColumnSchema =
+            serde_json::from_str(r#"{"dtype": "float64"}"#).unwrap();
+        assert_eq!(col.encoding, ColumnEncoding::Dense);
+        assert_eq!(
+            serde_json::to_string(&col).unwrap(),
+            r#"{"dtype":"float64","nullable":true}"#
+        );
+
+        let segment = crate::io::schema::SegmentSchema::from(&crate::core::TableSchema {
+            key: "id".into(),
+            columns: [("id".to_string(), col.clone()), ("x".to_string(), col)]
+                .into_iter()
+                .collect(),
+            key_alias: None,
+            derived: None,
+            entity: None,
+        });
+        assert_eq!(segment.columns[0].codec().size(), 8);
+        assert_eq!(segment.capacity, 8);
+    }
 }
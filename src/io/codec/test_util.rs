@@ -12,6 +12,7 @@ fn single_column_schema(dtype: DTypeName) -> (SegmentSchema, SegmentColumnSchema
         dtype,
         name: "v".into(),
         offset: 0,
+        encoding: Default::default(),
     };
     (SegmentSchema::new(std::slice::from_ref(&c)), c)
 }
@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
 use arrow::{
-    array::{Array, ArrayRef, StringArray, StringBuilder},
-    datatypes::DataType,
+    array::{Array, ArrayRef, RecordBatch, RecordBatchOptions, StringArray, StringBuilder},
+    compute::cast,
+    datatypes::{DataType, Schema},
 };
 use serde_json::Value;
 
@@ -105,6 +106,29 @@ impl ColumnEncoder for Utf8Encoder {
     }
 }
 
+/// Re-types every utf8 column of `batch` as Utf8View for clients that asked
+/// for `StringViewArray`. The cast keeps the string payload buffer and only
+/// builds the views over it.
+pub fn to_string_view(batch: &RecordBatch) -> Result<RecordBatch, MurrError> {
+    let mut fields = Vec::with_capacity(batch.num_columns());
+    let mut arrays = Vec::with_capacity(batch.num_columns());
+    for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+        if field.data_type() == &DataType::Utf8 {
+            fields.push(field.as_ref().clone().with_data_type(DataType::Utf8View));
+            arrays.push(cast(array, &DataType::Utf8View)?);
+        } else {
+            fields.push(field.as_ref().clone());
+            arrays.push(array.clone());
+        }
+    }
+    let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+    Ok(RecordBatch::try_new_with_options(
+        Arc::new(Schema::new(fields)),
+        arrays,
+        &options,
+    )?)
+}
+
 struct Utf8Decoder {
     column: SegmentColumnSchema,
     array: StringArray,
@@ -125,7 +149,7 @@ mod tests {
         codec::test_util::{assert_json_roundtrip, assert_row_roundtrip},
         schema::SegmentSchema,
     };
-    use arrow::array::Float32Array;
+    use arrow::array::{Float32Array, StringViewArray};
     use rstest::rstest;
 
     fn single_col() -> (SegmentSchema, SegmentColumnSchema) {
@@ -134,6 +158,7 @@ mod tests {
             dtype: DTypeName::Utf8,
             name: "s".into(),
             offset: 0,
+            encoding: Default::default(),
         };
         (SegmentSchema::new(std::slice::from_ref(&c)), c)
     }
@@ -176,6 +201,32 @@ mod tests {
         assert!(matches!(err, Err(MurrError::SegmentError(_))));
     }
 
+    #[test]
+    fn string_view_converts_only_utf8_columns() {
+        let batch = RecordBatch::try_from_iter([
+            (
+                "s",
+                Arc::new(StringArray::from(vec![Some("a-rather-long-value"), None])) as ArrayRef,
+            ),
+            (
+                "f",
+                Arc::new(Float32Array::from(vec![1.0, 2.0])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        let view = to_string_view(&batch).unwrap();
+        assert_eq!(view.schema().field(0).data_type(), &DataType::Utf8View);
+        assert_eq!(view.schema().field(1).data_type(), &DataType::Float32);
+        let s = view
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringViewArray>()
+            .unwrap();
+        assert_eq!(s.value(0), "a-rather-long-value");
+        assert!(s.is_null(1));
+    }
+
     #[test]
     fn json_from_invalid_type() {
         let values = vec![Value::from(42)];
@@ -1,3 +1,4 @@
+use std::io::{Read, Seek, SeekFrom};
 use std::path::PathBuf;
 
 use crate::{
@@ -27,4 +28,38 @@ impl Filesystem for LocalFS {
     ) -> Result<RequestResult, MurrError> {
         todo!()
     }
+
+    async fn read_range(
+        &self,
+        path: &LocalURL,
+        offset: u64,
+        len: usize,
+    ) -> Result<Vec<u8>, MurrError> {
+        let path = path.path.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut file = std::fs::File::open(&path)?;
+            file.seek(SeekFrom::Start(offset))?;
+            let mut buf = vec![0; len];
+            file.read_exact(&mut buf)?;
+            Ok(buf)
+        })
+        .await
+        .map_err(|e| MurrError::IoError(e.to_string()))?
+    }
+}
+
+#[cfg(all(test, feature = "testutil"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn read_range_reads_only_the_requested_bytes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("segment");
+        std::fs::write(&path, b"0123456789").unwrap();
+        let url = LocalURL { path };
+
+        assert_eq!(LocalFS {}.read_range(&url, 3, 4).await.unwrap(), b"3456");
+        assert!(LocalFS {}.read_range(&url, 8, 4).await.is_err());
+    }
 }
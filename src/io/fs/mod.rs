@@ -28,4 +28,13 @@ pub trait Filesystem {
         remote_path: &Self::U,
         local_path: PathBuf,
     ) -> Result<RequestResult, MurrError>;
+    /// Reads `len` bytes at `offset` without fetching the whole file: a
+    /// positioned read locally, a ranged GET on object stores. Fails if the
+    /// file is shorter than `offset + len`.
+    async fn read_range(
+        &self,
+        path: &Self::U,
+        offset: u64,
+        len: usize,
+    ) -> Result<Vec<u8>, MurrError>;
 }
@@ -4,3 +4,4 @@ pub mod row;
 pub mod schema;
 pub mod store;
 pub mod table;
+pub mod timing;
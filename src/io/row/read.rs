@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use arrow::{
     array::{ArrayRef, RecordBatch},
@@ -10,6 +11,7 @@ use crate::{
     io::{
         codec::ColumnEncoder,
         schema::{SegmentColumnSchema, SegmentSchema},
+        timing::{self, Phase},
     },
 };
 
@@ -63,6 +65,8 @@ pub struct ReadBatchBuilder<'a> {
     segment: &'a SegmentSchema,
     columns: Vec<&'a SegmentColumnSchema>,
     encoders: Vec<Box<dyn ColumnEncoder>>,
+    /// Per-column decode time, only while `timing` is profiling.
+    gather: Option<Vec<Duration>>,
 }
 
 impl<'a> ReadBatchBuilder<'a> {
@@ -73,19 +77,32 @@ impl<'a> ReadBatchBuilder<'a> {
     ) -> Self {
         let encoders = columns
             .iter()
-            .map(|c| c.dtype.codec().make_encoder((*c).clone(), capacity))
+            .map(|c| c.codec().make_encoder((*c).clone(), capacity))
             .collect();
+        let gather = timing::enabled().then(|| vec![Duration::ZERO; columns.len()]);
         Self {
             segment,
             columns,
             encoders,
+            gather,
         }
     }
 
     pub fn add_row(&mut self, bytes: &[u8]) -> Result<(), MurrError> {
         let row = ReadRow::new(self.segment, bytes);
-        for e in &mut self.encoders {
-            e.add_row(&row)?;
+        match &mut self.gather {
+            None => {
+                for e in &mut self.encoders {
+                    e.add_row(&row)?;
+                }
+            }
+            Some(gather) => {
+                for (e, spent) in self.encoders.iter_mut().zip(gather.iter_mut()) {
+                    let start = Instant::now();
+                    e.add_row(&row)?;
+                    *spent += start.elapsed();
+                }
+            }
         }
         Ok(())
     }
@@ -98,6 +115,15 @@ impl<'a> ReadBatchBuilder<'a> {
     }
 
     pub fn build(mut self) -> Result<RecordBatch, MurrError> {
+        if let Some(gather) = self.gather.take() {
+            for (column, spent) in self.columns.iter().zip(gather) {
+                timing::add_gather(&column.name, spent);
+            }
+        }
+        timing::timed(Phase::Assembly, || self.finish())
+    }
+
+    fn finish(mut self) -> Result<RecordBatch, MurrError> {
         let arrays: Vec<ArrayRef> = self.encoders.iter_mut().map(|e| e.build()).collect();
         let fields: Vec<Field> = self
             .columns
@@ -17,12 +17,12 @@ impl<'a> From<WriteRow<'a>> for KeyValue {
 }
 
 impl<'a> WriteRow<'a> {
-    pub fn new(schema: &'a SegmentSchema, key: &str) -> Self {
+    pub fn new(schema: &'a SegmentSchema, key: impl AsRef<[u8]>) -> Self {
         let mut bytes = vec![0u8; schema.bitset_size + schema.capacity];
         bytes[..schema.bitset_size].fill(0xFF);
         Self {
             schema,
-            key: key.as_bytes().to_vec(),
+            key: key.as_ref().to_vec(),
             bytes,
         }
     }
@@ -64,6 +64,7 @@ mod tests {
             dtype,
             name: name.into(),
             offset,
+            encoding: Default::default(),
         }
     }
 
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use arrow::datatypes::{DataType, Field, Schema};
 use serde::{Deserialize, Serialize};
 
-use crate::core::{DTypeName, MurrError, TableSchema};
+use crate::core::{ColumnEncoding, DTypeName, MurrError, TableSchema};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SegmentColumnSchema {
@@ -11,6 +11,8 @@ pub struct SegmentColumnSchema {
     pub dtype: DTypeName,
     pub name: String,
     pub offset: u32,
+    #[serde(default)]
+    pub encoding: ColumnEncoding,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -24,7 +26,7 @@ impl SegmentSchema {
     pub fn new(columns: &[SegmentColumnSchema]) -> Self {
         SegmentSchema {
             columns: columns.to_vec(),
-            capacity: columns.iter().map(|c| c.dtype.codec().size()).sum(),
+            capacity: columns.iter().map(|c| c.codec().size()).sum(),
             bitset_size: columns.len().div_ceil(8),
         }
     }
@@ -44,8 +46,9 @@ impl From<&TableSchema> for SegmentSchema {
                     dtype: col.dtype,
                     name: name.clone(),
                     offset,
+                    encoding: col.encoding,
                 };
-                offset += col.dtype.codec().size() as u32;
+                offset += column.codec().size() as u32;
                 column
             })
             .collect();
@@ -72,6 +75,7 @@ impl TryFrom<&DataType> for DTypeName {
     fn try_from(dt: &DataType) -> Result<Self, Self::Error> {
         match dt {
             DataType::Utf8 => Ok(DTypeName::Utf8),
+            DataType::Binary => Ok(DTypeName::Binary),
             DataType::Boolean => Ok(DTypeName::Bool),
             DataType::Int8 => Ok(DTypeName::Int8),
             DataType::Int16 => Ok(DTypeName::Int16),
@@ -83,6 +87,9 @@ impl TryFrom<&DataType> for DTypeName {
             DataType::UInt64 => Ok(DTypeName::UInt64),
             DataType::Float32 => Ok(DTypeName::Float32),
             DataType::Float64 => Ok(DTypeName::Float64),
+            DataType::List(item) if item.data_type() == &DataType::Float32 => {
+                Ok(DTypeName::Float32List)
+            }
             other => Err(MurrError::SegmentError(format!(
                 "unsupported dtype {other:?}"
             ))),
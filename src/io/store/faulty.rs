@@ -0,0 +1,443 @@
+//! A `Store` wrapper that fails chosen calls, for proving that callers leave
+//! no partial state behind when the store errors out. It can also hold
+//! compactions and alter copies before they start, for checking what runs
+//! meanwhile.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+
+use arrow::array::RecordBatch;
+
+use crate::core::{Entity, MurrError, TableSchema};
+use crate::io::row::read::ReadBatchBuilder;
+use crate::io::store::{AlterCopy, Compaction, KeyValue, Manifest, Rewrite, Store, TableStats};
+
+/// Store calls a fault can be armed for. `Write` covers `write`, `delete`,
+/// `deactivate`, `reactivate` and `truncate`, `Read` covers `read` and
+/// `read_rows`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    CreateTable,
+    DropTable,
+    AlterTable,
+    RenameTable,
+    SetAlias,
+    Write,
+    Read,
+    Compact,
+}
+
+/// Makes compactions and alter copies wait before they start while held. Clones share the
+/// gate, so it can be released without going through the store lock.
+#[derive(Clone, Default)]
+pub struct CompactionGate(Arc<(Mutex<GateState>, Condvar)>);
+
+#[derive(Default)]
+struct GateState {
+    held: bool,
+    waiting: usize,
+}
+
+impl CompactionGate {
+    pub fn hold(&self) {
+        self.set_held(true);
+    }
+
+    pub fn release(&self) {
+        self.set_held(false);
+    }
+
+    /// Compactions currently waiting at the gate.
+    pub fn waiting(&self) -> usize {
+        let (state, _) = &*self.0;
+        state.lock().unwrap_or_else(PoisonError::into_inner).waiting
+    }
+
+    fn set_held(&self, held: bool) {
+        let (state, released) = &*self.0;
+        state.lock().unwrap_or_else(PoisonError::into_inner).held = held;
+        released.notify_all();
+    }
+
+    fn pass(&self) {
+        let (state, released) = &*self.0;
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.waiting += 1;
+        while state.held {
+            state = released.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+        state.waiting -= 1;
+    }
+}
+
+pub struct FaultyStore<S: Store> {
+    inner: S,
+    armed: Mutex<HashMap<Op, usize>>,
+    gate: CompactionGate,
+}
+
+impl<S: Store> FaultyStore<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            armed: Mutex::new(HashMap::new()),
+            gate: CompactionGate::default(),
+        }
+    }
+
+    pub fn compaction_gate(&self) -> CompactionGate {
+        self.gate.clone()
+    }
+
+    /// Lets `successes` calls of `op` through, fails the next one with
+    /// `MurrError::IoError` before it reaches the inner store, then disarms.
+    pub fn fail_after(&self, op: Op, successes: usize) {
+        self.armed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(op, successes);
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn check(&self, op: Op) -> Result<(), MurrError> {
+        let mut armed = self.armed.lock().unwrap_or_else(PoisonError::into_inner);
+        match armed.get_mut(&op) {
+            Some(0) => {
+                armed.remove(&op);
+                Err(MurrError::IoError(format!("injected {op:?} fault")))
+            }
+            Some(n) => {
+                *n -= 1;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl<S: Store> Store for FaultyStore<S> {
+    fn create_table(&mut self, table: &str, schema: &TableSchema) -> Result<(), MurrError> {
+        self.check(Op::CreateTable)?;
+        self.inner.create_table(table, schema)
+    }
+
+    fn drop_table(&mut self, table: &str) -> Result<(), MurrError> {
+        self.check(Op::DropTable)?;
+        self.inner.drop_table(table)
+    }
+
+    fn truncate(&mut self, table: &str) -> Result<(), MurrError> {
+        self.check(Op::Write)?;
+        self.inner.truncate(table)
+    }
+
+    fn alteration(&mut self, table: &str, schema: &TableSchema) -> Result<AlterCopy, MurrError> {
+        self.check(Op::AlterTable)?;
+        let copy = self.inner.alteration(table, schema)?;
+        let gate = self.gate.clone();
+        Ok(Box::new(move |rewrite: &Rewrite<'_>| {
+            gate.pass();
+            copy(rewrite)
+        }))
+    }
+
+    fn finish_alter(
+        &mut self,
+        table: &str,
+        copied: Result<(), MurrError>,
+        rewrite: &Rewrite<'_>,
+    ) -> Result<(), MurrError> {
+        self.inner.finish_alter(table, copied, rewrite)
+    }
+
+    fn rename_table(&mut self, from: &str, to: &str) -> Result<(), MurrError> {
+        self.check(Op::RenameTable)?;
+        self.inner.rename_table(from, to)
+    }
+
+    fn set_alias(&mut self, alias: &str, table: Option<&str>) -> Result<Option<String>, MurrError> {
+        self.check(Op::SetAlias)?;
+        self.inner.set_alias(alias, table)
+    }
+
+    fn create_entity(&mut self, name: &str, entity: &Entity) -> Result<(), MurrError> {
+        self.check(Op::CreateTable)?;
+        self.inner.create_entity(name, entity)
+    }
+
+    fn write(
+        &mut self,
+        table: &str,
+        rows: impl IntoIterator<Item = KeyValue>,
+    ) -> Result<(), MurrError> {
+        self.check(Op::Write)?;
+        self.inner.write(table, rows)
+    }
+
+    fn delete(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError> {
+        self.check(Op::Write)?;
+        self.inner.delete(table, keys)
+    }
+
+    fn deactivate(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError> {
+        self.check(Op::Write)?;
+        self.inner.deactivate(table, keys)
+    }
+
+    fn reactivate(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError> {
+        self.check(Op::Write)?;
+        self.inner.reactivate(table, keys)
+    }
+
+    fn read(
+        &self,
+        table: &str,
+        keys: &[&[u8]],
+        builder: ReadBatchBuilder<'_>,
+    ) -> Result<RecordBatch, MurrError> {
+        self.check(Op::Read)?;
+        self.inner.read(table, keys, builder)
+    }
+
+    fn read_rows(&self, table: &str, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, MurrError> {
+        self.check(Op::Read)?;
+        self.inner.read_rows(table, keys)
+    }
+
+    fn contains(&self, table: &str, keys: &[&[u8]]) -> Result<Vec<bool>, MurrError> {
+        self.inner.contains(table, keys)
+    }
+
+    fn count(&self, table: &str) -> Result<usize, MurrError> {
+        self.inner.count(table)
+    }
+
+    fn keys(
+        &self,
+        table: &str,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, MurrError> {
+        self.inner.keys(table, cursor, limit)
+    }
+
+    fn stats(&self, table: &str) -> Result<TableStats, MurrError> {
+        self.inner.stats(table)
+    }
+
+    fn compaction(&self, table: &str, key: Option<&[u8]>) -> Result<Compaction, MurrError> {
+        self.check(Op::Compact)?;
+        let compaction = self.inner.compaction(table, key)?;
+        let gate = self.gate.clone();
+        Ok(Box::new(move || {
+            gate.pass();
+            compaction()
+        }))
+    }
+
+    fn set_auto_compaction(&self, enabled: bool) -> Result<(), MurrError> {
+        self.inner.set_auto_compaction(enabled)
+    }
+
+    fn manifest(&self) -> &Manifest {
+        self.inner.manifest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{RwLock, mpsc};
+    use std::thread;
+    use std::time::Duration;
+
+    use arrow::array::{Array, Float32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use indexmap::IndexMap;
+
+    use super::*;
+    use crate::conf::RowCacheConfig;
+    use crate::core::{ColumnSchema, DTypeName};
+    use crate::io::store::memory::MemoryStore;
+    use crate::io::table::Table;
+
+    type Faulty = Arc<RwLock<FaultyStore<MemoryStore>>>;
+
+    fn store() -> Faulty {
+        Arc::new(RwLock::new(FaultyStore::new(MemoryStore::new())))
+    }
+
+    fn arm(store: &Faulty, op: Op, successes: usize) {
+        store.read().unwrap().fail_after(op, successes);
+    }
+
+    fn schema() -> TableSchema {
+        let column = |dtype, nullable| ColumnSchema {
+            dtype,
+            nullable,
+            encoding: Default::default(),
+        };
+        let mut columns = IndexMap::new();
+        columns.insert("id".into(), column(DTypeName::Utf8, false));
+        columns.insert("score".into(), column(DTypeName::Float32, true));
+        TableSchema {
+            key: "id".into(),
+            columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
+        }
+    }
+
+    fn batch(ids: &[&str], scores: &[f32]) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("score", DataType::Float32, true),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(ids.to_vec())),
+                Arc::new(Float32Array::from(scores.to_vec())),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn scores(table: &Table<FaultyStore<MemoryStore>>, keys: &[&str]) -> Vec<Option<f32>> {
+        let out = table.read(keys, &["score"]).unwrap();
+        let arr = out
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        (0..arr.len())
+            .map(|i| (!arr.is_null(i)).then(|| arr.value(i)))
+            .collect()
+    }
+
+    #[test]
+    fn failed_create_leaves_no_table() {
+        let store = store();
+        arm(&store, Op::CreateTable, 0);
+        assert!(Table::create(store.clone(), "t", schema()).is_err());
+        assert!(!store.read().unwrap().manifest().contains("t"));
+        assert!(store.read().unwrap().inner().tables.is_empty());
+
+        Table::create(store.clone(), "t", schema()).unwrap();
+        assert!(store.read().unwrap().manifest().contains("t"));
+    }
+
+    #[test]
+    fn failed_write_keeps_previous_rows_and_cache() {
+        let store = store();
+        let table = Table::create(store.clone(), "t", schema())
+            .unwrap()
+            .with_row_cache(&RowCacheConfig {
+                max_rows: 16,
+                columns: vec![],
+            });
+        table.write(&batch(&["a"], &[1.0])).unwrap();
+        assert_eq!(scores(&table, &["a", "b"]), vec![Some(1.0), None]);
+
+        arm(&store, Op::Write, 1);
+        table.write(&batch(&["c"], &[3.0])).unwrap();
+        assert!(table.write(&batch(&["a", "b"], &[2.0, 2.0])).is_err());
+        assert_eq!(
+            scores(&table, &["a", "b", "c"]),
+            vec![Some(1.0), None, Some(3.0)]
+        );
+        assert_eq!(store.read().unwrap().inner().tables["t"].len(), 2);
+
+        table.write(&batch(&["a", "b"], &[2.0, 2.0])).unwrap();
+        assert_eq!(scores(&table, &["a", "b"]), vec![Some(2.0), Some(2.0)]);
+    }
+
+    #[test]
+    fn failed_alter_keeps_table_as_it_was() {
+        let store = store();
+        let table = Table::create(store.clone(), "t", schema()).unwrap();
+        table.write(&batch(&["a"], &[1.0])).unwrap();
+        let mut altered = schema();
+        altered.columns.insert(
+            "clicks".into(),
+            ColumnSchema {
+                dtype: DTypeName::Int64,
+                nullable: true,
+                encoding: Default::default(),
+            },
+        );
+
+        arm(&store, Op::AlterTable, 0);
+        assert!(table.alter(altered.clone()).is_err());
+        assert_eq!(
+            store.read().unwrap().manifest().schema("t"),
+            Some(&schema())
+        );
+        assert_eq!(scores(&table, &["a"]), vec![Some(1.0)]);
+
+        let table = table.alter(altered).unwrap();
+        assert_eq!(scores(&table, &["a"]), vec![Some(1.0)]);
+    }
+
+    #[test]
+    fn failed_rename_keeps_old_name() {
+        let store = store();
+        let table = Table::create(store.clone(), "t", schema()).unwrap();
+        table.write(&batch(&["a"], &[1.0])).unwrap();
+
+        arm(&store, Op::RenameTable, 0);
+        assert!(table.rename("u").is_err());
+        assert!(store.read().unwrap().manifest().contains("t"));
+        assert_eq!(scores(&table, &["a"]), vec![Some(1.0)]);
+
+        let table = table.rename("u").unwrap();
+        assert!(!store.read().unwrap().manifest().contains("t"));
+        assert_eq!(scores(&table, &["a"]), vec![Some(1.0)]);
+    }
+
+    #[test]
+    fn failed_read_fills_no_cache() {
+        let store = store();
+        let table = Table::create(store.clone(), "t", schema())
+            .unwrap()
+            .with_row_cache(&RowCacheConfig {
+                max_rows: 16,
+                columns: vec![],
+            });
+        table.write(&batch(&["a"], &[1.0])).unwrap();
+
+        arm(&store, Op::Read, 0);
+        assert!(table.read(&["a"], &["score"]).is_err());
+        assert_eq!(table.stats().unwrap().row_cache.unwrap().rows, 0);
+        assert_eq!(scores(&table, &["a"]), vec![Some(1.0)]);
+    }
+
+    #[test]
+    fn reads_and_writes_go_on_during_rebuild() {
+        let store = store();
+        let table = Table::create(store.clone(), "t", schema()).unwrap();
+        table.write(&batch(&["a"], &[1.0])).unwrap();
+        let gate = store.read().unwrap().compaction_gate();
+        gate.hold();
+
+        let table = &table;
+        thread::scope(|s| {
+            let rebuild = s.spawn(|| table.rebuild());
+            while gate.waiting() == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+            let (tx, rx) = mpsc::channel();
+            s.spawn(move || {
+                table.write(&batch(&["b"], &[2.0])).unwrap();
+                tx.send(scores(&table, &["a", "b"])).unwrap();
+            });
+            let during = rx.recv_timeout(Duration::from_secs(5));
+            gate.release();
+            assert_eq!(during, Ok(vec![Some(1.0), Some(2.0)]));
+            rebuild.join().unwrap().unwrap();
+        });
+    }
+}
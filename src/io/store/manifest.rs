@@ -5,13 +5,26 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
-use crate::core::{MurrError, TableSchema};
+use crate::core::{Entity, MurrError, TableSchema};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Manifest {
     pub version: u64,
     pub updated_at: u64,
     pub tables: HashMap<String, TableSchema>,
+    #[serde(default)]
+    pub entities: HashMap<String, Entity>,
+    /// Earlier schemas of altered tables, oldest first. A table's schema
+    /// version is the number of schemas it had before the current one.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub schema_history: HashMap<String, Vec<TableSchema>>,
+    /// RocksDB column family of tables whose rows don't live under the
+    /// table's own name, i.e. tables rewritten by `alter_table` or renamed.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub column_families: HashMap<String, String>,
+    /// Alternative names readers use for a table, alias to table.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub aliases: HashMap<String, String>,
 }
 
 impl Default for Manifest {
@@ -20,6 +33,10 @@ impl Default for Manifest {
             version: 1,
             updated_at: now_secs(),
             tables: HashMap::new(),
+            entities: HashMap::new(),
+            schema_history: HashMap::new(),
+            column_families: HashMap::new(),
+            aliases: HashMap::new(),
         }
     }
 }
@@ -63,14 +80,95 @@ impl Manifest {
         Ok(())
     }
 
+    /// Also forgets the table's schema history, so a table created later
+    /// under the same name starts at version 0.
     pub fn del_table(&mut self, name: &str) -> Result<(), MurrError> {
         if self.tables.remove(name).is_none() {
             return Err(MurrError::TableNotFound(name.to_string()));
         }
+        self.schema_history.remove(name);
+        self.column_families.remove(name);
         self.updated_at = now_secs();
         Ok(())
     }
 
+    /// Replaces the schema of an existing table and keeps the old one in
+    /// `schema_history`. Returns the new schema version.
+    pub fn alter_table(&mut self, name: &str, schema: &TableSchema) -> Result<u32, MurrError> {
+        let current = self
+            .tables
+            .get_mut(name)
+            .ok_or_else(|| MurrError::TableNotFound(name.to_string()))?;
+        let previous = std::mem::replace(current, schema.clone());
+        let history = self.schema_history.entry(name.to_string()).or_default();
+        history.push(previous);
+        self.updated_at = now_secs();
+        Ok(history.len() as u32)
+    }
+
+    /// Moves `from` to `to` with its schema history, keeping its rows where
+    /// they are: the column family stays the old one, recorded in
+    /// `column_families`. Aliases of `from` now point at `to`.
+    pub fn rename_table(&mut self, from: &str, to: &str) -> Result<(), MurrError> {
+        if self.tables.contains_key(to) {
+            return Err(MurrError::TableAlreadyExists(to.to_string()));
+        }
+        let schema = self
+            .tables
+            .remove(from)
+            .ok_or_else(|| MurrError::TableNotFound(from.to_string()))?;
+        self.tables.insert(to.to_string(), schema);
+        if let Some(history) = self.schema_history.remove(from) {
+            self.schema_history.insert(to.to_string(), history);
+        }
+        let cf = self
+            .column_families
+            .remove(from)
+            .unwrap_or_else(|| from.to_string());
+        if cf != to {
+            self.column_families.insert(to.to_string(), cf);
+        }
+        for table in self.aliases.values_mut() {
+            if table == from {
+                *table = to.to_string();
+            }
+        }
+        self.updated_at = now_secs();
+        Ok(())
+    }
+
+    /// Points `alias` at `table`, or removes it when `table` is `None`.
+    /// Returns the table it pointed at before.
+    pub fn set_alias(&mut self, alias: &str, table: Option<&str>) -> Option<String> {
+        let previous = match table {
+            Some(table) => self.aliases.insert(alias.to_string(), table.to_string()),
+            None => self.aliases.remove(alias),
+        };
+        self.updated_at = now_secs();
+        previous
+    }
+
+    /// 0 for tables that were never altered.
+    pub fn schema_version(&self, name: &str) -> u32 {
+        self.schema_history.get(name).map_or(0, Vec::len) as u32
+    }
+
+    /// Registers `entity`. Returns false when the same definition is already
+    /// there; a different one is an error, since tables may rely on it.
+    pub fn add_entity(&mut self, name: &str, entity: &Entity) -> Result<bool, MurrError> {
+        match self.entities.get(name) {
+            Some(existing) if existing == entity => Ok(false),
+            Some(_) => Err(MurrError::TableError(format!(
+                "entity '{name}' is already registered with a different definition"
+            ))),
+            None => {
+                self.entities.insert(name.to_string(), entity.clone());
+                self.updated_at = now_secs();
+                Ok(true)
+            }
+        }
+    }
+
     pub fn contains(&self, name: &str) -> bool {
         self.tables.contains_key(name)
     }
@@ -101,6 +199,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: false,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -108,11 +207,15 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Float32,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         TableSchema {
             key: "id".into(),
             columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
         }
     }
 
@@ -130,6 +233,29 @@ mod tests {
         assert_eq!(loaded.schema("users"), Some(&schema_id_score()));
     }
 
+    /// A crash between writing the temp file and renaming it leaves the
+    /// previous manifest in place; the next save overwrites the leftover.
+    #[test]
+    fn crash_before_rename_keeps_previous_manifest() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("manifest.json");
+        let mut m = Manifest::new();
+        m.add_table("users", &schema_id_score()).unwrap();
+        m.to_file(&path).unwrap();
+        fs::write(
+            dir.path().join("manifest.json.tmp"),
+            b"{\"version\": 1, \"tab",
+        )
+        .unwrap();
+
+        let loaded = Manifest::from_file(&path).unwrap();
+        assert_eq!(loaded, m);
+
+        m.add_table("items", &schema_id_score()).unwrap();
+        m.to_file(&path).unwrap();
+        assert_eq!(Manifest::from_file(&path).unwrap(), m);
+    }
+
     #[test]
     fn from_missing_file_returns_empty() {
         let dir = TempDir::new().unwrap();
@@ -153,6 +279,62 @@ mod tests {
         assert!(matches!(err, MurrError::TableNotFound(_)));
     }
 
+    #[test]
+    fn alter_keeps_history_until_drop() {
+        let mut m = Manifest::new();
+        m.add_table("t", &schema_id_score()).unwrap();
+        let mut altered = schema_id_score();
+        altered.columns.shift_remove("score");
+        assert_eq!(m.alter_table("t", &altered).unwrap(), 1);
+        assert_eq!(m.schema("t"), Some(&altered));
+        assert_eq!(m.schema_history["t"], vec![schema_id_score()]);
+        assert_eq!(m.schema_version("t"), 1);
+        assert!(matches!(
+            m.alter_table("nope", &altered).unwrap_err(),
+            MurrError::TableNotFound(_)
+        ));
+
+        m.del_table("t").unwrap();
+        m.add_table("t", &schema_id_score()).unwrap();
+        assert_eq!(m.schema_version("t"), 0);
+    }
+
+    #[test]
+    fn rename_moves_history_and_aliases() {
+        let mut m = Manifest::new();
+        m.add_table("a", &schema_id_score()).unwrap();
+        m.add_table("b", &schema_id_score()).unwrap();
+        let mut altered = schema_id_score();
+        altered.columns.shift_remove("score");
+        m.alter_table("a", &altered).unwrap();
+        m.column_families
+            .insert("a".to_string(), "a\u{1f}v1".to_string());
+        assert_eq!(m.set_alias("prod", Some("a")), None);
+
+        m.rename_table("a", "c").unwrap();
+        assert!(!m.contains("a"));
+        assert_eq!(m.schema("c"), Some(&altered));
+        assert_eq!(m.schema_version("c"), 1);
+        assert_eq!(m.column_families["c"], "a\u{1f}v1");
+        assert_eq!(m.aliases["prod"], "c");
+
+        m.rename_table("b", "a").unwrap();
+        assert_eq!(m.column_families["a"], "b");
+        m.rename_table("a", "b").unwrap();
+        assert!(!m.column_families.contains_key("b"));
+
+        assert!(matches!(
+            m.rename_table("b", "c").unwrap_err(),
+            MurrError::TableAlreadyExists(_)
+        ));
+        assert!(matches!(
+            m.rename_table("a", "d").unwrap_err(),
+            MurrError::TableNotFound(_)
+        ));
+        assert_eq!(m.set_alias("prod", None), Some("c".to_string()));
+        assert!(m.aliases.is_empty());
+    }
+
     #[test]
     fn add_then_del() {
         let mut m = Manifest::new();
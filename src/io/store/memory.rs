@@ -2,13 +2,17 @@ use std::collections::HashMap;
 
 use arrow::array::RecordBatch;
 
-use crate::core::{MurrError, TableSchema};
+use crate::core::{Entity, MurrError, TableSchema};
 use crate::io::row::read::ReadBatchBuilder;
-use crate::io::store::{KeyValue, Manifest, Store};
+use crate::io::store::{AlterCopy, Compaction, KeyValue, Manifest, Rewrite, Store, TableStats};
 
 #[derive(Default)]
 pub struct MemoryStore {
     pub tables: HashMap<String, HashMap<Vec<u8>, Vec<u8>>>,
+    /// Deactivated rows per table.
+    hidden: HashMap<String, HashMap<Vec<u8>, Vec<u8>>>,
+    /// New schemas of tables between `alteration` and `finish_alter`.
+    altering: HashMap<String, TableSchema>,
     manifest: Manifest,
 }
 
@@ -16,6 +20,15 @@ impl MemoryStore {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn check_not_altering(&self, table: &str) -> Result<(), MurrError> {
+        if self.altering.contains_key(table) {
+            return Err(MurrError::TableError(format!(
+                "'{table}' is being altered, try again"
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl Store for MemoryStore {
@@ -25,6 +38,96 @@ impl Store for MemoryStore {
         Ok(())
     }
 
+    fn drop_table(&mut self, table: &str) -> Result<(), MurrError> {
+        self.check_not_altering(table)?;
+        self.manifest.del_table(table)?;
+        self.tables.remove(table);
+        self.hidden.remove(table);
+        Ok(())
+    }
+
+    fn truncate(&mut self, table: &str) -> Result<(), MurrError> {
+        self.check_not_altering(table)?;
+        self.tables
+            .get_mut(table)
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?
+            .clear();
+        self.hidden.remove(table);
+        Ok(())
+    }
+
+    /// Nothing to copy ahead: `finish_alter` rewrites the rows in memory.
+    fn alteration(&mut self, table: &str, schema: &TableSchema) -> Result<AlterCopy, MurrError> {
+        if !self.tables.contains_key(table) {
+            return Err(MurrError::TableNotFound(table.to_string()));
+        }
+        self.check_not_altering(table)?;
+        self.altering.insert(table.to_string(), schema.clone());
+        Ok(Box::new(|_: &Rewrite<'_>| Ok(())))
+    }
+
+    /// Rewrites the rows in one go, so a failed rewrite changes nothing.
+    fn finish_alter(
+        &mut self,
+        table: &str,
+        copied: Result<(), MurrError>,
+        rewrite: &Rewrite<'_>,
+    ) -> Result<(), MurrError> {
+        let schema = self
+            .altering
+            .remove(table)
+            .ok_or_else(|| MurrError::TableError(format!("'{table}' is not being altered")))?;
+        copied?;
+        let rewrite_all = |rows: &HashMap<Vec<u8>, Vec<u8>>| {
+            rewrite(
+                rows.iter()
+                    .map(|(key, value)| KeyValue::new(key.clone(), value.clone()))
+                    .collect(),
+            )
+            .map(|rows| {
+                rows.into_iter()
+                    .map(|kv| (kv.key, kv.value))
+                    .collect::<HashMap<_, _>>()
+            })
+        };
+        let rows = self
+            .tables
+            .get(table)
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        let rows = rewrite_all(rows)?;
+        let hidden = match self.hidden.get(table) {
+            Some(hidden) => Some(rewrite_all(hidden)?),
+            None => None,
+        };
+        self.manifest.alter_table(table, &schema)?;
+        self.tables.insert(table.to_string(), rows);
+        if let Some(hidden) = hidden {
+            self.hidden.insert(table.to_string(), hidden);
+        }
+        Ok(())
+    }
+
+    fn rename_table(&mut self, from: &str, to: &str) -> Result<(), MurrError> {
+        self.check_not_altering(from)?;
+        self.manifest.rename_table(from, to)?;
+        if let Some(rows) = self.tables.remove(from) {
+            self.tables.insert(to.to_string(), rows);
+        }
+        if let Some(hidden) = self.hidden.remove(from) {
+            self.hidden.insert(to.to_string(), hidden);
+        }
+        Ok(())
+    }
+
+    fn set_alias(&mut self, alias: &str, table: Option<&str>) -> Result<Option<String>, MurrError> {
+        Ok(self.manifest.set_alias(alias, table))
+    }
+
+    fn create_entity(&mut self, name: &str, entity: &Entity) -> Result<(), MurrError> {
+        self.manifest.add_entity(name, entity)?;
+        Ok(())
+    }
+
     fn read(
         &self,
         table: &str,
@@ -44,6 +147,48 @@ impl Store for MemoryStore {
         builder.build()
     }
 
+    fn read_rows(&self, table: &str, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, MurrError> {
+        let rows = self
+            .tables
+            .get(table)
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        Ok(keys.iter().map(|k| rows.get(*k).cloned()).collect())
+    }
+
+    fn contains(&self, table: &str, keys: &[&[u8]]) -> Result<Vec<bool>, MurrError> {
+        let rows = self
+            .tables
+            .get(table)
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        Ok(keys.iter().map(|k| rows.contains_key(*k)).collect())
+    }
+
+    fn count(&self, table: &str) -> Result<usize, MurrError> {
+        let rows = self
+            .tables
+            .get(table)
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        Ok(rows.len())
+    }
+
+    fn keys(
+        &self,
+        table: &str,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, MurrError> {
+        let rows = self
+            .tables
+            .get(table)
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        let mut keys: Vec<&Vec<u8>> = rows
+            .keys()
+            .filter(|k| cursor.is_none_or(|c| k.as_slice() > c))
+            .collect();
+        keys.sort_unstable();
+        Ok(keys.into_iter().take(limit).cloned().collect())
+    }
+
     fn write(
         &mut self,
         table: &str,
@@ -53,13 +198,78 @@ impl Store for MemoryStore {
             .tables
             .get_mut(table)
             .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        let mut hidden = self.hidden.get_mut(table);
         for row in rows {
+            if let Some(hidden) = hidden.as_mut() {
+                hidden.remove(&row.key);
+            }
             entries.insert(row.key, row.value);
         }
         Ok(())
     }
 
-    fn compact(&self, _table: &str) -> Result<(), MurrError> {
+    fn delete(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError> {
+        let entries = self
+            .tables
+            .get_mut(table)
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        let mut hidden = self.hidden.get_mut(table);
+        for key in keys {
+            entries.remove(*key);
+            if let Some(hidden) = hidden.as_mut() {
+                hidden.remove(*key);
+            }
+        }
+        Ok(())
+    }
+
+    fn deactivate(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError> {
+        let entries = self
+            .tables
+            .get_mut(table)
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        let hidden = self.hidden.entry(table.to_string()).or_default();
+        for key in keys {
+            if let Some((key, row)) = entries.remove_entry(*key) {
+                hidden.insert(key, row);
+            }
+        }
+        Ok(())
+    }
+
+    fn reactivate(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError> {
+        let entries = self
+            .tables
+            .get_mut(table)
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        let Some(hidden) = self.hidden.get_mut(table) else {
+            return Ok(());
+        };
+        for key in keys {
+            if let Some((key, row)) = hidden.remove_entry(*key) {
+                entries.insert(key, row);
+            }
+        }
+        Ok(())
+    }
+
+    fn stats(&self, table: &str) -> Result<TableStats, MurrError> {
+        let rows = self
+            .tables
+            .get(table)
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        Ok(TableStats {
+            estimated_keys: rows.len() as u64,
+            memtable_bytes: rows.iter().map(|(k, v)| (k.len() + v.len()) as u64).sum(),
+            ..TableStats::default()
+        })
+    }
+
+    fn compaction(&self, _table: &str, _key: Option<&[u8]>) -> Result<Compaction, MurrError> {
+        Ok(Box::new(|| Ok(())))
+    }
+
+    fn set_auto_compaction(&self, _enabled: bool) -> Result<(), MurrError> {
         Ok(())
     }
 
@@ -82,6 +292,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: false,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -89,11 +300,15 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         TableSchema {
             key: "id".into(),
             columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
         }
     }
 
@@ -148,6 +363,29 @@ mod tests {
         assert!(matches!(err, MurrError::TableNotFound(_)));
     }
 
+    #[test]
+    fn write_brings_back_deactivated_key() {
+        let mut store = MemoryStore::new();
+        store.create_table("users", &schema()).unwrap();
+        put(&mut store, "users", &[("alice", b"old"), ("bob", b"b")]);
+
+        let alice: [&[u8]; 1] = [b"alice"];
+        let keys: [&[u8]; 2] = [b"alice", b"nope"];
+        store.deactivate("users", &keys).unwrap();
+        assert_eq!(fetch(&store, "users", &alice), vec![None]);
+        assert_eq!(store.count("users").unwrap(), 1);
+        assert_eq!(store.hidden["users"].len(), 1);
+
+        put(&mut store, "users", &[("alice", b"new")]);
+        assert_eq!(fetch(&store, "users", &alice), vec![Some(b"new".to_vec())]);
+        assert!(store.hidden["users"].is_empty());
+
+        store.deactivate("users", &alice).unwrap();
+        store.reactivate("users", &keys).unwrap();
+        assert_eq!(fetch(&store, "users", &alice), vec![Some(b"new".to_vec())]);
+        assert!(store.hidden["users"].is_empty());
+    }
+
     #[test]
     fn create_duplicate_table_fails() {
         let mut store = MemoryStore::new();
@@ -163,4 +401,30 @@ mod tests {
         assert!(store.manifest().contains("users"));
         assert_eq!(store.manifest().schema("users"), Some(&schema()));
     }
+
+    #[test]
+    fn truncate_keeps_schema_and_drop_forgets_table() {
+        let mut store = MemoryStore::new();
+        store.create_table("users", &schema()).unwrap();
+        put(&mut store, "users", &[("alice", b"a"), ("bob", b"b")]);
+        let bob: [&[u8]; 1] = [b"bob"];
+        store.deactivate("users", &bob).unwrap();
+
+        store.truncate("users").unwrap();
+        assert_eq!(store.count("users").unwrap(), 0);
+        assert!(!store.hidden.contains_key("users"));
+        assert!(store.manifest().contains("users"));
+
+        store.drop_table("users").unwrap();
+        assert!(!store.manifest().contains("users"));
+        assert!(matches!(
+            store.count("users").unwrap_err(),
+            MurrError::TableNotFound(_)
+        ));
+        assert!(matches!(
+            store.truncate("users").unwrap_err(),
+            MurrError::TableNotFound(_)
+        ));
+        store.create_table("users", &schema()).unwrap();
+    }
 }
@@ -1,8 +1,12 @@
 use arrow::array::RecordBatch;
+use serde::Serialize;
 
-use crate::core::{MurrError, TableSchema};
+use crate::core::{Entity, MurrError, TableSchema};
 use crate::io::row::read::ReadBatchBuilder;
+use crate::io::table::cache::RowCacheStats;
 
+#[cfg(any(test, feature = "testutil"))]
+pub mod faulty;
 pub mod manifest;
 pub mod memory;
 pub mod rocksdb;
@@ -13,6 +17,39 @@ pub(crate) mod test_util;
 
 pub use manifest::Manifest;
 
+/// Size of one table as reported by its store. RocksDB figures are estimates.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TableStats {
+    /// RocksDB's estimate, which counts an overwritten key once per file
+    /// still holding a version of it. `Store::count` is exact.
+    pub estimated_keys: u64,
+    /// Memory held by index and filter blocks of the table's SST readers.
+    pub index_bytes: u64,
+    /// Data still in memtables, not yet flushed to SST files.
+    pub memtable_bytes: u64,
+    pub disk_bytes: u64,
+    /// Set by `Table` when `server.row_cache` is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_cache: Option<RowCacheStats>,
+    /// Set by the block backend when `block_cache_mb > 0`. The cache is
+    /// shared by every table on the store, so these are store-wide figures.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_cache: Option<BlockCacheStats>,
+    /// Reads that failed on corrupt data since the store was opened, across
+    /// all tables. Reads only check block checksums with `verify_checksums`
+    /// set; without it, a flipped bit in a data block can be returned as is.
+    pub checksum_failures: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BlockCacheStats {
+    pub capacity_bytes: u64,
+    pub usage_bytes: u64,
+    /// Block lookups since the store was opened.
+    pub hits: u64,
+    pub misses: u64,
+}
+
 pub struct KeyValue {
     pub key: Vec<u8>,
     pub value: Vec<u8>,
@@ -27,19 +64,100 @@ impl KeyValue {
     }
 }
 
+/// A compaction prepared under the store lock, to run after releasing it.
+pub type Compaction = Box<dyn FnOnce() -> Result<(), MurrError> + Send>;
+
+/// Re-encodes a chunk of stored rows for a table's new schema.
+pub type Rewrite<'a> = dyn Fn(Vec<KeyValue>) -> Result<Vec<KeyValue>, MurrError> + 'a;
+
+/// The row copy of an `alter_table`, prepared under the store lock to run
+/// after releasing it.
+pub type AlterCopy = Box<dyn FnOnce(&Rewrite<'_>) -> Result<(), MurrError> + Send>;
+
 pub trait Store: Send + Sync + 'static {
     fn create_table(&mut self, table: &str, schema: &TableSchema) -> Result<(), MurrError>;
+    /// Removes the table, its rows and its manifest entry.
+    fn drop_table(&mut self, table: &str) -> Result<(), MurrError>;
+    /// Removes every row of the table, deactivated ones included, and keeps
+    /// its schema.
+    fn truncate(&mut self, table: &str) -> Result<(), MurrError>;
+    /// Starts switching the table to `schema`. The returned copy passes
+    /// every stored row, deactivated ones included, through its rewrite a
+    /// chunk at a time; it needs no store lock, and reads and writes keep
+    /// the old layout meanwhile. The table can't be dropped, renamed,
+    /// truncated or altered again until `finish_alter`.
+    fn alteration(&mut self, table: &str, schema: &TableSchema) -> Result<AlterCopy, MurrError>;
+    /// Ends the alteration of `table` once its copy returned `copied`: rows
+    /// written since it started go through `rewrite` again, then the table
+    /// switches to the new schema and the manifest's history records the
+    /// old one. Either every row is rewritten or the table is left as it
+    /// was, which is also what a failed copy gets.
+    fn finish_alter(
+        &mut self,
+        table: &str,
+        copied: Result<(), MurrError>,
+        rewrite: &Rewrite<'_>,
+    ) -> Result<(), MurrError>;
+    fn alter_table(
+        &mut self,
+        table: &str,
+        schema: &TableSchema,
+        rewrite: &Rewrite<'_>,
+    ) -> Result<(), MurrError> {
+        let copied = self.alteration(table, schema)?(rewrite);
+        self.finish_alter(table, copied, rewrite)
+    }
+    /// Moves the table to a new name without touching its rows; see
+    /// `Manifest::rename_table`.
+    fn rename_table(&mut self, from: &str, to: &str) -> Result<(), MurrError>;
+    /// Points `alias` at `table`, or removes it with `None`, in the manifest.
+    /// Returns the table it pointed at before.
+    fn set_alias(&mut self, alias: &str, table: Option<&str>) -> Result<Option<String>, MurrError>;
+    /// Registers an entity in the manifest; see `Manifest::add_entity`.
+    fn create_entity(&mut self, name: &str, entity: &Entity) -> Result<(), MurrError>;
+    /// Also drops deactivated copies of the written keys.
     fn write(
         &mut self,
         table: &str,
         rows: impl IntoIterator<Item = KeyValue>,
     ) -> Result<(), MurrError>;
+    /// Removes `keys`, deactivated copies included; absent keys are ignored.
+    fn delete(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError>;
+    /// Moves the rows of `keys` aside, so every other call sees them as
+    /// absent until a write or `reactivate` brings the key back. Absent keys
+    /// are ignored.
+    fn deactivate(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError>;
+    /// Moves the deactivated rows of `keys` back as they were. Keys without
+    /// a deactivated row are ignored.
+    fn reactivate(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError>;
     fn read(
         &self,
         table: &str,
         keys: &[&[u8]],
         builder: ReadBatchBuilder<'_>,
     ) -> Result<RecordBatch, MurrError>;
-    fn compact(&self, table: &str) -> Result<(), MurrError>;
+    /// Stored rows for `keys`, `None` for absent ones. Fills the row cache.
+    fn read_rows(&self, table: &str, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, MurrError>;
+    fn contains(&self, table: &str, keys: &[&[u8]]) -> Result<Vec<bool>, MurrError>;
+    fn count(&self, table: &str) -> Result<usize, MurrError>;
+    /// Up to `limit` keys in byte order, strictly after `cursor` when given.
+    fn keys(
+        &self,
+        table: &str,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, MurrError>;
+    fn stats(&self, table: &str) -> Result<TableStats, MurrError>;
+    /// Compacts the table, or only the rows of `key` when given, once the
+    /// returned closure runs. It needs no store lock, so reads and writes go
+    /// on meanwhile. Fails here, not in the closure, on an unknown table.
+    fn compaction(&self, table: &str, key: Option<&[u8]>) -> Result<Compaction, MurrError>;
+    fn compact(&self, table: &str) -> Result<(), MurrError> {
+        self.compaction(table, None)?()
+    }
+    /// Pauses or resumes background compaction for every table. Tables
+    /// created while paused start paused, and resuming restores the storage
+    /// config's setting rather than forcing compaction on.
+    fn set_auto_compaction(&self, enabled: bool) -> Result<(), MurrError>;
     fn manifest(&self) -> &Manifest;
 }
@@ -1,4 +1,9 @@
-use rocksdb::{BlockBasedOptions, Cache, DataBlockIndexType, Options};
+use std::collections::HashMap;
+
+use rocksdb::statistics::StatsLevel;
+use rocksdb::{
+    BlockBasedIndexType, BlockBasedOptions, Cache, DBCompressionType, DataBlockIndexType, Options,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::io::store::rocksdb::ReadMethod;
@@ -16,7 +21,7 @@ pub struct BlockConfig {
     pub whole_key_filtering: bool,
     #[serde(default = "default_block_size")]
     pub block_size: usize,
-    /// LRU block cache size in MiB. 0 disables.
+    /// LRU block cache size in MiB, shared by all tables. 0 disables.
     #[serde(default)]
     pub block_cache_mb: usize,
     #[serde(default)]
@@ -29,6 +34,11 @@ pub struct BlockConfig {
     pub data_block_hash_index: bool,
     #[serde(default = "default_data_block_hash_ratio")]
     pub data_block_hash_ratio: f64,
+    #[serde(default)]
+    pub index_type: IndexType,
+    /// Data block compression. RocksDB's default (snappy) when unset.
+    #[serde(default)]
+    pub compression: Option<Compression>,
     #[serde(default = "default_true")]
     pub mmap_reads: bool,
     /// Open SST files with O_DIRECT, bypassing the OS page cache.
@@ -37,6 +47,9 @@ pub struct BlockConfig {
     pub use_direct_reads: bool,
     #[serde(default = "default_true")]
     pub async_io: bool,
+    /// Check each data block's checksum on every read and scan, not only
+    /// when files are opened and compacted. Failures are counted in
+    /// `TableStats::checksum_failures`.
     #[serde(default)]
     pub verify_checksums: bool,
     #[serde(default = "default_write_buffer_size")]
@@ -45,8 +58,37 @@ pub struct BlockConfig {
     pub target_file_size_base: u64,
     #[serde(default = "default_disable_auto_compactions")]
     pub disable_auto_compactions: bool,
+    /// L0 files that trigger a background compaction into the levels below.
+    /// Every write is flushed to its own L0 file, so this bounds how many
+    /// small files a lookup may have to check. RocksDB's default (4) when
+    /// unset.
+    #[serde(default)]
+    pub l0_compaction_trigger: Option<i32>,
     #[serde(default = "default_block_read_method")]
     pub read_method: ReadMethod,
+    /// Per-table overrides of the table-format settings, keyed by table name.
+    #[serde(default)]
+    pub tables: HashMap<String, BlockTableConfig>,
+}
+
+/// Table-format settings a single table may override; unset fields fall back
+/// to the backend-wide value. Tables still share one block cache.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BlockTableConfig {
+    /// `0` turns off a backend-wide bloom filter for this table.
+    #[serde(default)]
+    pub bloom_filter_bits_per_key: Option<f64>,
+    #[serde(default)]
+    pub block_size: Option<usize>,
+    #[serde(default)]
+    pub index_type: Option<IndexType>,
+    #[serde(default)]
+    pub compression: Option<Compression>,
+    #[serde(default)]
+    pub cache_index_and_filter_blocks: Option<bool>,
+    #[serde(default)]
+    pub pin_l0_filter_and_index_blocks: Option<bool>,
 }
 
 impl Default for BlockConfig {
@@ -61,6 +103,8 @@ impl Default for BlockConfig {
             block_restart_interval: default_block_restart_interval(),
             data_block_hash_index: true,
             data_block_hash_ratio: default_data_block_hash_ratio(),
+            index_type: IndexType::default(),
+            compression: None,
             mmap_reads: true,
             use_direct_reads: false,
             async_io: true,
@@ -68,7 +112,46 @@ impl Default for BlockConfig {
             write_buffer_size: default_write_buffer_size(),
             target_file_size_base: default_target_file_size_base(),
             disable_auto_compactions: default_disable_auto_compactions(),
+            l0_compaction_trigger: None,
             read_method: default_block_read_method(),
+            tables: HashMap::new(),
+        }
+    }
+}
+
+/// How SSTs index their data blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexType {
+    /// One binary-search index per SST, held in memory while the file is open.
+    #[default]
+    BinarySearch,
+    /// Two-level index: a small resident top level over index partitions
+    /// that are loaded through the block cache on demand. Bloom filters are
+    /// partitioned the same way. Costs an extra block read on a cold lookup
+    /// in exchange for far less resident memory on large, cold tables.
+    Partitioned,
+}
+
+/// Data block compression. Applies to SSTs written from then on; `rebuild`
+/// rewrites existing ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    None,
+    Snappy,
+    Lz4,
+    /// Best ratio, slowest to decompress; for large, cold tables.
+    Zstd,
+}
+
+impl From<Compression> for DBCompressionType {
+    fn from(compression: Compression) -> Self {
+        match compression {
+            Compression::None => DBCompressionType::None,
+            Compression::Snappy => DBCompressionType::Snappy,
+            Compression::Lz4 => DBCompressionType::Lz4,
+            Compression::Zstd => DBCompressionType::Zstd,
         }
     }
 }
@@ -87,35 +170,97 @@ fn default_block_restart_interval() -> i32 {
     8
 }
 
-impl From<&BlockConfig> for Options {
-    fn from(config: &BlockConfig) -> Self {
+impl BlockConfig {
+    /// The settings `table` is opened with: these, with `tables[table]`
+    /// applied on top.
+    pub fn for_table(&self, table: &str) -> BlockConfig {
+        let mut config = self.clone();
+        config.tables.clear();
+        let Some(table) = self.tables.get(table) else {
+            return config;
+        };
+        if let Some(bits) = table.bloom_filter_bits_per_key {
+            config.bloom_filter_bits_per_key = (bits > 0.0).then_some(bits);
+        }
+        if let Some(block_size) = table.block_size {
+            config.block_size = block_size;
+        }
+        if let Some(index_type) = table.index_type {
+            config.index_type = index_type;
+        }
+        if let Some(compression) = table.compression {
+            config.compression = Some(compression);
+        }
+        if let Some(cache) = table.cache_index_and_filter_blocks {
+            config.cache_index_and_filter_blocks = cache;
+        }
+        if let Some(pin) = table.pin_l0_filter_and_index_blocks {
+            config.pin_l0_filter_and_index_blocks = pin;
+        }
+        config
+    }
+
+    /// The one block cache every table's options point at, so
+    /// `block_cache_mb` bounds the whole store.
+    pub fn block_cache(&self) -> Option<Cache> {
+        (self.block_cache_mb > 0).then(|| Cache::new_lru_cache(self.block_cache_mb << 20))
+    }
+
+    /// RocksDB options for these settings, with `cache` as the block cache.
+    pub fn options(&self, cache: Option<&Cache>) -> Options {
         let mut bbt = BlockBasedOptions::default();
-        bbt.set_block_size(config.block_size);
-        bbt.set_block_restart_interval(config.block_restart_interval);
-        bbt.set_whole_key_filtering(config.whole_key_filtering);
-        bbt.set_cache_index_and_filter_blocks(config.cache_index_and_filter_blocks);
-        bbt.set_pin_l0_filter_and_index_blocks_in_cache(config.pin_l0_filter_and_index_blocks);
-        if let Some(bits) = config.bloom_filter_bits_per_key {
+        bbt.set_block_size(self.block_size);
+        bbt.set_block_restart_interval(self.block_restart_interval);
+        bbt.set_whole_key_filtering(self.whole_key_filtering);
+        match self.index_type {
+            IndexType::BinarySearch => {
+                bbt.set_index_type(BlockBasedIndexType::BinarySearch);
+                bbt.set_cache_index_and_filter_blocks(self.cache_index_and_filter_blocks);
+            }
+            IndexType::Partitioned => {
+                bbt.set_index_type(BlockBasedIndexType::TwoLevelIndexSearch);
+                bbt.set_partition_filters(self.bloom_filter_bits_per_key.is_some());
+                bbt.set_cache_index_and_filter_blocks(true);
+            }
+        }
+        bbt.set_pin_l0_filter_and_index_blocks_in_cache(self.pin_l0_filter_and_index_blocks);
+        if let Some(bits) = self.bloom_filter_bits_per_key {
             bbt.set_bloom_filter(bits, false);
         }
-        if config.data_block_hash_index {
+        if self.data_block_hash_index {
             bbt.set_data_block_index_type(DataBlockIndexType::BinaryAndHash);
-            bbt.set_data_block_hash_ratio(config.data_block_hash_ratio);
+            bbt.set_data_block_hash_ratio(self.data_block_hash_ratio);
         }
-        if config.block_cache_mb > 0 {
-            let cache = Cache::new_lru_cache(config.block_cache_mb << 20);
-            bbt.set_block_cache(&cache);
+        if let Some(cache) = cache {
+            bbt.set_block_cache(cache);
         }
 
         let mut opts = Options::default();
         opts.create_if_missing(true);
         opts.create_missing_column_families(true);
-        opts.set_allow_mmap_reads(config.mmap_reads);
-        opts.set_use_direct_reads(config.use_direct_reads);
-        opts.set_write_buffer_size(config.write_buffer_size);
-        opts.set_target_file_size_base(config.target_file_size_base);
-        opts.set_disable_auto_compactions(config.disable_auto_compactions);
+        opts.set_allow_mmap_reads(self.mmap_reads);
+        opts.set_use_direct_reads(self.use_direct_reads);
+        opts.set_write_buffer_size(self.write_buffer_size);
+        opts.set_target_file_size_base(self.target_file_size_base);
+        opts.set_disable_auto_compactions(self.disable_auto_compactions);
+        if let Some(trigger) = self.l0_compaction_trigger {
+            opts.set_level_zero_file_num_compaction_trigger(trigger);
+        }
+        if let Some(compression) = self.compression {
+            opts.set_compression_type(compression.into());
+        }
         opts.set_block_based_table_factory(&bbt);
+        if self.block_cache_mb > 0 {
+            // Ticker counters only, for the block cache hit rate in table stats.
+            opts.enable_statistics();
+            opts.set_statistics_level(StatsLevel::ExceptTimers);
+        }
         opts
     }
 }
+
+impl From<&BlockConfig> for Options {
+    fn from(config: &BlockConfig) -> Self {
+        config.options(config.block_cache().as_ref())
+    }
+}
@@ -1,22 +1,119 @@
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Instant;
 
 use arrow::array::RecordBatch;
-use rocksdb::{ColumnFamily, DB, DBPinnableSlice, Options, ReadOptions, WriteBatch, WriteOptions};
+use rocksdb::properties::{self, PropName};
+use rocksdb::statistics::Ticker;
+use rocksdb::{
+    ColumnFamilyRef, DB, DBPinnableSlice, DBRawIterator, Options, ReadOptions, WriteBatch,
+    WriteOptions,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::conf::{BackendConfig, StorageConfig};
-use crate::core::{MurrError, TableSchema};
+use crate::core::{Entity, MurrError, TableSchema};
 use crate::io::row::read::ReadBatchBuilder;
 use crate::io::store::rocksdb::block::BlockConfig;
 use crate::io::store::rocksdb::plain::PlainConfig;
-use crate::io::store::{KeyValue, Manifest, Store};
+use crate::io::store::{
+    AlterCopy, BlockCacheStats, Compaction, KeyValue, Manifest, Rewrite, Store, TableStats,
+};
 use itertools::Itertools;
 pub mod block;
 pub mod plain;
-use log::info;
+use log::{info, warn};
 const MANIFEST_FILE: &str = "manifest.json";
 
+/// Column family holding a table's deactivated rows, created by the first
+/// `deactivate`. Table names can't contain control characters, so it can't
+/// clash with a table.
+fn hidden_cf(table: &str) -> String {
+    format!("{table}\u{1f}hidden")
+}
+
+/// Column family holding a table's rows: the table's own name, until
+/// `alter_table` moves them to one per schema version. A renamed table keeps
+/// the column family it had.
+fn data_cf<'a>(manifest: &'a Manifest, table: &'a str) -> &'a str {
+    manifest
+        .column_families
+        .get(table)
+        .map_or(table, String::as_str)
+}
+
+fn cf_in_use(manifest: &Manifest, cf: &str) -> bool {
+    manifest
+        .tables
+        .keys()
+        .any(|table| data_cf(manifest, table) == cf)
+}
+
+/// Counts a corruption error, which is how a checksum mismatch surfaces.
+fn read_error(checksum_failures: &AtomicU64, e: &rocksdb::Error) -> MurrError {
+    if e.kind() == rocksdb::ErrorKind::Corruption {
+        checksum_failures.fetch_add(1, Ordering::Relaxed);
+    }
+    MurrError::IoError(e.to_string())
+}
+
+/// Fills `to` with the rows of `from` as they are when the copy starts,
+/// passed through `rewrite` `chunk_size` rows at a time. Skips a pair
+/// missing either column family. Runs without the store, for the copy
+/// `alteration` returns.
+fn copy_cf(
+    db: &DB,
+    from: &str,
+    to: &str,
+    chunk_size: usize,
+    verify_checksums: bool,
+    checksum_failures: &AtomicU64,
+    rewrite: &Rewrite<'_>,
+) -> Result<(), MurrError> {
+    let (Some(source), Some(target)) = (db.cf_handle(from), db.cf_handle(to)) else {
+        return Ok(());
+    };
+    let mut opts = ReadOptions::default();
+    opts.set_total_order_seek(true);
+    opts.set_verify_checksums(verify_checksums);
+    let mut iter = db.raw_iterator_cf_opt(&source, opts);
+    iter.seek_to_first();
+    loop {
+        let mut chunk = Vec::with_capacity(chunk_size);
+        while chunk.len() < chunk_size {
+            let Some((key, value)) = iter.item() else {
+                break;
+            };
+            chunk.push(KeyValue::new(key, value));
+            iter.next();
+        }
+        iter.status()
+            .map_err(|e| read_error(checksum_failures, &e))?;
+        if chunk.is_empty() {
+            break;
+        }
+        let mut batch = WriteBatch::default();
+        for kv in rewrite(chunk)? {
+            batch.put_cf(&target, kv.key, kv.value);
+        }
+        db.write(batch)?;
+    }
+    db.flush_cf(&target)?;
+    Ok(())
+}
+
+/// An `alter_table` between `alteration` and `finish_alter`.
+struct PendingAlter {
+    schema: TableSchema,
+    /// Data column family of the new schema version.
+    cf: String,
+    /// Keys written, deleted, deactivated or reactivated since the copy
+    /// started, which `finish_alter` copies again.
+    touched: Mutex<HashSet<Vec<u8>>>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ReadMethod {
@@ -28,14 +125,35 @@ pub enum ReadMethod {
 }
 
 pub struct RocksDBStore {
-    db: DB,
+    /// Shared with compactions running outside the store lock. Column
+    /// families are created and dropped through `&self` (`multi-threaded-cf`),
+    /// so that works while a compaction holds a clone.
+    db: Arc<DB>,
     cf_opts: Options,
+    /// Column family options for tables with per-table overrides.
+    table_opts: HashMap<String, Options>,
     write_opts: WriteOptions,
     read_opts: ReadOptions,
     path: PathBuf,
     manifest: Manifest,
     write_buffer_size: usize,
     read_method: ReadMethod,
+    block_cache: bool,
+    /// Also set on scan iterators, which don't use `read_opts`.
+    verify_checksums: bool,
+    /// Whether scans can `seek` to a cursor. PlainTable's hash index can't
+    /// Seek in total order, so its scans start at the first key.
+    seekable: bool,
+    /// Reads that RocksDB failed with a corruption error, which is how a
+    /// checksum mismatch surfaces. Shared with alter copies.
+    checksum_failures: Arc<AtomicU64>,
+    /// The backend config's `disable_auto_compactions`, which resuming
+    /// compaction restores rather than overriding.
+    auto_compaction_disabled: bool,
+    /// Set while `set_auto_compaction(false)` is in effect; column families
+    /// created meanwhile start paused too.
+    compaction_paused: AtomicBool,
+    altering: HashMap<String, PendingAlter>,
 }
 
 impl RocksDBStore {
@@ -47,13 +165,18 @@ impl RocksDBStore {
             "Opening RocksDB PlainTable: read_method={:?}",
             config.read_method
         );
-        Self::open_inner(
+        let mut store = Self::open_inner(
             path,
             cf_opts,
+            HashMap::new(),
             read_opts,
             config.write_buffer_size,
             config.read_method,
-        )
+            false,
+        )?;
+        store.verify_checksums = config.verify_checksums;
+        store.auto_compaction_disabled = config.disable_auto_compactions;
+        Ok(store)
     }
 
     pub fn open_from_config(storage: &StorageConfig) -> Result<Self, MurrError> {
@@ -82,7 +205,16 @@ impl RocksDBStore {
     }
 
     pub fn open_block(path: &Path, config: &BlockConfig) -> Result<Self, MurrError> {
-        let cf_opts: Options = config.into();
+        let cache = config.block_cache();
+        let cf_opts = config.options(cache.as_ref());
+        let table_opts = config
+            .tables
+            .keys()
+            .map(|table| {
+                let opts = config.for_table(table).options(cache.as_ref());
+                (table.clone(), opts)
+            })
+            .collect();
         let mut read_opts = ReadOptions::default();
         read_opts.set_async_io(config.async_io);
         read_opts.set_verify_checksums(config.verify_checksums);
@@ -90,27 +222,31 @@ impl RocksDBStore {
             "Opening RocksDB BlockTable: read_method={:?}",
             config.read_method
         );
-        Self::open_inner(
+        let mut store = Self::open_inner(
             path,
             cf_opts,
+            table_opts,
             read_opts,
             config.write_buffer_size,
             config.read_method,
-        )
+            config.block_cache_mb > 0,
+        )?;
+        store.verify_checksums = config.verify_checksums;
+        store.seekable = true;
+        store.auto_compaction_disabled = config.disable_auto_compactions;
+        Ok(store)
     }
 
     fn open_inner(
         path: &Path,
         cf_opts: Options,
+        table_opts: HashMap<String, Options>,
         read_opts: ReadOptions,
         write_buffer_size: usize,
         read_method: ReadMethod,
+        block_cache: bool,
     ) -> Result<Self, MurrError> {
         info!("RocksDB path: {}", path.display());
-        let cfs = DB::list_cf(&cf_opts, path).unwrap_or_default();
-        info!("Discovered {} column families: {:?}", cfs.len(), cfs);
-        let cf_descriptors = cfs.iter().map(|name| (name.as_str(), cf_opts.clone()));
-        let db = DB::open_cf_with_opts(&cf_opts, path, cf_descriptors)?;
         let manifest_path = path.join(MANIFEST_FILE);
         let manifest = Manifest::from_file(&manifest_path)?;
         info!(
@@ -118,36 +254,250 @@ impl RocksDBStore {
             manifest_path.display(),
             manifest.tables.len()
         );
+        let cfs = DB::list_cf(&cf_opts, path).unwrap_or_default();
+        info!("Discovered {} column families: {:?}", cfs.len(), cfs);
+        let cf_descriptors = cfs.iter().map(|name| {
+            let table = manifest
+                .column_families
+                .iter()
+                .find(|(_, cf)| *cf == name)
+                .map_or(name, |(table, _)| table);
+            let opts = table_opts.get(table).unwrap_or(&cf_opts);
+            (name.as_str(), opts.clone())
+        });
+        let db = DB::open_cf_with_opts(&cf_opts, path, cf_descriptors)?;
+        // `truncate` drops and recreates the column family; a crash in
+        // between leaves the table without one, i.e. empty.
+        for table in manifest.tables.keys() {
+            let cf = data_cf(&manifest, table);
+            if db.cf_handle(cf).is_none() {
+                warn!("Table '{table}' has no column family, recreating it empty");
+                let opts = table_opts.get(table).unwrap_or(&cf_opts);
+                db.create_cf(cf, opts)?;
+            }
+        }
+        // An interrupted `alter_table` or `drop_table` leaves column
+        // families of old or unfinished schema versions that no table uses.
+        let live: HashSet<String> = manifest
+            .tables
+            .keys()
+            .flat_map(|table| {
+                let cf = data_cf(&manifest, table);
+                [cf.to_string(), hidden_cf(cf)]
+            })
+            .collect();
+        for cf in &cfs {
+            if cf.contains('\u{1f}') && !live.contains(cf) {
+                warn!("Dropping column family '{cf}', which no table uses");
+                db.drop_cf(cf)?;
+            }
+        }
         Ok(Self {
-            db,
+            db: Arc::new(db),
             cf_opts,
+            table_opts,
             write_opts: WriteOptions::default(),
             read_opts,
             path: path.to_path_buf(),
             manifest,
             write_buffer_size,
             read_method,
+            block_cache,
+            verify_checksums: false,
+            seekable: false,
+            checksum_failures: Arc::new(AtomicU64::new(0)),
+            auto_compaction_disabled: false,
+            compaction_paused: AtomicBool::new(false),
+            altering: HashMap::new(),
         })
     }
 
+    /// Creates a column family, paused like the others while
+    /// `set_auto_compaction(false)` is in effect.
+    fn create_cf(&self, name: &str, opts: &Options) -> Result<(), MurrError> {
+        if self.compaction_paused.load(Ordering::Relaxed) {
+            let mut opts = opts.clone();
+            opts.set_disable_auto_compactions(true);
+            self.db.create_cf(name, &opts)?;
+        } else {
+            self.db.create_cf(name, opts)?;
+        }
+        Ok(())
+    }
+
+    fn read_error(&self, e: &rocksdb::Error) -> MurrError {
+        read_error(&self.checksum_failures, e)
+    }
+
+    fn check_not_altering(&self, table: &str) -> Result<(), MurrError> {
+        if self.altering.contains_key(table) {
+            return Err(MurrError::TableError(format!(
+                "'{table}' is being altered, try again"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Notes `keys` for `finish_alter` to copy again if `table` is being
+    /// altered.
+    fn touch<'a>(&self, table: &str, keys: impl IntoIterator<Item = &'a [u8]>) {
+        if let Some(pending) = self.altering.get(table) {
+            let mut touched = pending
+                .touched
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            touched.extend(keys.into_iter().map(<[u8]>::to_vec));
+        }
+    }
+
+    /// Drops column families of an alter that didn't go through.
+    fn drop_alter_cfs(&self, cf: &str) {
+        for cf in [hidden_cf(cf), cf.to_string()] {
+            if self.db.cf_handle(&cf).is_none() {
+                continue;
+            }
+            if let Err(e) = self.db.drop_cf(&cf) {
+                warn!("Cannot drop column family '{cf}' of a failed alter: {e}");
+            }
+        }
+    }
+
+    fn block_cache_stats(&self) -> Result<Option<BlockCacheStats>, MurrError> {
+        if !self.block_cache {
+            return Ok(None);
+        }
+        let property = |name: &PropName| -> Result<u64, MurrError> {
+            Ok(self.db.property_int_value(name)?.unwrap_or(0))
+        };
+        Ok(Some(BlockCacheStats {
+            capacity_bytes: property(properties::BLOCK_CACHE_CAPACITY)?,
+            usage_bytes: property(properties::BLOCK_CACHE_USAGE)?,
+            hits: self.cf_opts.get_ticker_count(Ticker::BlockCacheHit),
+            misses: self.cf_opts.get_ticker_count(Ticker::BlockCacheMiss),
+        }))
+    }
+
     fn manifest_path(&self) -> PathBuf {
         self.path.join(MANIFEST_FILE)
     }
 
+    fn table_cf(&self, table: &str) -> Result<ColumnFamilyRef<'_>, MurrError> {
+        self.db
+            .cf_handle(data_cf(&self.manifest, table))
+            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))
+    }
+
+    /// A column family for a new schema version of `table` that no table
+    /// uses, skipping versions taken by a table renamed away from `table`.
+    fn version_cf(&self, table: &str) -> String {
+        (self.manifest.schema_version(table) + 1..)
+            .map(|version| format!("{table}\u{1f}v{version}"))
+            .find(|cf| !cf_in_use(&self.manifest, cf))
+            .expect("schema versions are unbounded")
+    }
+
+    /// Brings the rows of `keys` in `new_cf` and its hidden column family
+    /// up to date with `old_cf`'s, passed through `rewrite` one write buffer
+    /// at a time. Rows gone from `old_cf` are deleted.
+    fn recopy(
+        &self,
+        old_cf: &str,
+        new_cf: &str,
+        keys: &HashSet<Vec<u8>>,
+        rewrite: &Rewrite<'_>,
+    ) -> Result<(), MurrError> {
+        let keys: Vec<&[u8]> = keys.iter().map(Vec::as_slice).collect();
+        for (from, to) in [
+            (old_cf.to_string(), new_cf.to_string()),
+            (hidden_cf(old_cf), hidden_cf(new_cf)),
+        ] {
+            let Some(source) = self.db.cf_handle(&from) else {
+                continue;
+            };
+            // A deactivate during the copy may have created the old one.
+            if self.db.cf_handle(&to).is_none() {
+                self.create_cf(&to, &self.cf_opts)?;
+            }
+            let target = self
+                .db
+                .cf_handle(&to)
+                .ok_or_else(|| MurrError::IoError(format!("column family '{to}' missing")))?;
+            for chunk in keys.chunks(self.write_buffer_size) {
+                let mut batch = WriteBatch::default();
+                let mut rows = Vec::new();
+                for (key, row) in chunk.iter().zip(self.read_raw(&source, chunk)) {
+                    match row.map_err(|e| self.read_error(&e))? {
+                        Some(row) => rows.push(KeyValue::new(*key, row.as_ref())),
+                        None => batch.delete_cf(&target, key),
+                    }
+                }
+                if !rows.is_empty() {
+                    for kv in rewrite(rows)? {
+                        batch.put_cf(&target, kv.key, kv.value);
+                    }
+                }
+                self.db.write_opt(batch, &self.write_opts)?;
+            }
+            self.db.flush_cf(&target)?;
+        }
+        Ok(())
+    }
+
+    /// Iterator over `cf` in key order, positioned at `from` when the backend
+    /// can seek and at the first key otherwise, so callers still skip keys
+    /// before `from`.
+    fn key_iterator<'a>(
+        &'a self,
+        cf: &ColumnFamilyRef<'_>,
+        from: Option<&[u8]>,
+    ) -> DBRawIterator<'a> {
+        let mut opts = ReadOptions::default();
+        opts.set_total_order_seek(true);
+        opts.set_verify_checksums(self.verify_checksums);
+        let mut iter = self.db.raw_iterator_cf_opt(cf, opts);
+        match from {
+            Some(from) if self.seekable => iter.seek(from),
+            _ => iter.seek_to_first(),
+        }
+        iter
+    }
+
+    fn read_raw<'a>(
+        &'a self,
+        cf: &ColumnFamilyRef<'_>,
+        keys: &[&[u8]],
+    ) -> Vec<Result<Option<DBPinnableSlice<'a>>, rocksdb::Error>> {
+        match self.read_method {
+            ReadMethod::MultiGet => self.read_multiget(cf, keys),
+            ReadMethod::MultiGetSorted => self.read_multiget_sorted(cf, keys),
+            ReadMethod::Get => self.read_get(cf, keys),
+            ReadMethod::ParGet => self.read_get_parallel(cf, keys),
+            ReadMethod::ParMultiGet => self.read_multiget_parallel(cf, keys),
+        }
+    }
+
     fn read_multiget<'a>(
         &'a self,
-        cf: &ColumnFamily,
+        cf: &ColumnFamilyRef<'_>,
         keys: &[&[u8]],
     ) -> Vec<Result<Option<DBPinnableSlice<'a>>, rocksdb::Error>> {
+        // Callers often send keys in table order already (scans, exports); telling
+        // MultiGet lets it skip its own sort.
+        let sorted = keys.is_sorted();
         self.db
-            .batched_multi_get_cf_opt(cf, keys, false, &self.read_opts)
+            .batched_multi_get_cf_opt(cf, keys, sorted, &self.read_opts)
     }
 
     fn read_multiget_sorted<'a>(
         &'a self,
-        cf: &ColumnFamily,
+        cf: &ColumnFamilyRef<'_>,
         keys: &[&[u8]],
     ) -> Vec<Result<Option<DBPinnableSlice<'a>>, rocksdb::Error>> {
+        if keys.is_sorted() {
+            return self
+                .db
+                .batched_multi_get_cf_opt(cf, keys, true, &self.read_opts);
+        }
         let n = keys.len();
         let mut order: Vec<usize> = (0..n).collect();
         order.sort_unstable_by_key(|&i| keys[i]);
@@ -170,7 +520,7 @@ impl RocksDBStore {
 
     fn read_get<'a>(
         &'a self,
-        cf: &ColumnFamily,
+        cf: &ColumnFamilyRef<'_>,
         keys: &[&[u8]],
     ) -> Vec<Result<Option<DBPinnableSlice<'a>>, rocksdb::Error>> {
         keys.iter()
@@ -180,7 +530,7 @@ impl RocksDBStore {
 
     fn read_get_parallel<'a>(
         &'a self,
-        cf: &ColumnFamily,
+        cf: &ColumnFamilyRef<'_>,
         keys: &[&[u8]],
     ) -> Vec<Result<Option<DBPinnableSlice<'a>>, rocksdb::Error>> {
         use rayon::prelude::*;
@@ -191,7 +541,7 @@ impl RocksDBStore {
 
     fn read_multiget_parallel<'a>(
         &'a self,
-        cf: &ColumnFamily,
+        cf: &ColumnFamilyRef<'_>,
         keys: &[&[u8]],
     ) -> Vec<Result<Option<DBPinnableSlice<'a>>, rocksdb::Error>> {
         use rayon::prelude::*;
@@ -207,9 +557,231 @@ impl RocksDBStore {
 
 impl Store for RocksDBStore {
     fn create_table(&mut self, table: &str, schema: &TableSchema) -> Result<(), MurrError> {
+        if table == rocksdb::DEFAULT_COLUMN_FAMILY_NAME {
+            return Err(MurrError::TableError(format!(
+                "table name '{table}' is reserved"
+            )));
+        }
+        // The table's own name may still hold the rows of a table renamed
+        // away from it.
+        let cf = if cf_in_use(&self.manifest, table) {
+            self.version_cf(table)
+        } else {
+            table.to_string()
+        };
         self.manifest.add_table(table, schema)?;
-        self.db.create_cf(table, &self.cf_opts)?;
-        self.manifest.to_file(&self.manifest_path())?;
+        if cf != table {
+            self.manifest
+                .column_families
+                .insert(table.to_string(), cf.clone());
+        }
+        // Left behind by a drop that failed after the manifest was saved.
+        for orphan in [cf.clone(), hidden_cf(&cf)] {
+            if self.db.cf_handle(&orphan).is_none() {
+                continue;
+            }
+            if let Err(e) = self.db.drop_cf(&orphan) {
+                self.manifest.del_table(table)?;
+                return Err(e.into());
+            }
+        }
+        let opts = self.table_opts.get(table).unwrap_or(&self.cf_opts);
+        if let Err(e) = self.create_cf(&cf, opts) {
+            self.manifest.del_table(table)?;
+            return Err(e.into());
+        }
+        // A table that isn't in the manifest on disk must not exist either,
+        // or the next create after a restart would trip over its column family.
+        if let Err(e) = self.manifest.to_file(&self.manifest_path()) {
+            self.manifest.del_table(table)?;
+            self.db.drop_cf(&cf)?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Saves the manifest first, so a failure afterwards leaves column
+    /// families no table points at, which `create_table` or the next open
+    /// clears, and never a listed table without its data.
+    fn drop_table(&mut self, table: &str) -> Result<(), MurrError> {
+        self.check_not_altering(table)?;
+        let previous = self.manifest.clone();
+        let cf = data_cf(&previous, table).to_string();
+        self.manifest.del_table(table)?;
+        if let Err(e) = self.manifest.to_file(&self.manifest_path()) {
+            self.manifest = previous;
+            return Err(e);
+        }
+        for cf in [hidden_cf(&cf), cf] {
+            if self.db.cf_handle(&cf).is_some() {
+                self.db.drop_cf(&cf)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops and recreates the column family, which is quicker than
+    /// deleting every key and leaves no tombstones to compact away.
+    /// PlainTable has no range deletes, so this is the one way that works
+    /// on both backends.
+    fn truncate(&mut self, table: &str) -> Result<(), MurrError> {
+        self.check_not_altering(table)?;
+        let cf = data_cf(&self.manifest, table).to_string();
+        if self.db.cf_handle(&cf).is_none() {
+            return Err(MurrError::TableNotFound(table.to_string()));
+        }
+        self.db.drop_cf(&cf)?;
+        let opts = self.table_opts.get(table).unwrap_or(&self.cf_opts);
+        self.create_cf(&cf, opts)?;
+        let hidden = hidden_cf(&cf);
+        if self.db.cf_handle(&hidden).is_some() {
+            self.db.drop_cf(&hidden)?;
+        }
+        Ok(())
+    }
+
+    /// Creates column families named after the new schema version, then
+    /// copies into them from the old ones as they are when the copy starts.
+    /// Rows changed later are noted for `finish_alter`.
+    fn alteration(&mut self, table: &str, schema: &TableSchema) -> Result<AlterCopy, MurrError> {
+        let old_cf = data_cf(&self.manifest, table).to_string();
+        if !self.manifest.contains(table) || self.db.cf_handle(&old_cf).is_none() {
+            return Err(MurrError::TableNotFound(table.to_string()));
+        }
+        self.check_not_altering(table)?;
+        let new_cf = self.version_cf(table);
+        // Left behind by an earlier table of the same name.
+        for cf in [new_cf.clone(), hidden_cf(&new_cf)] {
+            if self.db.cf_handle(&cf).is_some() {
+                self.db.drop_cf(&cf)?;
+            }
+        }
+        let table_opts = self.table_opts.get(table).unwrap_or(&self.cf_opts);
+        let mut created = self.create_cf(&new_cf, table_opts);
+        if created.is_ok() && self.db.cf_handle(&hidden_cf(&old_cf)).is_some() {
+            created = self.create_cf(&hidden_cf(&new_cf), &self.cf_opts);
+        }
+        if let Err(e) = created {
+            self.drop_alter_cfs(&new_cf);
+            return Err(e);
+        }
+        self.altering.insert(
+            table.to_string(),
+            PendingAlter {
+                schema: schema.clone(),
+                cf: new_cf.clone(),
+                touched: Mutex::default(),
+            },
+        );
+        let db = Arc::clone(&self.db);
+        let chunk_size = self.write_buffer_size;
+        let verify_checksums = self.verify_checksums;
+        let checksum_failures = Arc::clone(&self.checksum_failures);
+        let copies = [(hidden_cf(&old_cf), hidden_cf(&new_cf)), (old_cf, new_cf)];
+        Ok(Box::new(move |rewrite: &Rewrite<'_>| {
+            copies.iter().try_for_each(|(from, to)| {
+                copy_cf(
+                    &db,
+                    from,
+                    to,
+                    chunk_size,
+                    verify_checksums,
+                    &checksum_failures,
+                    rewrite,
+                )
+            })
+        }))
+    }
+
+    /// Copies the rows changed during the copy again, then saves the
+    /// manifest pointing at the new column families and drops the old ones.
+    /// A failure before the save drops the new ones; a crash leaves column
+    /// families no table uses, which the next open drops.
+    fn finish_alter(
+        &mut self,
+        table: &str,
+        copied: Result<(), MurrError>,
+        rewrite: &Rewrite<'_>,
+    ) -> Result<(), MurrError> {
+        let PendingAlter {
+            schema,
+            cf: new_cf,
+            touched,
+        } = self
+            .altering
+            .remove(table)
+            .ok_or_else(|| MurrError::TableError(format!("'{table}' is not being altered")))?;
+        let old_cf = data_cf(&self.manifest, table).to_string();
+        let touched = touched.into_inner().unwrap_or_else(PoisonError::into_inner);
+        let mut result = copied.and_then(|()| self.recopy(&old_cf, &new_cf, &touched, rewrite));
+        if result.is_ok() {
+            let previous = self.manifest.clone();
+            self.manifest.alter_table(table, &schema)?;
+            self.manifest
+                .column_families
+                .insert(table.to_string(), new_cf.clone());
+            result = self.manifest.to_file(&self.manifest_path());
+            if result.is_err() {
+                self.manifest = previous;
+            }
+        }
+        if let Err(e) = result {
+            self.drop_alter_cfs(&new_cf);
+            return Err(e);
+        }
+        for cf in [hidden_cf(&old_cf), old_cf] {
+            if self.db.cf_handle(&cf).is_none() {
+                continue;
+            }
+            if let Err(e) = self.db.drop_cf(&cf) {
+                warn!(
+                    "Cannot drop column family '{cf}' of the old schema, the next open will: {e}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Only the manifest changes: the table keeps its column families, and
+    /// per-table options follow the new name from the next open.
+    fn rename_table(&mut self, from: &str, to: &str) -> Result<(), MurrError> {
+        self.check_not_altering(from)?;
+        if to == rocksdb::DEFAULT_COLUMN_FAMILY_NAME {
+            return Err(MurrError::TableError(format!(
+                "table name '{to}' is reserved"
+            )));
+        }
+        let previous = self.manifest.clone();
+        self.manifest.rename_table(from, to)?;
+        if let Err(e) = self.manifest.to_file(&self.manifest_path()) {
+            self.manifest = previous;
+            return Err(e);
+        }
+        // Its overrides follow it, so the next `alter_table` keeps them.
+        if let Some(opts) = self.table_opts.remove(from) {
+            self.table_opts.insert(to.to_string(), opts);
+        }
+        Ok(())
+    }
+
+    fn set_alias(&mut self, alias: &str, table: Option<&str>) -> Result<Option<String>, MurrError> {
+        let previous = self.manifest.clone();
+        let replaced = self.manifest.set_alias(alias, table);
+        if let Err(e) = self.manifest.to_file(&self.manifest_path()) {
+            self.manifest = previous;
+            return Err(e);
+        }
+        Ok(replaced)
+    }
+
+    fn create_entity(&mut self, name: &str, entity: &Entity) -> Result<(), MurrError> {
+        if !self.manifest.add_entity(name, entity)? {
+            return Ok(());
+        }
+        if let Err(e) = self.manifest.to_file(&self.manifest_path()) {
+            self.manifest.entities.remove(name);
+            return Err(e);
+        }
         Ok(())
     }
 
@@ -222,18 +794,100 @@ impl Store for RocksDBStore {
         table: &str,
         rows: impl IntoIterator<Item = KeyValue>,
     ) -> Result<(), MurrError> {
-        let cf = self
+        let cf = self.table_cf(table)?;
+
+        let hidden = self
             .db
-            .cf_handle(table)
-            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+            .cf_handle(&hidden_cf(data_cf(&self.manifest, table)));
 
         for chunk in &rows.into_iter().chunks(self.write_buffer_size) {
+            let chunk: Vec<KeyValue> = chunk.collect();
+            self.touch(table, chunk.iter().map(|kv| kv.key.as_slice()));
             let mut batch = WriteBatch::default();
             for kv in chunk {
-                batch.put_cf(cf, kv.key, kv.value);
+                if let Some(hidden) = &hidden {
+                    batch.delete_cf(hidden, &kv.key);
+                }
+                batch.put_cf(&cf, kv.key, kv.value);
+            }
+            self.db.write_opt(batch, &self.write_opts)?;
+            self.db.flush_cf(&cf)?;
+        }
+        Ok(())
+    }
+
+    /// Writes tombstones; compaction drops the rows and tombstones later.
+    fn delete(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError> {
+        let cf = self.table_cf(table)?;
+        let hidden = self
+            .db
+            .cf_handle(&hidden_cf(data_cf(&self.manifest, table)));
+        for chunk in keys.chunks(self.write_buffer_size) {
+            self.touch(table, chunk.iter().copied());
+            let mut batch = WriteBatch::default();
+            for key in chunk {
+                if let Some(hidden) = &hidden {
+                    batch.delete_cf(hidden, key);
+                }
+                batch.delete_cf(&cf, key);
+            }
+            self.db.write_opt(batch, &self.write_opts)?;
+            self.db.flush_cf(&cf)?;
+        }
+        Ok(())
+    }
+
+    /// Each chunk moves its rows in one atomic batch.
+    fn deactivate(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError> {
+        let hidden_name = hidden_cf(data_cf(&self.manifest, table));
+        self.table_cf(table)?;
+        if self.db.cf_handle(&hidden_name).is_none() {
+            self.create_cf(&hidden_name, &self.cf_opts)?;
+        }
+        let cf = self.table_cf(table)?;
+        let hidden = self
+            .db
+            .cf_handle(&hidden_name)
+            .ok_or_else(|| MurrError::IoError(format!("column family '{hidden_name}' missing")))?;
+        for chunk in keys.chunks(self.write_buffer_size) {
+            self.touch(table, chunk.iter().copied());
+            let rows = self.read_raw(&cf, chunk);
+            let mut batch = WriteBatch::default();
+            for (key, row) in chunk.iter().zip(rows) {
+                let row = row.map_err(|e| self.read_error(&e))?;
+                if let Some(row) = row {
+                    batch.put_cf(&hidden, key, row.as_ref());
+                    batch.delete_cf(&cf, key);
+                }
+            }
+            self.db.write_opt(batch, &self.write_opts)?;
+            self.db.flush_cf(&cf)?;
+        }
+        Ok(())
+    }
+
+    /// The reverse of `deactivate`, in the same atomic chunks.
+    fn reactivate(&mut self, table: &str, keys: &[&[u8]]) -> Result<(), MurrError> {
+        let cf = self.table_cf(table)?;
+        let Some(hidden) = self
+            .db
+            .cf_handle(&hidden_cf(data_cf(&self.manifest, table)))
+        else {
+            return Ok(());
+        };
+        for chunk in keys.chunks(self.write_buffer_size) {
+            self.touch(table, chunk.iter().copied());
+            let rows = self.read_raw(&hidden, chunk);
+            let mut batch = WriteBatch::default();
+            for (key, row) in chunk.iter().zip(rows) {
+                let row = row.map_err(|e| self.read_error(&e))?;
+                if let Some(row) = row {
+                    batch.put_cf(&cf, key, row.as_ref());
+                    batch.delete_cf(&hidden, key);
+                }
             }
             self.db.write_opt(batch, &self.write_opts)?;
-            self.db.flush_cf(cf)?;
+            self.db.flush_cf(&cf)?;
         }
         Ok(())
     }
@@ -244,34 +898,130 @@ impl Store for RocksDBStore {
         keys: &[&[u8]],
         mut builder: ReadBatchBuilder<'_>,
     ) -> Result<RecordBatch, MurrError> {
-        let cf = self
-            .db
-            .cf_handle(table)
-            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
+        let cf = self.table_cf(table)?;
 
-        let raw = match self.read_method {
-            ReadMethod::MultiGet => self.read_multiget(cf, keys),
-            ReadMethod::MultiGetSorted => self.read_multiget_sorted(cf, keys),
-            ReadMethod::Get => self.read_get(cf, keys),
-            ReadMethod::ParGet => self.read_get_parallel(cf, keys),
-            ReadMethod::ParMultiGet => self.read_multiget_parallel(cf, keys),
-        };
+        let raw = self.read_raw(&cf, keys);
         for r in &raw {
             match r {
                 Ok(Some(v)) => builder.add_row(v.as_ref())?,
                 Ok(None) => builder.add_empty()?,
-                Err(e) => return Err(MurrError::IoError(e.to_string())),
+                Err(e) => return Err(self.read_error(e)),
             }
         }
         builder.build()
     }
 
-    fn compact(&self, table: &str) -> Result<(), MurrError> {
-        let cf = self
-            .db
-            .cf_handle(table)
-            .ok_or_else(|| MurrError::TableNotFound(table.to_string()))?;
-        self.db.compact_range_cf(&cf, None::<&[u8]>, None::<&[u8]>);
+    fn read_rows(&self, table: &str, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, MurrError> {
+        let cf = self.table_cf(table)?;
+        self.read_raw(&cf, keys)
+            .into_iter()
+            .map(|r| {
+                r.map(|v| v.map(|v| v.to_vec()))
+                    .map_err(|e| self.read_error(&e))
+            })
+            .collect()
+    }
+
+    fn contains(&self, table: &str, keys: &[&[u8]]) -> Result<Vec<bool>, MurrError> {
+        let cf = self.table_cf(table)?;
+        self.read_raw(&cf, keys)
+            .into_iter()
+            .map(|r| r.map(|v| v.is_some()).map_err(|e| self.read_error(&e)))
+            .collect()
+    }
+
+    fn count(&self, table: &str) -> Result<usize, MurrError> {
+        let cf = self.table_cf(table)?;
+        let mut iter = self.key_iterator(&cf, None);
+        let mut count = 0;
+        while iter.valid() {
+            count += 1;
+            iter.next();
+        }
+        iter.status().map_err(|e| self.read_error(&e))?;
+        Ok(count)
+    }
+
+    fn keys(
+        &self,
+        table: &str,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, MurrError> {
+        let cf = self.table_cf(table)?;
+        let mut iter = self.key_iterator(&cf, cursor);
+        let mut keys = Vec::new();
+        while let Some(key) = iter.key() {
+            if keys.len() == limit {
+                break;
+            }
+            if cursor.is_none_or(|c| key > c) {
+                keys.push(key.to_vec());
+            }
+            iter.next();
+        }
+        iter.status().map_err(|e| self.read_error(&e))?;
+        Ok(keys)
+    }
+
+    fn stats(&self, table: &str) -> Result<TableStats, MurrError> {
+        let cf = self.table_cf(table)?;
+        let property = |name: &PropName| -> Result<u64, MurrError> {
+            Ok(self.db.property_int_value_cf(&cf, name)?.unwrap_or(0))
+        };
+        Ok(TableStats {
+            estimated_keys: property(properties::ESTIMATE_NUM_KEYS)?,
+            index_bytes: property(properties::ESTIMATE_TABLE_READERS_MEM)?,
+            memtable_bytes: property(properties::CUR_SIZE_ALL_MEM_TABLES)?,
+            disk_bytes: property(properties::TOTAL_SST_FILES_SIZE)?,
+            block_cache: self.block_cache_stats()?,
+            checksum_failures: self.checksum_failures.load(Ordering::Relaxed),
+            ..TableStats::default()
+        })
+    }
+
+    /// Covers the table's hidden CF too. A table dropped before the
+    /// compaction runs is skipped.
+    fn compaction(&self, table: &str, key: Option<&[u8]>) -> Result<Compaction, MurrError> {
+        self.table_cf(table)?;
+        let data = data_cf(&self.manifest, table).to_string();
+        let hidden = hidden_cf(&data);
+        let names = [data, hidden];
+        let key = key.map(<[u8]>::to_vec);
+        let db = Arc::clone(&self.db);
+        Ok(Box::new(move || {
+            for name in &names {
+                if let Some(cf) = db.cf_handle(name) {
+                    db.compact_range_cf(&cf, key.as_deref(), key.as_deref());
+                }
+            }
+            Ok(())
+        }))
+    }
+
+    /// Covers hidden column families and those of alters underway too.
+    /// Resuming goes back to the
+    /// configured `disable_auto_compactions`, so it never turns on
+    /// compaction an operator switched off.
+    fn set_auto_compaction(&self, enabled: bool) -> Result<(), MurrError> {
+        self.compaction_paused.store(!enabled, Ordering::Relaxed);
+        let disabled = if enabled && !self.auto_compaction_disabled {
+            "false"
+        } else {
+            "true"
+        };
+        let tables = self.manifest.tables.keys();
+        let cfs = tables
+            .map(|table| data_cf(&self.manifest, table))
+            .chain(self.altering.values().map(|pending| pending.cf.as_str()));
+        for cf in cfs {
+            for name in [cf.to_string(), hidden_cf(cf)] {
+                if let Some(cf) = self.db.cf_handle(&name) {
+                    self.db
+                        .set_options_cf(&cf, &[("disable_auto_compactions", disabled)])?;
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -280,7 +1030,10 @@ impl Store for RocksDBStore {
 mod tests {
     use super::*;
     use crate::core::{ColumnSchema, DTypeName};
-    use crate::io::store::test_util::{fetch, put};
+    use crate::io::row::read::ReadRow;
+    use crate::io::row::write::WriteRow;
+    use crate::io::store::rocksdb::block::{Compression, IndexType};
+    use crate::io::store::test_util::{fetch, payload_segment, put};
     use indexmap::IndexMap;
     use rstest::rstest;
     use std::path::Path;
@@ -303,6 +1056,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: false,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -310,11 +1064,15 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         TableSchema {
             key: key.to_string(),
             columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
         }
     }
 
@@ -336,12 +1094,22 @@ mod tests {
         store
     }
 
+    fn open_block_partitioned(path: &Path) -> RocksDBStore {
+        let config = BlockConfig {
+            index_type: IndexType::Partitioned,
+            bloom_filter_bits_per_key: Some(10.0),
+            ..BlockConfig::default()
+        };
+        RocksDBStore::open_block(path, &config).expect("open block")
+    }
+
     #[rstest]
     #[case::plain(open_plain)]
     #[case::block(open_block)]
     #[case::block_get(open_block_get)]
     #[case::block_par_get(open_block_par_get)]
     #[case::block_par_multi_get(open_block_par_multi_get)]
+    #[case::block_partitioned(open_block_partitioned)]
     fn round_trip(#[case] open: Opener) {
         let dir = TempDir::new().unwrap();
         let mut store = open(dir.path());
@@ -371,6 +1139,7 @@ mod tests {
     #[case::block_get(open_block_get)]
     #[case::block_par_get(open_block_par_get)]
     #[case::block_par_multi_get(open_block_par_multi_get)]
+    #[case::block_partitioned(open_block_partitioned)]
     fn read_preserves_caller_key_order(#[case] open: Opener) {
         let dir = TempDir::new().unwrap();
         let mut store = open(dir.path());
@@ -404,6 +1173,7 @@ mod tests {
     #[case::block_get(open_block_get)]
     #[case::block_par_get(open_block_par_get)]
     #[case::block_par_multi_get(open_block_par_multi_get)]
+    #[case::block_partitioned(open_block_partitioned)]
     fn missing_key_yields_none(#[case] open: Opener) {
         let dir = TempDir::new().unwrap();
         let mut store = open(dir.path());
@@ -423,6 +1193,53 @@ mod tests {
         assert_eq!(got[2].as_deref(), Some(&b"c-payload"[..]));
     }
 
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    #[case::block_get(open_block_get)]
+    #[case::block_par_get(open_block_par_get)]
+    #[case::block_par_multi_get(open_block_par_multi_get)]
+    #[case::block_partitioned(open_block_partitioned)]
+    fn contains_reports_presence(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(dir.path());
+        store.create_table("users", &schema("id")).unwrap();
+        put(&mut store, "users", &[("alice", b"a"), ("carol", b"c")]);
+
+        let lookup: [&[u8]; 3] = [b"carol", b"bob", b"alice"];
+        let got = store.contains("users", &lookup).unwrap();
+        assert_eq!(got, vec![true, false, true]);
+    }
+
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn count_and_paginate_keys(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(dir.path());
+        store.create_table("users", &schema("id")).unwrap();
+        put(&mut store, "users", &[("carol", b"c"), ("alice", b"a")]);
+        put(&mut store, "users", &[("bob", b"b"), ("alice", b"a2")]);
+
+        // Writes are upserts: the second write of "alice" replaces the first.
+        assert_eq!(store.count("users").unwrap(), 3);
+        let alice: [&[u8]; 1] = [b"alice"];
+        assert_eq!(
+            store.read_rows("users", &alice).unwrap(),
+            vec![Some(b"a2".to_vec())]
+        );
+
+        let page = store.keys("users", None, 2).unwrap();
+        assert_eq!(page, vec![b"alice".to_vec(), b"bob".to_vec()]);
+        let page = store.keys("users", Some(b"bob".as_slice()), 2).unwrap();
+        assert_eq!(page, vec![b"carol".to_vec()]);
+        let page = store.keys("users", Some(b"carol".as_slice()), 2).unwrap();
+        assert!(page.is_empty());
+        // A cursor between keys starts at the next one.
+        let page = store.keys("users", Some(b"b".as_slice()), 2).unwrap();
+        assert_eq!(page, vec![b"bob".to_vec(), b"carol".to_vec()]);
+    }
+
     #[rstest]
     #[case::plain(open_plain)]
     #[case::block(open_block)]
@@ -441,6 +1258,88 @@ mod tests {
         assert_eq!(got[1].as_deref(), Some(&b"v2"[..]));
     }
 
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn delete_removes_keys(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(dir.path());
+        store.create_table("users", &schema("id")).unwrap();
+        put(&mut store, "users", &[("alice", b"a"), ("bob", b"b")]);
+
+        store
+            .delete("users", &[b"alice".as_slice(), b"nobody".as_slice()])
+            .unwrap();
+        let lookup: [&[u8]; 2] = [b"alice", b"bob"];
+        assert_eq!(store.contains("users", &lookup).unwrap(), vec![false, true]);
+        assert_eq!(store.count("users").unwrap(), 1);
+
+        store.compact("users").unwrap();
+        let got = fetch(&store, "users", &lookup);
+        assert_eq!(got[0], None);
+        assert_eq!(got[1].as_deref(), Some(&b"b"[..]));
+        assert!(matches!(
+            store.delete("nope", &[b"alice".as_slice()]),
+            Err(MurrError::TableNotFound(_))
+        ));
+    }
+
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn deactivated_keys_stay_hidden_until_written(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        let lookup: [&[u8]; 2] = [b"alice", b"bob"];
+        {
+            let mut store = open(dir.path());
+            store.create_table("users", &schema("id")).unwrap();
+            put(&mut store, "users", &[("alice", b"a"), ("bob", b"b")]);
+            store
+                .deactivate("users", &[b"alice".as_slice(), b"nobody".as_slice()])
+                .unwrap();
+        }
+
+        let mut store = open(dir.path());
+        assert_eq!(store.contains("users", &lookup).unwrap(), vec![false, true]);
+        assert_eq!(store.count("users").unwrap(), 1);
+        assert_eq!(
+            store.keys("users", None, 10).unwrap(),
+            vec![b"bob".to_vec()]
+        );
+        let hidden = |store: &RocksDBStore, key: &[u8]| {
+            let cf = store.db.cf_handle(&hidden_cf("users")).unwrap();
+            store.db.get_cf(&cf, key).unwrap()
+        };
+        assert!(hidden(&store, b"alice").is_some());
+
+        put(&mut store, "users", &[("alice", b"a2")]);
+        assert_eq!(
+            fetch(&store, "users", &lookup)[0].as_deref(),
+            Some(&b"a2"[..])
+        );
+        assert!(hidden(&store, b"alice").is_none());
+
+        store.deactivate("users", &[b"alice".as_slice()]).unwrap();
+        store
+            .reactivate("users", &[b"alice".as_slice(), b"nobody".as_slice()])
+            .unwrap();
+        assert_eq!(
+            fetch(&store, "users", &lookup)[0].as_deref(),
+            Some(&b"a2"[..])
+        );
+        assert!(hidden(&store, b"alice").is_none());
+
+        // Deletes reach hidden rows too, and compaction covers their CF.
+        store.deactivate("users", &[b"bob".as_slice()]).unwrap();
+        store.delete("users", &[b"bob".as_slice()]).unwrap();
+        store.compact("users").unwrap();
+        assert!(hidden(&store, b"bob").is_none());
+        assert!(matches!(
+            store.deactivate("nope", &[b"alice".as_slice()]),
+            Err(MurrError::TableNotFound(_))
+        ));
+    }
+
     #[rstest]
     #[case::plain(open_plain)]
     #[case::block(open_block)]
@@ -464,6 +1363,358 @@ mod tests {
         assert!(matches!(err, MurrError::TableAlreadyExists(_)));
     }
 
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn stats_report_keys_and_disk_size(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(dir.path());
+        store.create_table("users", &schema("id")).unwrap();
+        put(&mut store, "users", &[("alice", b"a"), ("bob", b"b")]);
+
+        let stats = store.stats("users").unwrap();
+        assert_eq!(stats.estimated_keys, 2);
+        assert!(stats.disk_bytes > 0);
+        assert!(stats.block_cache.is_none());
+        assert!(matches!(
+            store.stats("nope"),
+            Err(MurrError::TableNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn per_table_block_overrides() {
+        let dir = TempDir::new().unwrap();
+        let yaml = "
+bloom_filter_bits_per_key: 10
+compression: lz4
+tables:
+  users:
+    index_type: partitioned
+    block_size: 4096
+    compression: zstd
+  items:
+    bloom_filter_bits_per_key: 0
+";
+        let config: BlockConfig = serde_yaml_ng::from_str(yaml).unwrap();
+        let users = config.for_table("users");
+        assert_eq!(users.index_type, IndexType::Partitioned);
+        assert_eq!(users.block_size, 4096);
+        assert_eq!(users.bloom_filter_bits_per_key, Some(10.0));
+        assert_eq!(users.compression, Some(Compression::Zstd));
+        assert_eq!(
+            config.for_table("items").compression,
+            Some(Compression::Lz4)
+        );
+        assert_eq!(config.for_table("items").bloom_filter_bits_per_key, None);
+        assert_eq!(config.for_table("other").block_size, config.block_size);
+
+        for _ in 0..2 {
+            let mut store = RocksDBStore::open_block(dir.path(), &config).unwrap();
+            for table in ["users", "items", "other"] {
+                if !store.manifest().contains(table) {
+                    store.create_table(table, &schema("id")).unwrap();
+                }
+                put(&mut store, table, &[("alice", b"a")]);
+                // Writes the SSTs with each table's compression.
+                store.compact(table).unwrap();
+                assert_eq!(
+                    fetch(&store, table, &[b"alice".as_slice()])[0].as_deref(),
+                    Some(&b"a"[..])
+                );
+            }
+        }
+
+        let mut store = RocksDBStore::open_block(dir.path(), &config).unwrap();
+        store.rename_table("users", "people").unwrap();
+        assert!(store.table_opts.contains_key("people"));
+        assert!(!store.table_opts.contains_key("users"));
+    }
+
+    #[test]
+    fn block_cache_stats_are_store_wide() {
+        let dir = TempDir::new().unwrap();
+        let config = BlockConfig {
+            block_cache_mb: 8,
+            mmap_reads: false,
+            ..BlockConfig::default()
+        };
+        let mut store = RocksDBStore::open_block(dir.path(), &config).unwrap();
+        store.create_table("users", &schema("id")).unwrap();
+        store.create_table("items", &schema("id")).unwrap();
+        put(&mut store, "users", &[("alice", b"a"), ("bob", b"b")]);
+        store.compact("users").unwrap();
+        fetch(&store, "users", &[b"alice".as_slice(), b"bob".as_slice()]);
+        fetch(&store, "users", &[b"alice".as_slice(), b"bob".as_slice()]);
+
+        let users = store.stats("users").unwrap().block_cache.unwrap();
+        assert_eq!(users.capacity_bytes, 8 << 20);
+        assert!(users.usage_bytes > 0);
+        assert!(users.hits > 0);
+        assert_eq!(store.stats("items").unwrap().block_cache, Some(users));
+    }
+
+    #[test]
+    fn verified_reads_count_checksum_failures() {
+        let dir = TempDir::new().unwrap();
+        let config = BlockConfig {
+            compression: Some(Compression::None),
+            verify_checksums: true,
+            ..BlockConfig::default()
+        };
+        let payload = [b'x'; 256];
+        {
+            let mut store = RocksDBStore::open_block(dir.path(), &config).unwrap();
+            store.create_table("users", &schema("id")).unwrap();
+            put(&mut store, "users", &[("alice", payload.as_slice())]);
+            store.compact("users").unwrap();
+        }
+        // Flip one payload byte in place, as bit-rot would.
+        for entry in std::fs::read_dir(dir.path()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "sst") {
+                continue;
+            }
+            let mut bytes = std::fs::read(&path).unwrap();
+            if let Some(at) = bytes.windows(payload.len()).position(|w| w == payload) {
+                bytes[at + 100] ^= 0x01;
+                std::fs::write(&path, bytes).unwrap();
+            }
+        }
+
+        let store = RocksDBStore::open_block(dir.path(), &config).unwrap();
+        assert_eq!(store.stats("users").unwrap().checksum_failures, 0);
+        assert!(store.read_rows("users", &[b"alice".as_slice()]).is_err());
+        assert!(store.count("users").is_err());
+        assert_eq!(store.stats("users").unwrap().checksum_failures, 2);
+    }
+
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn default_column_family_name_is_reserved(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(dir.path());
+        let err = store.create_table("default", &schema("id")).unwrap_err();
+        assert!(matches!(err, MurrError::TableError(_)));
+        assert!(!store.manifest().contains("default"));
+    }
+
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn truncate_and_drop_survive_reopen(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        {
+            let mut store = open(dir.path());
+            store.create_table("users", &schema("id")).unwrap();
+            store.create_table("items", &schema("id")).unwrap();
+            put(&mut store, "users", &[("alice", b"a"), ("bob", b"b")]);
+            put(&mut store, "items", &[("pen", b"p")]);
+            let bob: [&[u8]; 1] = [b"bob"];
+            store.deactivate("users", &bob).unwrap();
+
+            store.truncate("users").unwrap();
+            assert_eq!(store.count("users").unwrap(), 0);
+            assert!(store.db.cf_handle(&hidden_cf("users")).is_none());
+            store.drop_table("items").unwrap();
+            assert!(!store.manifest().contains("items"));
+        }
+
+        let mut store = open(dir.path());
+        assert_eq!(store.count("users").unwrap(), 0);
+        assert!(store.manifest().contains("users"));
+        assert!(!store.manifest().contains("items"));
+        assert!(matches!(
+            store.count("items").unwrap_err(),
+            MurrError::TableNotFound(_)
+        ));
+        store.create_table("items", &schema("id")).unwrap();
+        assert_eq!(store.count("items").unwrap(), 0);
+    }
+
+    /// A drop that saved the manifest but failed to drop the column family,
+    /// or a truncate that crashed between dropping and recreating it.
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn reopen_repairs_interrupted_drop_and_truncate(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        {
+            let mut store = open(dir.path());
+            store.create_table("users", &schema("id")).unwrap();
+            store.create_table("items", &schema("id")).unwrap();
+            put(&mut store, "items", &[("pen", b"p")]);
+            store.db.drop_cf("users").unwrap();
+            store.manifest.del_table("items").unwrap();
+            store.manifest.to_file(&store.manifest_path()).unwrap();
+        }
+
+        let mut store = open(dir.path());
+        assert_eq!(store.count("users").unwrap(), 0);
+        store.create_table("items", &schema("id")).unwrap();
+        assert_eq!(store.count("items").unwrap(), 0);
+    }
+
+    /// Prefixes every payload with `v:`, as a stand-in for a new layout.
+    fn prefix_payloads(rows: Vec<KeyValue>) -> Result<Vec<KeyValue>, MurrError> {
+        let segment = payload_segment();
+        let col = &segment.columns[0];
+        Ok(rows
+            .into_iter()
+            .map(|kv| {
+                let payload = ReadRow::new(&segment, &kv.value).read_dynamic(col).to_vec();
+                let mut row = WriteRow::new(&segment, &kv.key);
+                row.write_dynamic(col, &[b"v:".as_slice(), &payload].concat());
+                row.into()
+            })
+            .collect())
+    }
+
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn alter_rewrites_rows_into_a_new_version(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        let mut altered = schema("id");
+        altered.columns.shift_remove("payload");
+        {
+            let mut store = open(dir.path());
+            store.create_table("users", &schema("id")).unwrap();
+            put(&mut store, "users", &[("alice", b"a"), ("bob", b"b")]);
+            store.deactivate("users", &[b"bob"]).unwrap();
+
+            let failed = store.alter_table("users", &altered, &|_| {
+                Err(MurrError::IoError("rewrite failed".into()))
+            });
+            assert!(failed.is_err());
+            assert_eq!(store.manifest().schema_version("users"), 0);
+            assert!(store.db.cf_handle("users\u{1f}v1").is_none());
+
+            store
+                .alter_table("users", &altered, &prefix_payloads)
+                .unwrap();
+            assert_eq!(store.manifest().schema("users"), Some(&altered));
+            assert!(store.db.cf_handle("users").is_none());
+            assert!(store.db.cf_handle("users\u{1f}hidden").is_none());
+            // Left over from a crashed alter.
+            store
+                .db
+                .create_cf("users\u{1f}v9", &Options::default())
+                .unwrap();
+        }
+
+        let mut store = open(dir.path());
+        assert!(store.db.cf_handle("users\u{1f}v9").is_none());
+        assert_eq!(store.manifest().schema_version("users"), 1);
+        let keys: [&[u8]; 2] = [b"alice", b"bob"];
+        assert_eq!(
+            fetch(&store, "users", &keys),
+            vec![Some(b"v:a".to_vec()), None]
+        );
+        put(&mut store, "users", &[("bob", b"b2")]);
+        assert_eq!(store.count("users").unwrap(), 2);
+
+        store.drop_table("users").unwrap();
+        assert!(store.db.cf_handle("users\u{1f}v1").is_none());
+        store.create_table("users", &schema("id")).unwrap();
+        assert_eq!(store.manifest().schema_version("users"), 0);
+        assert_eq!(store.count("users").unwrap(), 0);
+    }
+
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn alter_catches_up_on_writes_during_the_copy(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(dir.path());
+        let mut altered = schema("id");
+        altered.columns.shift_remove("payload");
+        store.create_table("users", &schema("id")).unwrap();
+        put(
+            &mut store,
+            "users",
+            &[("alice", b"a"), ("bob", b"b"), ("carol", b"c")],
+        );
+        store.deactivate("users", &[b"carol"]).unwrap();
+
+        // Everything below lands after the copy, so only the catch-up in
+        // `finish_alter` carries it over.
+        let copy = store.alteration("users", &altered).unwrap();
+        let copied = copy(&prefix_payloads);
+        put(&mut store, "users", &[("alice", b"a2"), ("dave", b"d")]);
+        store.delete("users", &[b"bob"]).unwrap();
+        store.reactivate("users", &[b"carol"]).unwrap();
+        store.deactivate("users", &[b"dave"]).unwrap();
+        let busy = |e: MurrError| matches!(e, MurrError::TableError(_));
+        assert!(busy(store.alteration("users", &altered).unwrap_err()));
+        assert!(busy(store.truncate("users").unwrap_err()));
+        assert!(busy(store.rename_table("users", "people").unwrap_err()));
+        assert!(busy(store.drop_table("users").unwrap_err()));
+
+        store
+            .finish_alter("users", copied, &prefix_payloads)
+            .unwrap();
+        assert_eq!(store.manifest().schema_version("users"), 1);
+        let keys: [&[u8]; 4] = [b"alice", b"bob", b"carol", b"dave"];
+        assert_eq!(
+            fetch(&store, "users", &keys),
+            vec![Some(b"v:a2".to_vec()), None, Some(b"v:c".to_vec()), None]
+        );
+        store.reactivate("users", &[b"dave"]).unwrap();
+        assert_eq!(
+            fetch(&store, "users", &[b"dave".as_slice()])[0].as_deref(),
+            Some(&b"v:d"[..])
+        );
+        store.truncate("users").unwrap();
+    }
+
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn rename_keeps_rows_in_place(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        {
+            let mut store = open(dir.path());
+            store.create_table("users", &schema("id")).unwrap();
+            put(&mut store, "users", &[("alice", b"a")]);
+            store.set_alias("prod_users", Some("users")).unwrap();
+            store.rename_table("users", "users_old").unwrap();
+            assert!(store.db.cf_handle("users").is_some());
+            assert_eq!(store.manifest().aliases["prod_users"], "users_old");
+
+            // The old name is free again, and its rows can't collide with
+            // the renamed table's.
+            store.create_table("users", &schema("id")).unwrap();
+            assert_eq!(store.count("users").unwrap(), 0);
+            let mut altered = schema("id");
+            altered.columns.shift_remove("payload");
+            store
+                .alter_table("users_old", &altered, &prefix_payloads)
+                .unwrap();
+            put(&mut store, "users", &[("bob", b"b")]);
+            store
+                .alter_table("users", &altered, &prefix_payloads)
+                .unwrap();
+            assert_ne!(
+                data_cf(store.manifest(), "users"),
+                data_cf(store.manifest(), "users_old")
+            );
+        }
+
+        let store = open(dir.path());
+        assert_eq!(store.manifest().aliases["prod_users"], "users_old");
+        let keys: [&[u8]; 2] = [b"alice", b"bob"];
+        assert_eq!(
+            fetch(&store, "users_old", &keys),
+            vec![Some(b"v:a".to_vec()), None]
+        );
+        assert_eq!(
+            fetch(&store, "users", &keys),
+            vec![None, Some(b"v:b".to_vec())]
+        );
+    }
+
     #[rstest]
     #[case::plain(open_plain)]
     #[case::block(open_block)]
@@ -483,6 +1734,27 @@ mod tests {
             .unwrap();
     }
 
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn failed_manifest_write_rolls_back_create(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        // A directory where the manifest's temp file goes makes its write fail.
+        let blocker = dir.path().join("manifest.json.tmp");
+        {
+            let mut store = open(dir.path());
+            std::fs::create_dir(&blocker).unwrap();
+            assert!(store.create_table("users", &schema("id")).is_err());
+            assert!(!store.manifest().contains("users"));
+        }
+
+        std::fs::remove_dir(&blocker).unwrap();
+        let mut store = open(dir.path());
+        assert!(!store.manifest().contains("users"));
+        store.create_table("users", &schema("id")).unwrap();
+        put(&mut store, "users", &[("alice", b"a")]);
+    }
+
     #[rstest]
     #[case::plain(open_plain)]
     #[case::block(open_block)]
@@ -523,6 +1795,113 @@ mod tests {
         assert_eq!(got[2].as_deref(), Some(&b"c"[..]));
     }
 
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn key_compaction_drops_deleted_row(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(dir.path());
+        store.create_table("users", &schema("id")).unwrap();
+        put(&mut store, "users", &[("alice", b"a"), ("bob", b"b")]);
+        store.delete("users", &[b"alice".as_slice()]).unwrap();
+
+        let compaction = store.compaction("users", Some(b"alice".as_slice()));
+        compaction.unwrap()().unwrap();
+
+        assert_eq!(store.stats("users").unwrap().estimated_keys, 1);
+        let got = fetch(&store, "users", &[b"alice".as_slice(), b"bob"]);
+        assert_eq!(got[0], None);
+        assert_eq!(got[1].as_deref(), Some(&b"b"[..]));
+    }
+
+    #[test]
+    fn plain_opens_files_with_and_without_stored_index() {
+        let dir = TempDir::new().unwrap();
+        let open = |store_index_in_file| {
+            let config = PlainConfig {
+                store_index_in_file,
+                ..PlainConfig::default()
+            };
+            RocksDBStore::open_plain(dir.path(), &config).expect("open plain")
+        };
+        {
+            let mut store = open(false);
+            store.create_table("users", &schema("id")).unwrap();
+            put(&mut store, "users", &[("alice", b"a"), ("bob", b"b")]);
+            store.compact("users").unwrap();
+        }
+        {
+            let mut store = open(true);
+            put(&mut store, "users", &[("carol", b"c")]);
+            store.compact("users").unwrap();
+        }
+
+        let store = open(true);
+        let lookup: [&[u8]; 4] = [b"alice", b"bob", b"carol", b"dave"];
+        let got = fetch(&store, "users", &lookup);
+        assert_eq!(got[0].as_deref(), Some(&b"a"[..]));
+        assert_eq!(got[2].as_deref(), Some(&b"c"[..]));
+        assert_eq!(got[3], None);
+    }
+
+    #[rstest]
+    #[case::plain(open_plain)]
+    #[case::block(open_block)]
+    fn toggles_auto_compaction(#[case] open: Opener) {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(dir.path());
+        store.create_table("users", &schema("id")).unwrap();
+        store.set_auto_compaction(false).unwrap();
+        put(&mut store, "users", &[("alice", b"a")]);
+        store.set_auto_compaction(true).unwrap();
+        assert_eq!(
+            fetch(&store, "users", &[b"alice".as_slice()])[0].as_deref(),
+            Some(&b"a"[..])
+        );
+    }
+
+    // Each put flushes its own L0 file, so with a trigger of 1 any running
+    // auto-compaction would fold them into level 1.
+    fn l0_files(store: &RocksDBStore, table: &str) -> u64 {
+        let cf = store.table_cf(table).unwrap();
+        let name = properties::num_files_at_level(0);
+        store.db.property_int_value_cf(&cf, name).unwrap().unwrap()
+    }
+
+    #[test]
+    fn table_created_while_paused_starts_paused() {
+        let dir = TempDir::new().unwrap();
+        let config = PlainConfig {
+            l0_compaction_trigger: Some(1),
+            ..PlainConfig::default()
+        };
+        let mut store = RocksDBStore::open_plain(dir.path(), &config).unwrap();
+        store.set_auto_compaction(false).unwrap();
+        store.create_table("users", &schema("id")).unwrap();
+        for (key, value) in [("alice", b"a"), ("bob", b"b"), ("carol", b"c")] {
+            put(&mut store, "users", &[(key, value.as_slice())]);
+        }
+        assert_eq!(l0_files(&store, "users"), 3);
+    }
+
+    #[test]
+    fn resuming_auto_compaction_keeps_configured_setting() {
+        let dir = TempDir::new().unwrap();
+        let config = PlainConfig {
+            disable_auto_compactions: true,
+            l0_compaction_trigger: Some(1),
+            ..PlainConfig::default()
+        };
+        let mut store = RocksDBStore::open_plain(dir.path(), &config).unwrap();
+        store.create_table("users", &schema("id")).unwrap();
+        store.set_auto_compaction(false).unwrap();
+        store.set_auto_compaction(true).unwrap();
+        for (key, value) in [("alice", b"a"), ("bob", b"b"), ("carol", b"c")] {
+            put(&mut store, "users", &[(key, value.as_slice())]);
+        }
+        assert_eq!(l0_files(&store, "users"), 3);
+    }
+
     #[rstest]
     #[case::plain(open_plain)]
     #[case::block(open_block)]
@@ -15,8 +15,10 @@ pub struct PlainConfig {
     /// PlainTable: stride between sparse-index entries (lower = faster lookup, more memory).
     #[serde(default = "default_index_sparseness")]
     pub index_sparseness: usize,
-    /// PlainTable: store the full index in the file instead of rebuilding at open.
-    #[serde(default)]
+    /// PlainTable: store the hash index in each SST when it is written, so
+    /// opening a table doesn't scan every key to rebuild it. Files written
+    /// without one still open, with the index built in memory as before.
+    #[serde(default = "default_store_index_in_file")]
     pub store_index_in_file: bool,
     #[serde(default)]
     pub huge_page_tlb_size: usize,
@@ -26,8 +28,16 @@ pub struct PlainConfig {
     pub target_file_size_base: u64,
     #[serde(default = "default_disable_auto_compactions")]
     pub disable_auto_compactions: bool,
+    /// L0 files that trigger a background compaction into the levels below.
+    /// Every write is flushed to its own L0 file, so this bounds how many
+    /// small files a lookup may have to check. RocksDB's default (4) when
+    /// unset.
+    #[serde(default)]
+    pub l0_compaction_trigger: Option<i32>,
     #[serde(default = "default_plain_read_method")]
     pub read_method: ReadMethod,
+    /// PlainTable rows carry no checksums, so this does not catch corrupt
+    /// rows. Use the block backend to check every read.
     #[serde(default)]
     pub verify_checksums: bool,
 }
@@ -38,11 +48,12 @@ impl Default for PlainConfig {
             bloom_bits_per_key: default_bloom_bits(),
             hash_table_ratio: default_data_block_hash_ratio(),
             index_sparseness: default_index_sparseness(),
-            store_index_in_file: false,
+            store_index_in_file: default_store_index_in_file(),
             huge_page_tlb_size: 0,
             write_buffer_size: default_write_buffer_size(),
             target_file_size_base: default_target_file_size_base(),
             disable_auto_compactions: default_disable_auto_compactions(),
+            l0_compaction_trigger: None,
             read_method: default_plain_read_method(),
             verify_checksums: false,
         }
@@ -59,6 +70,9 @@ fn default_bloom_bits() -> i32 {
 fn default_index_sparseness() -> usize {
     4
 }
+fn default_store_index_in_file() -> bool {
+    true
+}
 pub(super) fn default_data_block_hash_ratio() -> f64 {
     0.75
 }
@@ -83,6 +97,9 @@ impl From<&PlainConfig> for Options {
         opts.set_write_buffer_size(config.write_buffer_size);
         opts.set_target_file_size_base(config.target_file_size_base);
         opts.set_disable_auto_compactions(config.disable_auto_compactions);
+        if let Some(trigger) = config.l0_compaction_trigger {
+            opts.set_level_zero_file_num_compaction_trigger(trigger);
+        }
         opts.set_plain_table_factory(&PlainTableFactoryOptions {
             user_key_length: 0,
             bloom_bits_per_key: config.bloom_bits_per_key,
@@ -12,6 +12,7 @@ pub fn payload_segment() -> SegmentSchema {
         dtype: DTypeName::Utf8,
         name: "payload".into(),
         offset: 0,
+        encoding: Default::default(),
     }])
 }
 
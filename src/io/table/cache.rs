@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use hashlink::LruCache;
+use serde::Serialize;
+
+use crate::conf::RowCacheConfig;
+
+/// A cached store row in its stored encoding; `None` remembers that the key
+/// is absent, so storms on missing keys stay off the store too.
+pub type CachedRow = Option<Arc<[u8]>>;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RowCacheStats {
+    pub rows: u64,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// LRU of hot rows for one table. A hit skips the store lookup entirely and
+/// is decoded straight into the response builders. `Table` fills it while
+/// holding the store read lock and invalidates written keys while holding
+/// the write lock, so a stale row can never be put back after a write.
+pub struct RowCache {
+    rows: Mutex<LruCache<Box<[u8]>, CachedRow>>,
+    columns: HashSet<String>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RowCache {
+    pub fn new(config: &RowCacheConfig) -> Self {
+        Self {
+            rows: Mutex::new(LruCache::new(config.max_rows.max(1))),
+            columns: config.columns.iter().cloned().collect(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether a read of `columns` goes through the cache.
+    pub fn covers(&self, columns: &[&str]) -> bool {
+        self.columns.is_empty() || columns.iter().all(|c| self.columns.contains(*c))
+    }
+
+    /// One entry per key: the cached row on a hit, `None` on a miss.
+    pub fn get(&self, keys: &[&[u8]]) -> Vec<Option<CachedRow>> {
+        let mut rows = self.rows.lock().unwrap_or_else(PoisonError::into_inner);
+        let found: Vec<Option<CachedRow>> = keys.iter().map(|k| rows.get(*k).cloned()).collect();
+        let hits = found.iter().filter(|r| r.is_some()).count() as u64;
+        self.hits.fetch_add(hits, Ordering::Relaxed);
+        self.misses
+            .fetch_add(keys.len() as u64 - hits, Ordering::Relaxed);
+        found
+    }
+
+    pub fn insert(&self, key: &[u8], row: CachedRow) {
+        self.rows
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key.into(), row);
+    }
+
+    pub fn invalidate<'a>(&self, keys: impl IntoIterator<Item = &'a [u8]>) {
+        let mut rows = self.rows.lock().unwrap_or_else(PoisonError::into_inner);
+        for key in keys {
+            rows.remove(key);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.rows
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    pub fn stats(&self) -> RowCacheStats {
+        RowCacheStats {
+            rows: self
+                .rows
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .len() as u64,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(max_rows: usize, columns: &[&str]) -> RowCache {
+        RowCache::new(&RowCacheConfig {
+            max_rows,
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = cache(2, &[]);
+        cache.insert(b"a", Some(Arc::from(&b"1"[..])));
+        cache.insert(b"b", None);
+        assert!(cache.get(&[b"a".as_slice()])[0].is_some());
+        cache.insert(b"c", None);
+
+        let found = cache.get(&[b"a".as_slice(), b"b".as_slice(), b"c".as_slice()]);
+        assert_eq!(found[0], Some(Some(Arc::from(&b"1"[..]))));
+        assert_eq!(found[1], None);
+        assert_eq!(found[2], Some(None));
+        assert_eq!(
+            cache.stats(),
+            RowCacheStats {
+                rows: 2,
+                hits: 3,
+                misses: 1
+            }
+        );
+    }
+
+    #[test]
+    fn covers_only_configured_columns() {
+        assert!(cache(1, &[]).covers(&["x", "y"]));
+        let cache = cache(1, &["x"]);
+        assert!(cache.covers(&["x"]));
+        assert!(!cache.covers(&["x", "y"]));
+    }
+}
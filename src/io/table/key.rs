@@ -0,0 +1,197 @@
+//! How a table's key column maps to store keys. Utf8 keys are stored as their
+//! bytes. Int64 and UInt64 keys are stored as 8 big-endian bytes, with the
+//! sign bit flipped for Int64, so byte order is numeric order and a key takes
+//! 8 bytes in the index instead of up to 20 digits. `Table` still takes keys
+//! as strings; numeric keys travel in decimal and are parsed here.
+
+use std::borrow::Cow;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, Int64Array, StringArray, UInt64Array};
+use arrow::datatypes::DataType;
+
+use crate::core::{DTypeName, MurrError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeyCodec {
+    Utf8,
+    Int64,
+    UInt64,
+}
+
+impl KeyCodec {
+    pub(crate) fn for_dtype(dtype: DTypeName) -> Option<Self> {
+        match dtype {
+            DTypeName::Utf8 => Some(KeyCodec::Utf8),
+            DTypeName::Int64 => Some(KeyCodec::Int64),
+            DTypeName::UInt64 => Some(KeyCodec::UInt64),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn data_type(&self) -> DataType {
+        match self {
+            KeyCodec::Utf8 => DataType::Utf8,
+            KeyCodec::Int64 => DataType::Int64,
+            KeyCodec::UInt64 => DataType::UInt64,
+        }
+    }
+
+    /// Store form of each key. The failing key is not echoed back, as with
+    /// writes, since keys may be sensitive.
+    pub(crate) fn encode_all<'a>(&self, keys: &[&'a str]) -> Result<Vec<Cow<'a, [u8]>>, MurrError> {
+        keys.iter()
+            .enumerate()
+            .map(|(i, key)| self.encode(key).ok_or_else(|| self.invalid(i)))
+            .collect()
+    }
+
+    /// String-form keys as a column of the key's own dtype, for results that
+    /// echo the keys back.
+    pub(crate) fn array(&self, keys: &[&str]) -> Result<ArrayRef, MurrError> {
+        Ok(match self {
+            KeyCodec::Utf8 => Arc::new(StringArray::from(keys.to_vec())),
+            KeyCodec::Int64 => Arc::new(Int64Array::from(self.parse_all::<i64>(keys)?)),
+            KeyCodec::UInt64 => Arc::new(UInt64Array::from(self.parse_all::<u64>(keys)?)),
+        })
+    }
+
+    fn parse_all<T: FromStr>(&self, keys: &[&str]) -> Result<Vec<T>, MurrError> {
+        keys.iter()
+            .enumerate()
+            .map(|(i, key)| key.parse().map_err(|_| self.invalid(i)))
+            .collect()
+    }
+
+    fn invalid(&self, index: usize) -> MurrError {
+        MurrError::TableError(format!("key at index {index} is not a valid {self:?} key"))
+    }
+
+    pub(crate) fn encode<'a>(&self, key: &'a str) -> Option<Cow<'a, [u8]>> {
+        Some(match self {
+            KeyCodec::Utf8 => Cow::Borrowed(key.as_bytes()),
+            KeyCodec::Int64 => Cow::Owned(int64_bytes(key.parse().ok()?).to_vec()),
+            KeyCodec::UInt64 => Cow::Owned(key.parse::<u64>().ok()?.to_be_bytes().to_vec()),
+        })
+    }
+
+    /// Store form of a write batch's key column, which holds either the key
+    /// dtype itself or its string form.
+    pub(crate) fn encode_array<'a>(
+        &self,
+        keys: &'a dyn Array,
+    ) -> Result<Vec<Cow<'a, [u8]>>, MurrError> {
+        if keys.null_count() > 0 {
+            return Err(MurrError::SegmentError("null in key column".into()));
+        }
+        let any = keys.as_any();
+        if let Some(keys) = any.downcast_ref::<StringArray>() {
+            let keys: Vec<&str> = keys.iter().flatten().collect();
+            return self.encode_all(&keys);
+        }
+        match self {
+            KeyCodec::Int64 => {
+                if let Some(keys) = any.downcast_ref::<Int64Array>() {
+                    return Ok(keys
+                        .values()
+                        .iter()
+                        .map(|key| Cow::Owned(int64_bytes(*key).to_vec()))
+                        .collect());
+                }
+            }
+            KeyCodec::UInt64 => {
+                if let Some(keys) = any.downcast_ref::<UInt64Array>() {
+                    return Ok(keys
+                        .values()
+                        .iter()
+                        .map(|key| Cow::Owned(key.to_be_bytes().to_vec()))
+                        .collect());
+                }
+            }
+            KeyCodec::Utf8 => {}
+        }
+        Err(MurrError::SegmentError(format!(
+            "key column must be {self:?} or Utf8, got {}",
+            keys.data_type()
+        )))
+    }
+
+    /// String form of a stored key.
+    pub(crate) fn decode(&self, key: &[u8]) -> Result<String, MurrError> {
+        let fixed = || -> Result<[u8; 8], MurrError> {
+            key.try_into().map_err(|_| {
+                MurrError::SegmentError(format!("stored {self:?} key is {} bytes", key.len()))
+            })
+        };
+        Ok(match self {
+            KeyCodec::Utf8 => String::from_utf8(key.to_vec())
+                .map_err(|e| MurrError::SegmentError(format!("invalid utf8 key: {e}")))?,
+            KeyCodec::Int64 => ((u64::from_be_bytes(fixed()?) ^ SIGN_BIT) as i64).to_string(),
+            KeyCodec::UInt64 => u64::from_be_bytes(fixed()?).to_string(),
+        })
+    }
+}
+
+const SIGN_BIT: u64 = 1 << 63;
+
+/// Flipping the sign bit makes negative keys sort before positive ones.
+fn int64_bytes(key: i64) -> [u8; 8] {
+    (key as u64 ^ SIGN_BIT).to_be_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_keys_roundtrip_in_numeric_order() {
+        let keys = [
+            "-9223372036854775808",
+            "-1",
+            "0",
+            "7",
+            "9223372036854775807",
+        ];
+        let encoded = KeyCodec::Int64.encode_all(&keys).unwrap();
+        assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+        for (key, bytes) in keys.iter().zip(&encoded) {
+            assert_eq!(bytes.len(), 8);
+            assert_eq!(KeyCodec::Int64.decode(bytes).unwrap(), *key);
+        }
+
+        let max = u64::MAX.to_string();
+        let encoded = KeyCodec::UInt64.encode_all(&["1", &max]).unwrap();
+        assert_eq!(KeyCodec::UInt64.decode(&encoded[1]).unwrap(), max);
+        assert!(KeyCodec::UInt64.encode_all(&["-1"]).is_err());
+        assert!(KeyCodec::Int64.encode_all(&["abc"]).is_err());
+    }
+
+    #[test]
+    fn encode_array_takes_numbers_or_strings() {
+        let numbers = Int64Array::from(vec![-1, 5]);
+        let strings = StringArray::from(vec!["-1", "5"]);
+        assert_eq!(
+            KeyCodec::Int64.encode_array(&numbers).unwrap(),
+            KeyCodec::Int64.encode_array(&strings).unwrap()
+        );
+        assert!(KeyCodec::Utf8.encode_array(&numbers).is_err());
+        assert!(KeyCodec::UInt64.encode_array(&numbers).is_err());
+        let nulls = Int64Array::from(vec![Some(1), None]);
+        assert!(KeyCodec::Int64.encode_array(&nulls).is_err());
+    }
+
+    #[test]
+    fn array_uses_the_key_dtype() {
+        let keys = KeyCodec::UInt64
+            .array(&["1", "18446744073709551615"])
+            .unwrap();
+        assert_eq!(keys.data_type(), &DataType::UInt64);
+        let keys = KeyCodec::Int64.array(&["-3"]).unwrap();
+        assert_eq!(
+            keys.as_any().downcast_ref::<Int64Array>().unwrap().value(0),
+            -3
+        );
+        assert!(KeyCodec::Int64.array(&["x"]).is_err());
+    }
+}
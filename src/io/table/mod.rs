@@ -1,21 +1,60 @@
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
+    time::UNIX_EPOCH,
 };
 
 use crate::{
-    core::{DTypeName, MurrError, TableSchema},
+    conf::RowCacheConfig,
+    core::{Clock, DTypeName, MurrError, SystemClock, TableSchema, UPDATED_AT_COLUMN},
     io::{
-        codec::ColumnDecoder,
+        codec::{ColumnDecoder, check_encoding},
         row::{read::ReadBatchBuilder, write::WriteRow},
         schema::{SegmentColumnSchema, SegmentSchema},
-        store::Store,
+        store::{AlterCopy, Compaction, KeyValue, Store, TableStats},
+        timing::{self, Phase},
     },
 };
 use arrow::{
-    array::{Array, RecordBatch, StringArray},
-    datatypes::Schema,
+    array::{Array, ArrayRef, BooleanArray, Int64Array, RecordBatch, UInt32Array, new_null_array},
+    compute::{filter_record_batch, is_null, take_record_batch},
+    datatypes::{DataType, Field, Schema},
 };
+use serde::Serialize;
+use serde_json::Value;
+
+pub mod cache;
+mod key;
+
+use cache::{CachedRow, RowCache};
+use key::KeyCodec;
+
+/// The only column of a read that asks for no columns: whether each key has
+/// a row.
+pub const FOUND_COLUMN: &str = "found";
+
+/// What a read would return, worked out from the schema and table stats
+/// without touching any rows.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ReadEstimate {
+    /// Always one per requested key, found or not.
+    pub rows: usize,
+    /// Approximate size of the result's Arrow buffers. Variable-width columns
+    /// are sized from the table's average stored row, which includes keys
+    /// and file overhead, so wide tables lean high.
+    pub bytes: u64,
+}
+
+/// A schema change started by `Table::alteration`. It must be finished, or
+/// the store keeps refusing other changes to the table.
+pub struct Alteration<S: Store> {
+    table: Table<S>,
+    old: SegmentSchema,
+    copy: Option<AlterCopy>,
+}
+
+/// Keys read and rewritten per store lock by `Table::backfill`.
+const BACKFILL_PAGE: usize = 4096;
 
 pub struct Table<S: Store> {
     store: Arc<RwLock<S>>,
@@ -23,6 +62,10 @@ pub struct Table<S: Store> {
     table: TableSchema,
     segment: SegmentSchema,
     columns: HashMap<String, usize>,
+    key: KeyCodec,
+    row_cache: Option<RowCache>,
+    /// Source of the `UPDATED_AT_COLUMN` stamps.
+    clock: Arc<dyn Clock>,
 }
 
 impl<S: Store> Table<S> {
@@ -32,6 +75,7 @@ impl<S: Store> Table<S> {
         table: TableSchema,
     ) -> Result<Self, MurrError> {
         let name = name.into();
+        Self::validate(&table)?;
         store
             .write()
             .expect("store lock poisoned")
@@ -47,11 +91,40 @@ impl<S: Store> Table<S> {
         Self::build(store, name.into(), table)
     }
 
+    /// Serves reads through a per-table LRU of hot rows.
+    pub fn with_row_cache(mut self, config: &RowCacheConfig) -> Self {
+        self.row_cache = Some(RowCache::new(config));
+        self
+    }
+
+    /// Replaces the wall clock that stamps `UPDATED_AT_COLUMN`, e.g. with a
+    /// `ManualClock` in tests.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     pub fn schema(&self) -> &TableSchema {
         &self.table
     }
 
+    /// `keys` as this table's key column, typed like the key, for results
+    /// that carry the keys next to the values.
+    pub fn key_column(&self, keys: &[&str]) -> Result<(Field, ArrayRef), MurrError> {
+        let field = Field::new(&self.table.key, self.key.data_type(), false);
+        Ok((field, self.key.array(keys)?))
+    }
+
     pub fn write(&self, batch: &RecordBatch) -> Result<(), MurrError> {
+        let mut store = self.store.write().expect("store lock poisoned");
+        self.write_locked(&mut store, batch)
+    }
+
+    fn write_locked(&self, store: &mut S, batch: &RecordBatch) -> Result<(), MurrError> {
+        let stamped = self.stamp_updated_at(batch)?;
+        let batch = stamped.as_ref().unwrap_or(batch);
+        let merged = self.merge_existing(store, batch)?;
+        let batch = merged.as_ref().unwrap_or(batch);
         let canonical: Schema = (&self.table).into();
         let indices: Vec<usize> = canonical
             .fields()
@@ -70,16 +143,7 @@ impl<S: Store> Table<S> {
         let key_idx = canonical
             .index_of(&self.table.key)
             .map_err(|e| MurrError::ArrowError(e.to_string()))?;
-        let key_array = ordered
-            .column(key_idx)
-            .as_any()
-            .downcast_ref::<StringArray>()
-            .ok_or_else(|| {
-                MurrError::SegmentError(format!("key column '{}' must be Utf8", self.table.key))
-            })?;
-        if key_array.null_count() > 0 {
-            return Err(MurrError::SegmentError("null in key column".into()));
-        }
+        let keys = self.key.encode_array(ordered.column(key_idx).as_ref())?;
 
         let mut decoders: Vec<Box<dyn ColumnDecoder>> =
             Vec::with_capacity(self.segment.columns.len());
@@ -88,30 +152,112 @@ impl<S: Store> Table<S> {
                 .index_of(&col.name)
                 .map_err(|e| MurrError::ArrowError(e.to_string()))?;
             decoders.push(
-                col.dtype
-                    .codec()
+                col.codec()
                     .make_decoder(col.clone(), ordered.column(arr_idx).as_ref())?,
             );
         }
 
         let n = ordered.num_rows();
-        let mut store = self.store.write().expect("store lock poisoned");
-
         store.write(
             &self.name,
             (0..n).into_iter().map(|i| {
-                let mut row = WriteRow::new(&self.segment, key_array.value(i));
+                let mut row = WriteRow::new(&self.segment, &keys[i]);
                 for d in &decoders {
                     d.write_to_row(i, &mut row);
                 }
                 row.into()
             }),
         )?;
+        if let Some(cache) = &self.row_cache {
+            cache.invalidate(keys.iter().map(|key| key.as_ref()));
+        }
 
         Ok(())
     }
 
+    /// Sets `UPDATED_AT_COLUMN` to the current time on every row when the
+    /// table declares it, replacing whatever the batch carried there.
+    fn stamp_updated_at(&self, batch: &RecordBatch) -> Result<Option<RecordBatch>, MurrError> {
+        if !self.columns.contains_key(UPDATED_AT_COLUMN) {
+            return Ok(None);
+        }
+        let now = self
+            .clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        let schema = batch.schema();
+        let (mut fields, mut arrays): (Vec<_>, Vec<_>) = schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .filter(|(field, _)| field.name() != UPDATED_AT_COLUMN)
+            .map(|(field, array)| (field.clone(), array.clone()))
+            .unzip();
+        fields.push(Arc::new(Field::new(
+            UPDATED_AT_COLUMN,
+            DataType::Int64,
+            true,
+        )));
+        arrays.push(Arc::new(Int64Array::from(vec![now; batch.num_rows()])));
+        Ok(Some(RecordBatch::try_new(
+            Arc::new(Schema::new(fields)),
+            arrays,
+        )?))
+    }
+
+    /// Completes a batch that leaves out value columns with what the stored
+    /// rows hold for them, so a partial write only changes the columns it
+    /// carries. New keys get nulls. Returns `None` for complete batches and
+    /// for batches whose key column `write_locked` will reject anyway.
+    fn merge_existing(
+        &self,
+        store: &S,
+        batch: &RecordBatch,
+    ) -> Result<Option<RecordBatch>, MurrError> {
+        let schema = batch.schema();
+        let missing: Vec<&SegmentColumnSchema> = self
+            .segment
+            .columns
+            .iter()
+            .filter(|col| schema.index_of(&col.name).is_err())
+            .collect();
+        if missing.is_empty() {
+            return Ok(None);
+        }
+        let Some(Ok(keys)) = batch
+            .column_by_name(&self.table.key)
+            .map(|keys| self.key.encode_array(keys.as_ref()))
+        else {
+            return Ok(None);
+        };
+        let keys: Vec<&[u8]> = keys.iter().map(|key| key.as_ref()).collect();
+        let builder = ReadBatchBuilder::new(&self.segment, missing, keys.len());
+        let existing = store.read(&self.name, &keys, builder)?;
+
+        let mut fields = schema.fields().to_vec();
+        fields.extend(existing.schema().fields().iter().cloned());
+        let mut arrays = batch.columns().to_vec();
+        arrays.extend(existing.columns().iter().cloned());
+        Ok(Some(RecordBatch::try_new(
+            Arc::new(Schema::new(fields)),
+            arrays,
+        )?))
+    }
+
+    /// Reads `columns` for `keys`, in request order, with nulls for missing
+    /// keys. With no columns it only checks presence and returns a
+    /// `FOUND_COLUMN` batch, without decoding any row.
     pub fn read(&self, keys: &[&str], columns: &[&str]) -> Result<RecordBatch, MurrError> {
+        if columns.is_empty() {
+            let found = BooleanArray::from(self.contains(keys)?);
+            let schema = Schema::new(vec![Field::new(FOUND_COLUMN, DataType::Boolean, false)]);
+            return Ok(RecordBatch::try_new(
+                Arc::new(schema),
+                vec![Arc::new(found)],
+            )?);
+        }
         let req_cols: Vec<&SegmentColumnSchema> = columns
             .iter()
             .map(|name| {
@@ -122,21 +268,465 @@ impl<S: Store> Table<S> {
             })
             .collect::<Result<_, _>>()?;
 
-        let builder = ReadBatchBuilder::new(&self.segment, req_cols, keys.len());
-        let key_bytes: Vec<&[u8]> = keys.iter().map(|s| s.as_bytes()).collect();
+        // Skewed traffic repeats keys; look each one up once and fan the rows
+        // back out to request order afterwards.
+        let encoded = self.key.encode_all(keys)?;
+        let mut first_seen: HashMap<&[u8], u32> = HashMap::with_capacity(keys.len());
+        let mut unique: Vec<&[u8]> = Vec::with_capacity(keys.len());
+        let positions: Vec<u32> = encoded
+            .iter()
+            .map(|key| {
+                *first_seen.entry(key.as_ref()).or_insert_with(|| {
+                    unique.push(key.as_ref());
+                    (unique.len() - 1) as u32
+                })
+            })
+            .collect();
+
+        let builder = ReadBatchBuilder::new(&self.segment, req_cols, unique.len());
         let store = self.store.read().expect("store lock poisoned");
-        store.read(&self.name, &key_bytes, builder)
+        let lookup = timing::Lookup::start();
+        let batch = match &self.row_cache {
+            Some(cache) if cache.covers(columns) => {
+                self.read_cached(cache, &store, &unique, builder)?
+            }
+            _ => store.read(&self.name, &unique, builder)?,
+        };
+        if let Some(lookup) = lookup {
+            lookup.finish();
+        }
+        if unique.len() == keys.len() {
+            return Ok(batch);
+        }
+        timing::timed(Phase::Assembly, || {
+            Ok(take_record_batch(&batch, &UInt32Array::from(positions))?)
+        })
     }
 
-    fn build(store: Arc<RwLock<S>>, name: String, table: TableSchema) -> Result<Self, MurrError> {
+    /// Builds the batch from cached rows, fetching and caching the misses.
+    /// Runs under the store read lock, which `write` needs to invalidate.
+    fn read_cached(
+        &self,
+        cache: &RowCache,
+        store: &S,
+        keys: &[&[u8]],
+        mut builder: ReadBatchBuilder<'_>,
+    ) -> Result<RecordBatch, MurrError> {
+        let mut rows = cache.get(keys);
+        let missing: Vec<&[u8]> = keys
+            .iter()
+            .zip(&rows)
+            .filter(|(_, row)| row.is_none())
+            .map(|(key, _)| *key)
+            .collect();
+        if !missing.is_empty() {
+            let mut fetched = store.read_rows(&self.name, &missing)?.into_iter();
+            for (key, row) in keys.iter().zip(rows.iter_mut()) {
+                if row.is_none() {
+                    let value: CachedRow = fetched.next().flatten().map(Arc::from);
+                    cache.insert(key, value.clone());
+                    *row = Some(value);
+                }
+            }
+        }
+        for row in rows.into_iter().flatten() {
+            match row {
+                Some(bytes) => builder.add_row(&bytes)?,
+                None => builder.add_empty()?,
+            }
+        }
+        builder.build()
+    }
+
+    /// Deletes the rows for `keys` and returns how many of them existed.
+    /// Reads see them as missing keys right away.
+    pub fn delete(&self, keys: &[&str]) -> Result<usize, MurrError> {
+        let encoded = self.key.encode_all(keys)?;
+        let mut key_bytes: Vec<&[u8]> = encoded.iter().map(|key| key.as_ref()).collect();
+        key_bytes.sort_unstable();
+        key_bytes.dedup();
+        let mut store = self.store.write().expect("store lock poisoned");
+        let found = store.contains(&self.name, &key_bytes)?;
+        store.delete(&self.name, &key_bytes)?;
+        if let Some(cache) = &self.row_cache {
+            cache.invalidate(key_bytes.iter().copied());
+        }
+        Ok(found.into_iter().filter(|found| *found).count())
+    }
+
+    /// Hides the rows for `keys` and returns how many of them existed. Reads
+    /// see them as missing keys right away, but the rows are kept aside
+    /// until a write for the key replaces them or a delete removes them.
+    pub fn deactivate(&self, keys: &[&str]) -> Result<usize, MurrError> {
+        let encoded = self.key.encode_all(keys)?;
+        let mut key_bytes: Vec<&[u8]> = encoded.iter().map(|key| key.as_ref()).collect();
+        key_bytes.sort_unstable();
+        key_bytes.dedup();
+        let mut store = self.store.write().expect("store lock poisoned");
+        let found = store.contains(&self.name, &key_bytes)?;
+        store.deactivate(&self.name, &key_bytes)?;
+        if let Some(cache) = &self.row_cache {
+            cache.invalidate(key_bytes.iter().copied());
+        }
+        Ok(found.into_iter().filter(|found| *found).count())
+    }
+
+    /// Brings back the rows `deactivate` hid for `keys`, unchanged, and
+    /// returns how many came back. Keys that aren't deactivated are left
+    /// alone.
+    pub fn reactivate(&self, keys: &[&str]) -> Result<usize, MurrError> {
+        let encoded = self.key.encode_all(keys)?;
+        let mut key_bytes: Vec<&[u8]> = encoded.iter().map(|key| key.as_ref()).collect();
+        key_bytes.sort_unstable();
+        key_bytes.dedup();
+        let mut store = self.store.write().expect("store lock poisoned");
+        let before = store.contains(&self.name, &key_bytes)?;
+        store.reactivate(&self.name, &key_bytes)?;
+        let after = store.contains(&self.name, &key_bytes)?;
+        if let Some(cache) = &self.row_cache {
+            cache.invalidate(key_bytes.iter().copied());
+        }
+        Ok(before
+            .into_iter()
+            .zip(after)
+            .filter(|(before, after)| !before && *after)
+            .count())
+    }
+
+    /// Removes every row, deactivated ones included, and keeps the schema.
+    pub fn truncate(&self) -> Result<(), MurrError> {
+        let mut store = self.store.write().expect("store lock poisoned");
+        store.truncate(&self.name)?;
+        if let Some(cache) = &self.row_cache {
+            cache.clear();
+        }
+        Ok(())
+    }
+
+    /// Switches the table to `schema` in one go; see `alteration`. Returns
+    /// the table under its new schema, which replaces this one.
+    pub fn alter(&self, schema: TableSchema) -> Result<Self, MurrError> {
+        let mut alteration = self.alteration(schema)?;
+        let copied = alteration.copy();
+        alteration.finish(copied)
+    }
+
+    /// Starts switching the table to `schema`, which must keep the key and
+    /// every remaining column as they are. Each stored row is rewritten to
+    /// the new layout, with added columns null on existing rows. The store
+    /// write lock is only taken to start and to finish, so reads and writes
+    /// go on against the old layout during the copy.
+    pub fn alteration(&self, schema: TableSchema) -> Result<Alteration<S>, MurrError> {
+        Self::validate(&schema)?;
+        if schema.key != self.table.key
+            || schema.columns[&schema.key] != self.table.columns[&self.table.key]
+        {
+            return Err(MurrError::TableError(
+                "the key column cannot be changed".into(),
+            ));
+        }
+        let altered = Self::build(self.store.clone(), self.name.clone(), schema)?
+            .with_clock(self.clock.clone());
+        for col in &altered.segment.columns {
+            let Some(&i) = self.columns.get(&col.name) else {
+                continue;
+            };
+            let old = &self.segment.columns[i];
+            if old.dtype != col.dtype || old.encoding != col.encoding {
+                return Err(MurrError::TableError(format!(
+                    "column '{}' cannot change its dtype or encoding",
+                    col.name
+                )));
+            }
+        }
+        let copy = self
+            .store
+            .write()
+            .expect("store lock poisoned")
+            .alteration(&self.name, &altered.table)?;
+        Ok(Alteration {
+            table: altered,
+            old: self.segment.clone(),
+            copy: Some(copy),
+        })
+    }
+
+    /// Moves the table to `name` without touching its rows. Returns the table
+    /// under its new name, which replaces this one.
+    pub fn rename(&self, name: impl Into<String>) -> Result<Self, MurrError> {
+        let renamed = Self::build(self.store.clone(), name.into(), self.table.clone())?
+            .with_clock(self.clock.clone());
+        self.store
+            .write()
+            .expect("store lock poisoned")
+            .rename_table(&self.name, &renamed.name)?;
+        Ok(renamed)
+    }
+
+    /// Rows stored in the `old` layout, in this table's layout.
+    fn reencode(
+        &self,
+        old: &SegmentSchema,
+        rows: Vec<KeyValue>,
+    ) -> Result<Vec<KeyValue>, MurrError> {
+        let kept: Vec<&SegmentColumnSchema> = old
+            .columns
+            .iter()
+            .filter(|col| self.columns.contains_key(&col.name))
+            .collect();
+        // A batch needs at least one column.
+        let existing = if kept.is_empty() {
+            None
+        } else {
+            let mut builder = ReadBatchBuilder::new(old, kept, rows.len());
+            for row in &rows {
+                builder.add_row(&row.value)?;
+            }
+            Some(builder.build()?)
+        };
+        let mut decoders: Vec<Box<dyn ColumnDecoder>> =
+            Vec::with_capacity(self.segment.columns.len());
+        for col in &self.segment.columns {
+            let array = match existing
+                .as_ref()
+                .and_then(|batch| batch.column_by_name(&col.name))
+            {
+                Some(array) => array.clone(),
+                None => new_null_array(&col.dtype.codec().arrow_dtype(), rows.len()),
+            };
+            decoders.push(col.codec().make_decoder(col.clone(), array.as_ref())?);
+        }
+        Ok(rows
+            .iter()
+            .enumerate()
+            .map(|(i, kv)| {
+                let mut row = WriteRow::new(&self.segment, &kv.key);
+                for d in &decoders {
+                    d.write_to_row(i, &mut row);
+                }
+                row.into()
+            })
+            .collect())
+    }
+
+    /// Sets `column` to `value` on every row where it is null, e.g. to give a
+    /// column a default after rows were written without it, and returns how
+    /// many rows that was. Walks the keys a page at a time, holding the store
+    /// write lock per page so concurrent writes are never overwritten, and
+    /// rewrites only the rows it changes. With `dry_run` it only counts them.
+    pub fn backfill(&self, column: &str, value: &Value, dry_run: bool) -> Result<usize, MurrError> {
+        let mut cursor = None;
+        let mut filled = 0;
+        while let Some(rows) = self.backfill_page(column, value, dry_run, &mut cursor)? {
+            filled += rows;
+        }
+        Ok(filled)
+    }
+
+    /// One page of `backfill`: the keys after `cursor`, which it advances.
+    /// Returns the rows filled, or `None` once no keys are left. Callers that
+    /// must not hold anything across the whole walk, like the service's
+    /// table registry, loop over this themselves.
+    pub fn backfill_page(
+        &self,
+        column: &str,
+        value: &Value,
+        dry_run: bool,
+        cursor: &mut Option<Vec<u8>>,
+    ) -> Result<Option<usize>, MurrError> {
+        let col = self
+            .columns
+            .get(column)
+            .map(|idx| &self.segment.columns[*idx])
+            .ok_or_else(|| MurrError::TableError(format!("column '{column}' not found")))?;
+        if value.is_null() {
+            return Err(MurrError::TableError(
+                "backfill value can't be null".to_string(),
+            ));
+        }
+        // Fails early on a value the column can't hold.
+        col.dtype.codec().from_json(std::slice::from_ref(value))?;
+
+        let all: Vec<&SegmentColumnSchema> = self.segment.columns.iter().collect();
+        let position = self.columns[column];
+        let mut store = self.store.write().expect("store lock poisoned");
+        let keys = store.keys(&self.name, cursor.as_deref(), BACKFILL_PAGE)?;
+        let Some(last) = keys.last() else {
+            return Ok(None);
+        };
+        *cursor = Some(last.clone());
+        let keys: Vec<&[u8]> = keys.iter().map(Vec::as_slice).collect();
+        let builder = ReadBatchBuilder::new(&self.segment, all, keys.len());
+        let rows = store.read(&self.name, &keys, builder)?;
+        let missing = is_null(rows.column(position))?;
+        let count = missing.true_count();
+        if count == 0 || dry_run {
+            return Ok(Some(count));
+        }
+
+        let rows = filter_record_batch(&rows, &missing)?;
+        let keys = keys
+            .iter()
+            .zip(missing.values())
+            .filter(|(_, missing)| *missing)
+            .map(|(key, _)| self.key.decode(key))
+            .collect::<Result<Vec<_>, _>>()?;
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let (key_field, keys) = self.key_column(&keys)?;
+        let mut fields = vec![Arc::new(key_field)];
+        let mut arrays: Vec<ArrayRef> = vec![keys];
+        for (i, field) in rows.schema().fields().iter().enumerate() {
+            fields.push(field.clone());
+            arrays.push(if i == position {
+                col.dtype.codec().from_json(&vec![value.clone(); count])?
+            } else {
+                rows.column(i).clone()
+            });
+        }
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+        self.write_locked(&mut store, &batch)?;
+        Ok(Some(count))
+    }
+
+    pub fn contains(&self, keys: &[&str]) -> Result<Vec<bool>, MurrError> {
+        let encoded = self.key.encode_all(keys)?;
+        let key_bytes: Vec<&[u8]> = encoded.iter().map(|key| key.as_ref()).collect();
+        let store = self.store.read().expect("store lock poisoned");
+        store.contains(&self.name, &key_bytes)
+    }
+
+    /// Sizes a read of `keys` keys and `columns` from the schema and
+    /// `stats`, for clients choosing between fetching and exporting.
+    pub fn estimate_read(&self, keys: usize, columns: &[&str]) -> Result<ReadEstimate, MurrError> {
+        let validity = keys.div_ceil(8) as u64;
+        if columns.is_empty() {
+            return Ok(ReadEstimate {
+                rows: keys,
+                bytes: validity,
+            });
+        }
+        let requested: Vec<&SegmentColumnSchema> = columns
+            .iter()
+            .map(|name| {
+                self.columns
+                    .get(*name)
+                    .map(|&i| &self.segment.columns[i])
+                    .ok_or_else(|| MurrError::SegmentError(format!("column '{name}' not found")))
+            })
+            .collect::<Result<_, _>>()?;
+
+        // Whatever a stored row holds beyond its bitset and static section is
+        // variable-width payload, shared out evenly between those columns.
+        let stats = self
+            .store
+            .read()
+            .expect("store lock poisoned")
+            .stats(&self.name)?;
+        let stored = stats.disk_bytes + stats.memtable_bytes;
+        let avg_row = stored.checked_div(stats.estimated_keys).unwrap_or(0);
+        let static_row = (self.segment.bitset_size + self.segment.capacity) as u64;
+        let variable = self
+            .segment
+            .columns
+            .iter()
+            .filter(|col| col.dtype.codec().arrow_dtype().primitive_width().is_none())
+            .count() as u64;
+        let payload = avg_row
+            .saturating_sub(static_row)
+            .checked_div(variable)
+            .unwrap_or(0);
+
+        let rows = keys as u64;
+        let bytes = requested
+            .iter()
+            .map(|col| {
+                let values = match col.dtype.codec().arrow_dtype() {
+                    DataType::Boolean => validity,
+                    dtype => match dtype.primitive_width() {
+                        Some(width) => width as u64 * rows,
+                        // 4-byte offsets plus the values.
+                        None => (4 + payload) * rows,
+                    },
+                };
+                validity + values
+            })
+            .sum();
+        Ok(ReadEstimate { rows: keys, bytes })
+    }
+
+    pub fn stats(&self) -> Result<TableStats, MurrError> {
+        let store = self.store.read().expect("store lock poisoned");
+        let mut stats = store.stats(&self.name)?;
+        stats.row_cache = self.row_cache.as_ref().map(RowCache::stats);
+        Ok(stats)
+    }
+
+    /// Rewrites the table's files with the store's current options, building
+    /// fresh indexes and filters (e.g. after changing `index_type`). The
+    /// compaction runs without the store lock: reads keep using the old
+    /// files until the new ones are swapped in, and writes go on meanwhile.
+    pub fn rebuild(&self) -> Result<(), MurrError> {
+        self.compaction(None)?()
+    }
+
+    /// Like `rebuild` when run, or limited to the rows of `key` when given,
+    /// e.g. to drop a deleted key's data from disk. Holds no lock until run.
+    pub fn compaction(&self, key: Option<&str>) -> Result<Compaction, MurrError> {
+        let key = match key {
+            Some(key) => Some(self.key.encode_all(&[key])?.remove(0)),
+            None => None,
+        };
+        let store = self.store.read().expect("store lock poisoned");
+        store.compaction(&self.name, key.as_deref())
+    }
+
+    pub fn count(&self) -> Result<usize, MurrError> {
+        let store = self.store.read().expect("store lock poisoned");
+        store.count(&self.name)
+    }
+
+    pub fn keys(&self, cursor: Option<&str>, limit: usize) -> Result<Vec<String>, MurrError> {
+        let cursor = match cursor {
+            Some(cursor) => Some(self.key.encode_all(&[cursor])?.remove(0)),
+            None => None,
+        };
+        let store = self.store.read().expect("store lock poisoned");
+        store
+            .keys(&self.name, cursor.as_deref(), limit)?
+            .iter()
+            .map(|key| self.key.decode(key))
+            .collect()
+    }
+
+    /// Schema checks that must pass before the store learns about a table.
+    fn validate(table: &TableSchema) -> Result<(), MurrError> {
         let key_col = table.columns.get(&table.key).ok_or_else(|| {
             MurrError::TableError(format!("key column '{}' not in schema", table.key))
         })?;
-        if key_col.dtype != DTypeName::Utf8 {
+        if KeyCodec::for_dtype(key_col.dtype).is_none() {
             return Err(MurrError::TableError(
-                "io currently supports Utf8 keys only".into(),
+                "key column must be utf8, int64 or uint64".into(),
             ));
         }
+        for (name, col) in &table.columns {
+            check_encoding(name, col.dtype, col.encoding)?;
+        }
+        if table
+            .columns
+            .get(UPDATED_AT_COLUMN)
+            .is_some_and(|col| col.dtype != DTypeName::Int64)
+        {
+            return Err(MurrError::TableError(format!(
+                "column '{UPDATED_AT_COLUMN}' holds write timestamps and must be int64"
+            )));
+        }
+        Ok(())
+    }
+
+    fn build(store: Arc<RwLock<S>>, name: String, table: TableSchema) -> Result<Self, MurrError> {
+        Self::validate(&table)?;
+        let key = KeyCodec::for_dtype(table.columns[&table.key].dtype)
+            .expect("validate checked the key dtype");
         let segment = SegmentSchema::from(&table);
         let columns = segment
             .columns
@@ -150,20 +740,50 @@ impl<S: Store> Table<S> {
             table,
             segment,
             columns,
+            key,
+            row_cache: None,
+            clock: Arc::new(SystemClock),
         })
     }
 }
 
+impl<S: Store> Alteration<S> {
+    /// Rewrites the stored rows to the new layout without taking the store
+    /// lock. Only the first call copies anything.
+    pub fn copy(&mut self) -> Result<(), MurrError> {
+        match self.copy.take() {
+            Some(copy) => copy(&|rows| self.table.reencode(&self.old, rows)),
+            None => Ok(()),
+        }
+    }
+
+    /// Switches the store to the new layout once `copy` returned `copied`,
+    /// first rewriting rows written during the copy, under the store write
+    /// lock. Returns the table under its new schema, which replaces the old
+    /// one. A failed copy leaves the table as it was.
+    pub fn finish(self, copied: Result<(), MurrError>) -> Result<Table<S>, MurrError> {
+        self.table
+            .store
+            .write()
+            .expect("store lock poisoned")
+            .finish_alter(&self.table.name, copied, &|rows| {
+                self.table.reencode(&self.old, rows)
+            })?;
+        Ok(self.table)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::{Arc, RwLock};
+    use std::time::Duration;
 
-    use arrow::array::{Float32Array, Float64Array, RecordBatch, StringArray};
+    use arrow::array::{Float32Array, Float64Array, RecordBatch, StringArray, UInt64Array};
     use arrow::datatypes::{DataType, Field, Schema};
     use indexmap::IndexMap;
 
     use super::*;
-    use crate::core::{ColumnSchema, DTypeName, TableSchema};
+    use crate::core::{ColumnEncoding, ColumnSchema, DTypeName, ManualClock, TableSchema};
     use crate::io::store::memory::MemoryStore;
 
     fn store() -> Arc<RwLock<MemoryStore>> {
@@ -177,6 +797,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: false,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -184,11 +805,15 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Float32,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         TableSchema {
             key: "id".into(),
             columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
         }
     }
 
@@ -253,6 +878,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: false,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -260,6 +886,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Float32,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -267,11 +894,15 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         let schema = TableSchema {
             key: "id".into(),
             columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
         };
 
         let arrow_schema = Arc::new(Schema::new(vec![
@@ -335,6 +966,81 @@ mod tests {
         assert_eq!(project_f32(&out, "score").value(0), 7.0);
     }
 
+    #[test]
+    fn partial_write_keeps_other_columns() {
+        let mut schema = schema_id_score();
+        schema.columns.insert(
+            "label".into(),
+            ColumnSchema {
+                dtype: DTypeName::Utf8,
+                nullable: true,
+                encoding: Default::default(),
+            },
+        );
+        let table = Table::create(store(), "t", schema).unwrap();
+        let full = RecordBatch::try_from_iter([
+            ("id", Arc::new(StringArray::from(vec!["a"])) as ArrayRef),
+            ("score", Arc::new(Float32Array::from(vec![1.0]))),
+            ("label", Arc::new(StringArray::from(vec!["first"]))),
+        ])
+        .unwrap();
+        table.write(&full).unwrap();
+
+        table
+            .write(&batch_id_score(
+                &[Some("a"), Some("b")],
+                &[Some(2.0), Some(3.0)],
+            ))
+            .unwrap();
+        let out = table.read(&["a", "b"], &["score", "label"]).unwrap();
+        let scores = project_f32(&out, "score");
+        assert_eq!((scores.value(0), scores.value(1)), (2.0, 3.0));
+        let labels = project_string(&out, "label");
+        assert_eq!(labels.value(0), "first");
+        assert!(labels.is_null(1));
+
+        let keys_only = RecordBatch::try_from_iter([(
+            "score",
+            Arc::new(Float32Array::from(vec![1.0])) as ArrayRef,
+        )])
+        .unwrap();
+        assert!(table.write(&keys_only).is_err());
+    }
+
+    #[test]
+    fn updated_at_is_stamped_on_every_write() {
+        let mut schema = schema_id_score();
+        schema.columns.insert(
+            UPDATED_AT_COLUMN.into(),
+            ColumnSchema {
+                dtype: DTypeName::Int64,
+                nullable: true,
+                encoding: Default::default(),
+            },
+        );
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(1)));
+        let table = Table::create(store(), "t", schema.clone())
+            .unwrap()
+            .with_clock(clock.clone());
+        table
+            .write(&batch_id_score(&[Some("a")], &[Some(1.0)]))
+            .unwrap();
+        clock.advance(Duration::from_secs(1));
+        let forged = RecordBatch::try_from_iter([
+            ("id", Arc::new(StringArray::from(vec!["b"])) as ArrayRef),
+            (UPDATED_AT_COLUMN, Arc::new(Int64Array::from(vec![1]))),
+        ])
+        .unwrap();
+        table.write(&forged).unwrap();
+
+        let out = table.read(&["a", "b"], &[UPDATED_AT_COLUMN]).unwrap();
+        let stamps = out.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(&stamps.values()[..], &[1_000, 2_000]);
+
+        schema.columns[UPDATED_AT_COLUMN].dtype = DTypeName::Utf8;
+        assert!(Table::create(store(), "u", schema).is_err());
+    }
+
     #[test]
     fn read_missing_keys_returns_nulls() {
         let table = Table::create(store(), "t", schema_id_score()).unwrap();
@@ -348,6 +1054,257 @@ mod tests {
         assert!(scores.is_null(1));
     }
 
+    #[test]
+    fn read_without_columns_reports_presence() {
+        let table = Table::create(store(), "t", schema_id_score()).unwrap();
+        table.write(&batch_id_score(&[Some("a")], &[None])).unwrap();
+
+        let out = table.read(&["missing", "a", "a"], &[]).unwrap();
+        assert_eq!(out.num_columns(), 1);
+        let found = out
+            .column_by_name(FOUND_COLUMN)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<BooleanArray>()
+            .unwrap();
+        assert_eq!(found, &BooleanArray::from(vec![false, true, true]));
+        assert_eq!(table.read(&[], &[]).unwrap().num_rows(), 0);
+    }
+
+    #[test]
+    fn read_duplicate_keys_expands_to_request_order() {
+        let table = Table::create(store(), "t", schema_id_score()).unwrap();
+        table
+            .write(&batch_id_score(
+                &[Some("a"), Some("b")],
+                &[Some(1.0), Some(2.0)],
+            ))
+            .unwrap();
+
+        let out = table.read(&["b", "a", "b", "x", "x"], &["score"]).unwrap();
+        assert_eq!(out.num_rows(), 5);
+        let scores = project_f32(&out, "score");
+        assert_eq!(scores.value(0), 2.0);
+        assert_eq!(scores.value(1), 1.0);
+        assert_eq!(scores.value(2), 2.0);
+        assert!(scores.is_null(3));
+        assert!(scores.is_null(4));
+    }
+
+    #[test]
+    fn row_cache_serves_hits_and_drops_written_keys() {
+        let table = Table::create(store(), "t", schema_id_score())
+            .unwrap()
+            .with_row_cache(&RowCacheConfig {
+                max_rows: 16,
+                columns: vec![],
+            });
+        table
+            .write(&batch_id_score(&[Some("a")], &[Some(1.0)]))
+            .unwrap();
+
+        let out = table.read(&["a", "x"], &["score"]).unwrap();
+        assert_eq!(project_f32(&out, "score").value(0), 1.0);
+        let out = table.read(&["a", "x", "a"], &["score"]).unwrap();
+        let scores = project_f32(&out, "score");
+        assert_eq!(scores.value(2), 1.0);
+        assert!(scores.is_null(1));
+
+        table
+            .write(&batch_id_score(
+                &[Some("a"), Some("x")],
+                &[Some(2.0), Some(3.0)],
+            ))
+            .unwrap();
+        let out = table.read(&["a", "x"], &["score"]).unwrap();
+        let scores = project_f32(&out, "score");
+        assert_eq!(scores.value(0), 2.0);
+        assert_eq!(scores.value(1), 3.0);
+
+        let stats = table.stats().unwrap().row_cache.unwrap();
+        assert_eq!((stats.rows, stats.hits, stats.misses), (2, 2, 4));
+    }
+
+    #[test]
+    fn deactivated_keys_read_missing_until_rewritten() {
+        let table = Table::create(store(), "t", schema_id_score())
+            .unwrap()
+            .with_row_cache(&RowCacheConfig {
+                max_rows: 16,
+                columns: vec![],
+            });
+        table
+            .write(&batch_id_score(
+                &[Some("a"), Some("b")],
+                &[Some(1.0), Some(2.0)],
+            ))
+            .unwrap();
+        table.read(&["a"], &["score"]).unwrap();
+
+        assert_eq!(table.deactivate(&["a", "a", "missing"]).unwrap(), 1);
+        let out = table.read(&["a", "b"], &["score"]).unwrap();
+        assert!(project_f32(&out, "score").is_null(0));
+        assert_eq!(table.contains(&["a"]).unwrap(), vec![false]);
+        assert_eq!(table.count().unwrap(), 1);
+
+        table
+            .write(&batch_id_score(&[Some("a")], &[Some(3.0)]))
+            .unwrap();
+        let out = table.read(&["a"], &["score"]).unwrap();
+        assert_eq!(project_f32(&out, "score").value(0), 3.0);
+
+        table.deactivate(&["b"]).unwrap();
+        assert!(project_f32(&table.read(&["b"], &["score"]).unwrap(), "score").is_null(0));
+        assert_eq!(table.reactivate(&["a", "b", "missing"]).unwrap(), 1);
+        let out = table.read(&["a", "b"], &["score"]).unwrap();
+        assert_eq!(&project_f32(&out, "score").values()[..], &[3.0, 2.0]);
+    }
+
+    #[test]
+    fn truncate_empties_table_and_row_cache() {
+        let table = Table::create(store(), "t", schema_id_score())
+            .unwrap()
+            .with_row_cache(&RowCacheConfig {
+                max_rows: 16,
+                columns: vec![],
+            });
+        table
+            .write(&batch_id_score(&[Some("a")], &[Some(1.0)]))
+            .unwrap();
+        table.read(&["a"], &["score"]).unwrap();
+
+        table.truncate().unwrap();
+        let out = table.read(&["a"], &["score"]).unwrap();
+        assert!(project_f32(&out, "score").is_null(0));
+        assert_eq!(table.count().unwrap(), 0);
+        table
+            .write(&batch_id_score(&[Some("b")], &[Some(2.0)]))
+            .unwrap();
+        assert_eq!(table.keys(None, 10).unwrap(), vec!["b"]);
+    }
+
+    #[test]
+    fn alter_adds_and_drops_columns_of_existing_rows() {
+        let mut schema = schema_id_score();
+        schema.columns.insert(
+            "name".into(),
+            ColumnSchema {
+                dtype: DTypeName::Utf8,
+                nullable: true,
+                encoding: Default::default(),
+            },
+        );
+        let store = store();
+        let table = Table::create(store.clone(), "t", schema.clone()).unwrap();
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Utf8, false),
+                Field::new("score", DataType::Float32, true),
+                Field::new("name", DataType::Utf8, true),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b"])),
+                Arc::new(Float32Array::from(vec![1.0, 2.0])),
+                Arc::new(StringArray::from(vec![Some("alice"), None])),
+            ],
+        )
+        .unwrap();
+        table.write(&batch).unwrap();
+
+        let mut altered = schema.clone();
+        altered.columns.shift_remove("score");
+        altered.columns.insert(
+            "clicks".into(),
+            ColumnSchema {
+                dtype: DTypeName::Int64,
+                nullable: true,
+                encoding: Default::default(),
+            },
+        );
+        let table = table.alter(altered.clone()).unwrap();
+        assert_eq!(table.schema(), &altered);
+        assert_eq!(store.read().unwrap().manifest().schema_version("t"), 1);
+        let out = table.read(&["a", "b"], &["name", "clicks"]).unwrap();
+        let names = project_string(&out, "name");
+        assert_eq!(names.value(0), "alice");
+        assert!(names.is_null(1));
+        assert_eq!(out.column_by_name("clicks").unwrap().null_count(), 2);
+        assert!(table.read(&["a"], &["score"]).is_err());
+
+        let clicks = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Utf8, false),
+                Field::new("clicks", DataType::Int64, true),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["b"])),
+                Arc::new(Int64Array::from(vec![7])),
+            ],
+        )
+        .unwrap();
+        table.write(&clicks).unwrap();
+        let out = table.read(&["b"], &["clicks"]).unwrap();
+        let out = out.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(out.value(0), 7);
+
+        let mut retyped = altered.clone();
+        retyped.columns["name"].dtype = DTypeName::Binary;
+        assert!(table.alter(retyped).is_err());
+        let mut rekeyed = altered;
+        rekeyed.key = "name".into();
+        assert!(table.alter(rekeyed).is_err());
+    }
+
+    #[test]
+    fn estimate_read_sizes_columns_without_reading() {
+        let mut schema = schema_id_score();
+        schema.columns.insert(
+            "name".into(),
+            ColumnSchema {
+                dtype: DTypeName::Utf8,
+                nullable: true,
+                encoding: Default::default(),
+            },
+        );
+        let table = Table::create(store(), "t", schema).unwrap();
+        let estimate = table.estimate_read(16, &["score"]).unwrap();
+        assert_eq!(estimate.rows, 16);
+        assert_eq!(estimate.bytes, 2 + 16 * 4);
+        // An empty table has no payload to average over.
+        assert_eq!(
+            table.estimate_read(16, &["name"]).unwrap().bytes,
+            2 + 16 * 4
+        );
+
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Utf8, false),
+                Field::new("name", DataType::Utf8, true),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b"])),
+                Arc::new(StringArray::from(vec!["x".repeat(100), "y".repeat(100)])),
+            ],
+        )
+        .unwrap();
+        table.write(&batch).unwrap();
+        let name = table.estimate_read(16, &["name"]).unwrap().bytes;
+        assert!(name > 16 * 100, "{name}");
+        assert_eq!(table.estimate_read(16, &[]).unwrap().bytes, 2);
+        assert!(table.estimate_read(16, &["nope"]).is_err());
+    }
+
+    #[test]
+    fn contains_counts_rows_with_null_values() {
+        let table = Table::create(store(), "t", schema_id_score()).unwrap();
+        table
+            .write(&batch_id_score(&[Some("a"), Some("b")], &[Some(1.0), None]))
+            .unwrap();
+
+        let found = table.contains(&["b", "missing", "a"]).unwrap();
+        assert_eq!(found, vec![true, false, true]);
+    }
+
     #[test]
     fn read_unknown_column_errors() {
         let table = Table::create(store(), "t", schema_id_score()).unwrap();
@@ -385,6 +1342,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: false,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -392,6 +1350,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Float32,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -399,6 +1358,7 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Float64,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         columns.insert(
@@ -406,11 +1366,15 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Utf8,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
         let schema = TableSchema {
             key: "id".into(),
             columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
         };
 
         let arrow_schema = Arc::new(Schema::new(vec![
@@ -493,15 +1457,113 @@ mod tests {
             ColumnSchema {
                 dtype: DTypeName::Float32,
                 nullable: false,
+                encoding: Default::default(),
             },
         );
         let schema = TableSchema {
             key: "id".into(),
             columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
         };
         assert!(matches!(
             Table::create(store(), "t", schema),
             Err(MurrError::TableError(_))
         ));
     }
+
+    #[test]
+    fn int64_keys_roundtrip_in_numeric_order() {
+        let mut schema = schema_id_score();
+        schema.columns["id"].dtype = DTypeName::Int64;
+        let table = Table::create(store(), "t", schema).unwrap();
+        let batch = RecordBatch::try_from_iter([
+            (
+                "id",
+                Arc::new(Int64Array::from(vec![10, -3, 2])) as ArrayRef,
+            ),
+            ("score", Arc::new(Float32Array::from(vec![1.0, 2.0, 3.0]))),
+        ])
+        .unwrap();
+        table.write(&batch).unwrap();
+
+        let out = table.read(&["2", "-3", "99"], &["score"]).unwrap();
+        let scores = project_f32(&out, "score");
+        assert_eq!((scores.value(0), scores.value(1)), (3.0, 2.0));
+        assert!(scores.is_null(2));
+        assert_eq!(table.keys(None, 10).unwrap(), vec!["-3", "2", "10"]);
+        assert_eq!(table.keys(Some("2"), 10).unwrap(), vec!["10"]);
+        assert_eq!(table.delete(&["10"]).unwrap(), 1);
+        assert!(matches!(
+            table.read(&["ten"], &["score"]),
+            Err(MurrError::TableError(_))
+        ));
+    }
+
+    #[test]
+    fn backfill_and_key_column_keep_int_key_type() {
+        let mut schema = schema_id_score();
+        schema.columns["id"].dtype = DTypeName::UInt64;
+        let table = Table::create(store(), "t", schema).unwrap();
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(UInt64Array::from(vec![1, 2])) as ArrayRef),
+            ("score", Arc::new(Float32Array::from(vec![Some(1.0), None]))),
+        ])
+        .unwrap();
+        table.write(&batch).unwrap();
+
+        assert_eq!(
+            table
+                .backfill("score", &serde_json::json!(0.5), false)
+                .unwrap(),
+            1
+        );
+        let scores = project_f32(&table.read(&["1", "2"], &["score"]).unwrap(), "score");
+        assert_eq!(scores.values(), &[1.0, 0.5]);
+        let (field, keys) = table.key_column(&["2"]).unwrap();
+        assert_eq!(field.data_type(), &DataType::UInt64);
+        assert_eq!(keys.data_type(), &DataType::UInt64);
+    }
+
+    #[test]
+    fn backfill_pages_through_every_row() {
+        let table = Table::create(store(), "t", schema_id_score()).unwrap();
+        let ids: Vec<String> = (0..=BACKFILL_PAGE).map(|i| format!("k{i:05}")).collect();
+        let ids: Vec<Option<&str>> = ids.iter().map(|id| Some(id.as_str())).collect();
+        table
+            .write(&batch_id_score(&ids, &vec![None; ids.len()]))
+            .unwrap();
+
+        let default = serde_json::json!(0.5);
+        let mut cursor = None;
+        let first = table.backfill_page("score", &default, true, &mut cursor);
+        assert_eq!(first.unwrap(), Some(BACKFILL_PAGE));
+        assert_eq!(
+            table.backfill("score", &default, false).unwrap(),
+            BACKFILL_PAGE + 1
+        );
+        assert_eq!(table.backfill("score", &default, false).unwrap(), 0);
+    }
+
+    #[test]
+    fn half_float_column_reads_back_float32() {
+        let mut schema = schema_id_score();
+        schema.columns["score"].encoding = ColumnEncoding::F16;
+        let table = Table::create(store(), "t", schema).unwrap();
+        table
+            .write(&batch_id_score(&[Some("a"), Some("b")], &[Some(0.5), None]))
+            .unwrap();
+        let out = table.read(&["a", "b"], &["score"]).unwrap();
+        let scores = project_f32(&out, "score");
+        assert_eq!(scores.value(0), 0.5);
+        assert!(scores.is_null(1));
+
+        let mut schema = schema_id_score();
+        schema.columns["id"].encoding = ColumnEncoding::Bf16;
+        assert!(matches!(
+            Table::create(store(), "u", schema),
+            Err(MurrError::TableError(_))
+        ));
+    }
 }
@@ -0,0 +1,157 @@
+//! Opt-in timing breakdown of the read path for `debug=true` requests.
+//!
+//! Collection is per thread: `profile` switches it on around one request,
+//! and the read path checks `enabled()` before touching the clock, so normal
+//! requests pay one thread-local lookup per batch, not per row.
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+/// Where a debug request spent its time, in microseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReadTimings {
+    /// Store lookups (RocksDB gets or row cache hits), without decoding.
+    pub lookup_us: u64,
+    /// Decoding rows into each requested column's Arrow builder.
+    pub gather_us: IndexMap<String, u64>,
+    /// Finishing the builders into a batch and fanning rows out to request
+    /// order.
+    pub assembly_us: u64,
+    /// Serialising the response. Absent where the API encodes while
+    /// streaming (Flight).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encode_us: Option<u64>,
+}
+
+#[derive(Clone, Copy)]
+pub enum Phase {
+    Assembly,
+    Encode,
+}
+
+#[derive(Default)]
+struct Collector {
+    lookup: Duration,
+    gather: IndexMap<String, Duration>,
+    assembly: Duration,
+    encode: Option<Duration>,
+}
+
+impl Collector {
+    fn decode_total(&self) -> Duration {
+        self.gather.values().sum::<Duration>() + self.assembly
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Collector>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, collecting a breakdown of its reads on this thread when
+/// `enabled`.
+pub fn profile<T>(enabled: bool, f: impl FnOnce() -> T) -> (T, Option<ReadTimings>) {
+    if !enabled {
+        return (f(), None);
+    }
+    CURRENT.with(|c| *c.borrow_mut() = Some(Collector::default()));
+    let result = f();
+    let collector = CURRENT.with(|c| c.borrow_mut().take()).unwrap_or_default();
+    let micros = |d: Duration| d.as_micros() as u64;
+    let timings = ReadTimings {
+        lookup_us: micros(collector.lookup),
+        gather_us: collector
+            .gather
+            .into_iter()
+            .map(|(column, d)| (column, micros(d)))
+            .collect(),
+        assembly_us: micros(collector.assembly),
+        encode_us: collector.encode.map(micros),
+    };
+    (result, Some(timings))
+}
+
+pub fn enabled() -> bool {
+    CURRENT.with(|c| c.borrow().is_some())
+}
+
+fn with_collector(f: impl FnOnce(&mut Collector)) {
+    CURRENT.with(|c| {
+        if let Some(collector) = c.borrow_mut().as_mut() {
+            f(collector);
+        }
+    });
+}
+
+/// Times `f` as `phase` when profiling.
+pub fn timed<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    if !enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    with_collector(|c| match phase {
+        Phase::Assembly => c.assembly += elapsed,
+        Phase::Encode => *c.encode.get_or_insert_default() += elapsed,
+    });
+    result
+}
+
+pub fn add_gather(column: &str, elapsed: Duration) {
+    with_collector(|c| *c.gather.entry(column.to_string()).or_default() += elapsed);
+}
+
+/// Times a `Store::read` call. Stores interleave lookups with decoding, so
+/// the lookup share is the call's wall time minus the gather and assembly
+/// time `ReadBatchBuilder` recorded meanwhile.
+pub struct Lookup {
+    start: Instant,
+    decoded: Duration,
+}
+
+impl Lookup {
+    pub fn start() -> Option<Self> {
+        let mut decoded = None;
+        with_collector(|c| decoded = Some(c.decode_total()));
+        decoded.map(|decoded| Self {
+            start: Instant::now(),
+            decoded,
+        })
+    }
+
+    pub fn finish(self) {
+        let elapsed = self.start.elapsed();
+        with_collector(|c| {
+            let decoded = c.decode_total().saturating_sub(self.decoded);
+            c.lookup += elapsed.saturating_sub(decoded);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_only_inside_enabled_profile() {
+        let (_, timings) = profile(false, || add_gather("x", Duration::from_millis(1)));
+        assert!(timings.is_none());
+        assert!(!enabled());
+
+        let (_, timings) = profile(true, || {
+            let lookup = Lookup::start().unwrap();
+            add_gather("x", Duration::from_millis(2));
+            add_gather("x", Duration::from_millis(3));
+            lookup.finish();
+            timed(Phase::Encode, || ());
+        });
+        let timings = timings.unwrap();
+        assert_eq!(timings.gather_us["x"], 5000);
+        assert_eq!(timings.lookup_us, 0);
+        assert!(timings.encode_us.is_some());
+        assert!(!enabled());
+    }
+}
@@ -1,6 +1,9 @@
 pub mod api;
 pub mod conf;
 pub mod core;
+pub mod embedded;
 pub mod io;
 pub mod service;
 pub mod util;
+
+pub use embedded::Murr;
@@ -15,7 +15,7 @@ use murr::util::logo::ASCII_LOGO;
 
 use crate::api::{MurrFlightService, MurrHttpService};
 use crate::conf::{BackendConfig, Config};
-use crate::core::{CliArgs, setup_logging};
+use crate::core::{CliArgs, Command, setup_logging};
 use crate::io::store::rocksdb::RocksDBStore;
 use crate::service::MurrService;
 use log::info;
@@ -24,7 +24,27 @@ use log::info;
 async fn main() {
     setup_logging();
     let args = CliArgs::parse();
+    if let Some(Command::ValidateConfig { file }) = &args.command {
+        let check = CliArgs {
+            config: Some(file.clone()),
+            overrides: args.overrides.clone(),
+            read_only: args.read_only,
+            ..CliArgs::default()
+        };
+        match Config::from_args(&check) {
+            Ok(_) => println!("{file}: OK"),
+            Err(e) => {
+                eprintln!("{file}: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
     let config = Config::from_args(&args).expect("failed to load config");
+    if args.print_config {
+        print!("{}", config.to_yaml().expect("failed to print config"));
+        return;
+    }
 
     info!("{ASCII_LOGO}");
     let profile = if cfg!(debug_assertions) {
@@ -39,6 +59,9 @@ async fn main() {
         config.server.http.max_payload_size >> 20
     );
     info!("gRPC listen: {}", config.server.grpc.addr());
+    if let Some(admin) = &config.server.admin {
+        info!("Admin HTTP listen: {}", admin.addr());
+    }
     info!("Storage path: {}", config.storage.path.display());
     match &config.storage.backend {
         BackendConfig::Mmap(p) => info!(
@@ -61,6 +84,8 @@ async fn main() {
         RocksDBStore::open_from_config(&config.storage).expect("failed to open store"),
     ));
     let service = Arc::new(MurrService::new(store, config).expect("failed to load tables"));
+    service.start_background_load();
+    service.start_maintenance();
     info!("Service initialized, starting listeners");
 
     let http = MurrHttpService::new(service.clone());
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+
+use arrow::record_batch::RecordBatch;
+
+use crate::core::MurrError;
+
+type ReadKey = (String, Vec<String>, Vec<String>);
+type ReadResult = Result<RecordBatch, MurrError>;
+
+#[derive(Default)]
+struct Call {
+    result: Mutex<Option<ReadResult>>,
+    ready: Condvar,
+}
+
+/// Single-flight for reads: while a `(table, keys, columns)` read is running,
+/// identical reads wait for it and share its result instead of hitting the
+/// store again. `RecordBatch` clones only bump array refcounts.
+#[derive(Default)]
+pub struct Coalescer {
+    in_flight: Mutex<HashMap<ReadKey, Arc<Call>>>,
+    coalesced: AtomicU64,
+}
+
+/// Publishes the leader's result and retires the call, also when the read
+/// panics, so followers never wait forever.
+struct Leader<'a> {
+    coalescer: &'a Coalescer,
+    key: Option<ReadKey>,
+    call: Arc<Call>,
+}
+
+impl Leader<'_> {
+    fn finish(mut self, result: &ReadResult) {
+        *self
+            .call
+            .result
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(result.clone());
+        self.retire();
+    }
+
+    fn retire(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.coalescer
+                .in_flight
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&key);
+            self.call.ready.notify_all();
+        }
+    }
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        if self.key.is_some() {
+            let mut result = self
+                .call
+                .result
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if result.is_none() {
+                *result = Some(Err(MurrError::TableError(
+                    "coalesced read failed".to_string(),
+                )));
+            }
+        }
+        self.retire();
+    }
+}
+
+impl Coalescer {
+    pub fn read(
+        &self,
+        table: &str,
+        keys: &[&str],
+        columns: &[&str],
+        read: impl FnOnce() -> ReadResult,
+    ) -> ReadResult {
+        let key: ReadKey = (
+            table.to_string(),
+            keys.iter().map(|k| k.to_string()).collect(),
+            columns.iter().map(|c| c.to_string()).collect(),
+        );
+        let (call, leader) = {
+            let mut in_flight = self
+                .in_flight
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            match in_flight.get(&key) {
+                Some(call) => (call.clone(), false),
+                None => {
+                    let call = Arc::new(Call::default());
+                    in_flight.insert(key.clone(), call.clone());
+                    (call, true)
+                }
+            }
+        };
+
+        if !leader {
+            self.coalesced.fetch_add(1, Ordering::Relaxed);
+            let mut result = call.result.lock().unwrap_or_else(PoisonError::into_inner);
+            while result.is_none() {
+                result = call
+                    .ready
+                    .wait(result)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+            return result.clone().expect("checked above");
+        }
+
+        let leader = Leader {
+            coalescer: self,
+            key: Some(key),
+            call,
+        };
+        let result = read();
+        leader.finish(&result);
+        result
+    }
+
+    /// Reads answered from another request's in-flight result since startup.
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn identical_concurrent_reads_share_one_call() {
+        let coalescer = Coalescer::default();
+        let calls = AtomicUsize::new(0);
+        let barrier = Barrier::new(4);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    barrier.wait();
+                    coalescer
+                        .read("t", &["a"], &["x"], || {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            thread::sleep(Duration::from_millis(100));
+                            Ok(RecordBatch::new_empty(Arc::new(
+                                arrow::datatypes::Schema::empty(),
+                            )))
+                        })
+                        .unwrap();
+                });
+            }
+        });
+        let calls = calls.load(Ordering::SeqCst);
+        assert!(calls < 4);
+        assert_eq!(calls as u64 + coalescer.coalesced(), 4);
+        assert!(coalescer.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn errors_are_shared_and_not_cached() {
+        let coalescer = Coalescer::default();
+        let err = coalescer.read("t", &["a"], &[], || {
+            Err(MurrError::TableNotFound("t".to_string()))
+        });
+        assert!(matches!(err, Err(MurrError::TableNotFound(_))));
+        let ok = coalescer.read("t", &["a"], &[], || {
+            Ok(RecordBatch::new_empty(Arc::new(
+                arrow::datatypes::Schema::empty(),
+            )))
+        });
+        assert!(ok.is_ok());
+    }
+}
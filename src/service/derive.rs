@@ -0,0 +1,310 @@
+//! Derived tables: each column is an expression over the columns of a source
+//! table, evaluated per key by `MurrService::refresh`. Expressions cover
+//! renames (`other_name`), arithmetic (`clicks / views * 100`) and, through
+//! the cast to the column's dtype, type changes.
+
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Array, RecordBatch};
+use arrow::compute::cast;
+use arrow::compute::kernels::numeric;
+use arrow::datatypes::{DataType, Field, Schema};
+
+use crate::core::{Derivation, MurrError, TableSchema, UPDATED_AT_COLUMN};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Column(String),
+    Number(f64),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Expr {
+    fn parse(text: &str) -> Result<Self, MurrError> {
+        let mut parser = Parser { text, pos: 0 };
+        let expr = parser.sum()?;
+        parser.skip_space();
+        if parser.pos < text.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(expr)
+    }
+
+    fn columns<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Column(name) => out.push(name),
+            Expr::Number(_) => {}
+            Expr::Neg(inner) => inner.columns(out),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.columns(out);
+                rhs.columns(out);
+            }
+        }
+    }
+
+    /// A bare column keeps its type, so renames also work for non-numeric
+    /// columns. Arithmetic runs on float64.
+    fn eval(&self, inputs: &RecordBatch) -> Result<ArrayRef, MurrError> {
+        match self {
+            Expr::Column(name) => inputs
+                .column_by_name(name)
+                .cloned()
+                .ok_or_else(|| MurrError::TableError(format!("unknown column '{name}'"))),
+            _ => self.eval_f64(inputs),
+        }
+    }
+
+    fn eval_f64(&self, inputs: &RecordBatch) -> Result<ArrayRef, MurrError> {
+        Ok(match self {
+            Expr::Column(_) => cast(&self.eval(inputs)?, &DataType::Float64)?,
+            Expr::Number(n) => Arc::new(Float64Array::from(vec![*n; inputs.num_rows()])),
+            Expr::Neg(inner) => numeric::neg(&inner.eval_f64(inputs)?)?,
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval_f64(inputs)?, rhs.eval_f64(inputs)?);
+                match op {
+                    Op::Add => numeric::add(&lhs, &rhs)?,
+                    Op::Sub => numeric::sub(&lhs, &rhs)?,
+                    Op::Mul => numeric::mul(&lhs, &rhs)?,
+                    Op::Div => numeric::div(&lhs, &rhs)?,
+                }
+            }
+        })
+    }
+}
+
+/// Recursive descent over `sum := product (('+'|'-') product)*`,
+/// `product := unary (('*'|'/') unary)*`,
+/// `unary := '-' unary | number | column | '(' sum ')'`.
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn sum(&mut self) -> Result<Expr, MurrError> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.eat_op(&[('+', Op::Add), ('-', Op::Sub)]) {
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, MurrError> {
+        let mut expr = self.unary()?;
+        while let Some(op) = self.eat_op(&[('*', Op::Mul), ('/', Op::Div)]) {
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, MurrError> {
+        self.skip_space();
+        let rest = &self.text[self.pos..];
+        let Some(c) = rest.chars().next() else {
+            return Err(self.error("expected a value"));
+        };
+        if c == '-' {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        if c == '(' {
+            self.pos += 1;
+            let expr = self.sum()?;
+            self.skip_space();
+            if !self.text[self.pos..].starts_with(')') {
+                return Err(self.error("expected ')'"));
+            }
+            self.pos += 1;
+            return Ok(expr);
+        }
+        if c.is_ascii_digit() || c == '.' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let number = rest[..len]
+                .parse()
+                .map_err(|_| self.error("invalid number"))?;
+            self.pos += len;
+            return Ok(Expr::Number(number));
+        }
+        if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            self.pos += len;
+            return Ok(Expr::Column(rest[..len].to_string()));
+        }
+        Err(self.error("expected a number, column or '('"))
+    }
+
+    fn eat_op(&mut self, ops: &[(char, Op)]) -> Option<Op> {
+        self.skip_space();
+        let c = self.text[self.pos..].chars().next()?;
+        let (_, op) = ops.iter().find(|(symbol, _)| *symbol == c)?;
+        self.pos += 1;
+        Some(*op)
+    }
+
+    fn skip_space(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, what: &str) -> MurrError {
+        MurrError::TableError(format!(
+            "expression '{}': {what} at offset {}",
+            self.text, self.pos
+        ))
+    }
+}
+
+/// A derived table's expressions, parsed and checked against the source.
+pub(crate) struct Plan {
+    columns: Vec<(Field, Expr)>,
+    /// Source columns the expressions read, without duplicates.
+    inputs: Vec<String>,
+}
+
+impl Plan {
+    pub(crate) fn new(
+        table: &TableSchema,
+        derivation: &Derivation,
+        source: &TableSchema,
+    ) -> Result<Self, MurrError> {
+        if derivation.refresh_secs == Some(0) {
+            return Err(MurrError::TableError(
+                "derived refresh_secs must be at least 1".to_string(),
+            ));
+        }
+        // Source keys are written as the derived table's keys.
+        let key_dtype = |schema: &TableSchema| schema.columns.get(&schema.key).map(|c| c.dtype);
+        if key_dtype(table) != key_dtype(source) {
+            return Err(MurrError::TableError(format!(
+                "key column '{}' does not have the source table's key dtype",
+                table.key
+            )));
+        }
+        if let Some(name) = derivation
+            .columns
+            .keys()
+            .find(|name| !table.columns.contains_key(*name) || **name == table.key)
+        {
+            return Err(MurrError::TableError(format!(
+                "derived column '{name}' is not a value column of the table"
+            )));
+        }
+        let mut columns = Vec::new();
+        for (name, column) in &table.columns {
+            // Stamped by the write itself.
+            if *name == table.key || name == UPDATED_AT_COLUMN {
+                continue;
+            }
+            let expr = match derivation.columns.get(name) {
+                Some(text) => Expr::parse(text)?,
+                None => Expr::Column(name.clone()),
+            };
+            let field = Field::new(name, column.dtype.codec().arrow_dtype(), true);
+            columns.push((field, expr));
+        }
+        let mut inputs: Vec<&str> = Vec::new();
+        for (_, expr) in &columns {
+            expr.columns(&mut inputs);
+        }
+        let mut seen = std::collections::HashSet::new();
+        inputs.retain(|name| seen.insert(*name));
+        if let Some(name) = inputs
+            .iter()
+            .find(|name| **name == source.key || !source.columns.contains_key(**name))
+        {
+            return Err(MurrError::TableError(format!(
+                "source table has no value column '{name}'"
+            )));
+        }
+        Ok(Self {
+            columns,
+            inputs: inputs.into_iter().map(str::to_string).collect(),
+        })
+    }
+
+    pub(crate) fn inputs(&self) -> Vec<&str> {
+        self.inputs.iter().map(String::as_str).collect()
+    }
+
+    /// The derived rows for `keys`, the derived table's key column (see
+    /// `Table::key_column`), given their source `inputs` columns.
+    pub(crate) fn apply(
+        &self,
+        (key_field, keys): (Field, ArrayRef),
+        inputs: &RecordBatch,
+    ) -> Result<RecordBatch, MurrError> {
+        let mut fields = vec![key_field];
+        let mut arrays: Vec<ArrayRef> = vec![keys];
+        for (field, expr) in &self.columns {
+            arrays.push(cast(&expr.eval(inputs)?, field.data_type())?);
+            fields.push(field.clone());
+        }
+        Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, Float32Array, Int64Array};
+
+    #[test]
+    fn parses_precedence_and_parentheses() {
+        use Expr::*;
+        let col = |name: &str| Box::new(Column(name.to_string()));
+        assert_eq!(
+            Expr::parse("a + b * 2").unwrap(),
+            Binary(
+                Op::Add,
+                col("a"),
+                Box::new(Binary(Op::Mul, col("b"), Box::new(Number(2.0))))
+            )
+        );
+        assert_eq!(
+            Expr::parse(" -(a - b) ").unwrap(),
+            Neg(Box::new(Binary(Op::Sub, col("a"), col("b"))))
+        );
+        for bad in ["", "a +", "(a", "a b", "1.2.3", "a % b"] {
+            assert!(Expr::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn evaluates_over_float64_and_keeps_bare_columns() {
+        let inputs = RecordBatch::try_from_iter([
+            (
+                "clicks",
+                Arc::new(Int64Array::from(vec![Some(3), None])) as ArrayRef,
+            ),
+            (
+                "views",
+                Arc::new(Float32Array::from(vec![4.0, 2.0])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let ctr = Expr::parse("clicks / views * 100")
+            .unwrap()
+            .eval(&inputs)
+            .unwrap();
+        let ctr = ctr.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(ctr.value(0), 75.0);
+        assert!(ctr.is_null(1));
+
+        let renamed = Expr::parse("clicks").unwrap().eval(&inputs).unwrap();
+        assert_eq!(renamed.data_type(), &DataType::Int64);
+        assert!(Expr::parse("nope").unwrap().eval(&inputs).is_err());
+    }
+}
@@ -0,0 +1,302 @@
+//! Table lifecycle events, so downstream systems can react to data refreshes
+//! without polling stats. The service hands every event to one `EventSink`;
+//! `server.events` configures the default one, which logs each event and can
+//! also POST it to a webhook.
+
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::body::Bytes;
+use axum::http::{Request, header};
+use http_body_util::Full;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::conf::{EventKind, EventsConfig};
+use crate::core::MurrError;
+
+/// Serialized as `{"event": "<name>", "table": ..., "at_ms": ..., ...}`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A table from the manifest was opened, at startup or on first access.
+    TableLoaded {
+        table: String,
+        estimated_keys: u64,
+        took_ms: u64,
+    },
+    /// A table from the manifest could not be opened. Tables retried on
+    /// access report this once, until they open again.
+    TableLoadFailed {
+        table: String,
+        error: String,
+    },
+    TableCreated {
+        table: String,
+    },
+    TableDropped {
+        table: String,
+    },
+    /// Every row of the table was removed; the schema stays.
+    TableTruncated {
+        table: String,
+    },
+    /// Columns were added or dropped and every row rewritten.
+    TableAltered {
+        table: String,
+        version: u32,
+    },
+    /// The table moved from `from` to its current name.
+    TableRenamed {
+        table: String,
+        from: String,
+    },
+    /// `alias` now resolves to `table`, replacing `previous` if it pointed
+    /// elsewhere before.
+    AliasSet {
+        table: String,
+        alias: String,
+        previous: Option<String>,
+    },
+    /// `alias` no longer resolves to `table`.
+    AliasRemoved {
+        table: String,
+        alias: String,
+    },
+    /// A write batch is durable and visible to reads.
+    WriteCommitted {
+        table: String,
+        rows: usize,
+    },
+    /// Rows were deleted; `keys` counts the ones that existed.
+    KeysDeleted {
+        table: String,
+        keys: usize,
+    },
+    /// An explicit rebuild (full compaction) finished.
+    RebuildFinished {
+        table: String,
+        took_ms: u64,
+    },
+    /// A key was purged from the table and the table compacted, so its
+    /// data is gone from disk. The key itself is not part of the event.
+    KeyPurged {
+        table: String,
+        entity: String,
+        took_ms: u64,
+    },
+}
+
+impl Event {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Event::TableLoaded { .. } => EventKind::TableLoaded,
+            Event::TableLoadFailed { .. } => EventKind::TableLoadFailed,
+            Event::TableCreated { .. } => EventKind::TableCreated,
+            Event::TableDropped { .. } => EventKind::TableDropped,
+            Event::TableTruncated { .. } => EventKind::TableTruncated,
+            Event::TableAltered { .. } => EventKind::TableAltered,
+            Event::TableRenamed { .. } => EventKind::TableRenamed,
+            Event::AliasSet { .. } => EventKind::AliasSet,
+            Event::AliasRemoved { .. } => EventKind::AliasRemoved,
+            Event::WriteCommitted { .. } => EventKind::WriteCommitted,
+            Event::KeysDeleted { .. } => EventKind::KeysDeleted,
+            Event::RebuildFinished { .. } => EventKind::RebuildFinished,
+            Event::KeyPurged { .. } => EventKind::KeyPurged,
+        }
+    }
+
+    pub fn table(&self) -> &str {
+        match self {
+            Event::TableLoaded { table, .. }
+            | Event::TableLoadFailed { table, .. }
+            | Event::TableCreated { table }
+            | Event::TableDropped { table }
+            | Event::TableTruncated { table }
+            | Event::TableAltered { table, .. }
+            | Event::TableRenamed { table, .. }
+            | Event::AliasSet { table, .. }
+            | Event::AliasRemoved { table, .. }
+            | Event::WriteCommitted { table, .. }
+            | Event::KeysDeleted { table, .. }
+            | Event::RebuildFinished { table, .. }
+            | Event::KeyPurged { table, .. } => table,
+        }
+    }
+}
+
+/// Receives events on the thread that produced them, so implementations must
+/// return quickly.
+pub trait EventSink: Send + Sync {
+    fn send(&self, event: &Event);
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    #[serde(flatten)]
+    event: &'a Event,
+    at_ms: u64,
+}
+
+/// The `server.events` sink: logs every event and, with `webhook` set, queues
+/// it for a background thread that POSTs it as JSON. Events are dropped with
+/// a warning when the queue is full, so a slow endpoint never stalls writes.
+pub struct EventLog {
+    kinds: Vec<EventKind>,
+    webhook: Option<SyncSender<String>>,
+}
+
+impl EventLog {
+    pub fn new(config: &EventsConfig) -> Result<Self, MurrError> {
+        let webhook = match &config.webhook {
+            Some(url) => {
+                let (tx, rx) = mpsc::sync_channel(config.queue_size);
+                let url = url.clone();
+                let timeout = Duration::from_millis(config.timeout_ms);
+                std::thread::Builder::new()
+                    .name("murr-events".to_string())
+                    .spawn(move || post_events(&url, timeout, rx))
+                    .map_err(|e| MurrError::ConfigParsingError(format!("event webhook: {e}")))?;
+                Some(tx)
+            }
+            None => None,
+        };
+        Ok(Self {
+            kinds: config.kinds.clone(),
+            webhook,
+        })
+    }
+}
+
+impl EventSink for EventLog {
+    fn send(&self, event: &Event) {
+        if !self.kinds.is_empty() && !self.kinds.contains(&event.kind()) {
+            return;
+        }
+        let at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let json = match serde_json::to_string(&Envelope { event, at_ms }) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("cannot serialize event {event:?}: {e}");
+                return;
+            }
+        };
+        info!("event: {json}");
+        let Some(webhook) = &self.webhook else {
+            return;
+        };
+        match webhook.try_send(json) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                warn!("event queue full, dropping event for '{}'", event.table())
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                warn!(
+                    "event webhook thread is gone, dropping event for '{}'",
+                    event.table()
+                )
+            }
+        }
+    }
+}
+
+/// The service's end: the sink, if any, plus the tables whose last open
+/// failed, so a table retried on every access is reported once per outage.
+pub(crate) struct Events {
+    sink: Option<Arc<dyn EventSink>>,
+    failing: Mutex<HashSet<String>>,
+}
+
+impl Events {
+    pub(crate) fn new(config: Option<&EventsConfig>) -> Result<Self, MurrError> {
+        let sink: Option<Arc<dyn EventSink>> = match config {
+            Some(config) => Some(Arc::new(EventLog::new(config)?)),
+            None => None,
+        };
+        Ok(Self {
+            sink,
+            failing: Mutex::default(),
+        })
+    }
+
+    pub(crate) fn set_sink(&mut self, sink: Arc<dyn EventSink>) {
+        self.sink = Some(sink);
+    }
+
+    /// Builds the event only when someone is listening.
+    pub(crate) fn emit(&self, event: impl FnOnce() -> Event) {
+        if let Some(sink) = &self.sink {
+            sink.send(&event());
+        }
+    }
+
+    pub(crate) fn table_loaded(&self, table: &str, estimated_keys: u64, took_ms: u64) {
+        self.failing
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(table);
+        self.emit(|| Event::TableLoaded {
+            table: table.to_string(),
+            estimated_keys,
+            took_ms,
+        });
+    }
+
+    pub(crate) fn table_load_failed(&self, table: &str, error: &MurrError) {
+        let first = self
+            .failing
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(table.to_string());
+        if first {
+            self.emit(|| Event::TableLoadFailed {
+                table: table.to_string(),
+                error: error.to_string(),
+            });
+        }
+    }
+}
+
+/// Webhook thread: POSTs queued events one at a time, in order, until the
+/// sink is dropped. Failed deliveries are logged and not retried.
+fn post_events(url: &str, timeout: Duration, rx: Receiver<String>) {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            warn!("cannot start event webhook runtime, events are only logged: {e}");
+            return;
+        }
+    };
+    // The client spawns its connection tasks on the runtime it is used from.
+    let _context = runtime.enter();
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+    while let Ok(json) = rx.recv() {
+        let request = match Request::post(url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Full::new(Bytes::from(json)))
+        {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("invalid event webhook URL '{url}': {e}");
+                return;
+            }
+        };
+        let sent = runtime.block_on(tokio::time::timeout(timeout, client.request(request)));
+        match sent {
+            Ok(Ok(response)) if response.status().is_success() => {}
+            Ok(Ok(response)) => warn!("event webhook returned {}", response.status()),
+            Ok(Err(e)) => warn!("event webhook failed: {e}"),
+            Err(_) => warn!("event webhook timed out after {} ms", timeout.as_millis()),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+use log::debug;
+
+use crate::conf::RateLimitConfig;
+use crate::core::MurrError;
+
+/// Buckets kept before idle (fully refilled) ones are pruned.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket per client id: `burst` requests up front, refilled at
+/// `requests_per_second`.
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    throttled: AtomicU64,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            rate: config.requests_per_second,
+            burst: config.burst as f64,
+            buckets: Mutex::new(HashMap::new()),
+            throttled: AtomicU64::new(0),
+        }
+    }
+
+    pub fn check(&self, client: &str) -> Result<(), MurrError> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(client) {
+            let (rate, burst) = (self.rate, self.burst);
+            buckets.retain(|_, b| {
+                b.tokens + now.duration_since(b.updated).as_secs_f64() * rate < burst
+            });
+        }
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let total = self.throttled.fetch_add(1, Ordering::Relaxed) + 1;
+        debug!("throttled request ({total} throttled since start)");
+        Err(MurrError::RateLimited(format!(
+            "more than {} requests/s",
+            self.rate
+        )))
+    }
+
+    pub fn throttled(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_each_client_separately() {
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            requests_per_second: 0.001,
+            burst: 2,
+        });
+        assert!(limiter.check("a").is_ok());
+        assert!(limiter.check("a").is_ok());
+        assert!(matches!(limiter.check("a"), Err(MurrError::RateLimited(_))));
+        assert!(limiter.check("b").is_ok());
+        assert_eq!(limiter.throttled(), 1);
+    }
+}
@@ -1,258 +1,2925 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::{Arc, PoisonError, RwLock};
-use std::time::Instant;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant, SystemTime};
 
-use arrow::record_batch::RecordBatch;
+use arrow::array::{Array, ArrayRef, StringArray};
+use arrow::datatypes::{Field, FieldRef, Schema};
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
 use log::{info, warn};
+use rayon::prelude::*;
+use serde::Serialize;
 
-use crate::conf::Config;
-use crate::core::{MurrError, TableSchema};
-use crate::io::store::Store;
-use crate::io::table::Table;
+use crate::conf::{Config, TimeOfDay};
+use crate::core::{
+    Clock, DTypeName, Entity, MurrError, SchemaChange, SchemaDiff, SystemClock, TableSchema,
+};
+use crate::io::store::{Store, TableStats};
+use crate::io::table::{ReadEstimate, Table};
+
+mod coalesce;
+mod derive;
+mod events;
+mod limiter;
+mod priority;
+mod progress;
+mod slo;
+
+pub use coalesce::Coalescer;
+use derive::Plan;
+use events::Events;
+pub use events::{Event, EventLog, EventSink};
+pub use limiter::RateLimiter;
+pub use priority::{PRIORITY_HEADER, Priority};
+pub use progress::LoadProgress;
+use progress::LoadTracker;
+pub use slo::{TableSlo, counter_text, prometheus_text};
+
+const MAX_TABLE_NAME_LEN: usize = 255;
+
+/// How often the maintenance thread checks `server.compaction_window` and
+/// derived table refreshes.
+const MAINTENANCE_TICK: Duration = Duration::from_secs(60);
+
+/// Source keys recomputed per write when refreshing a derived table.
+const REFRESH_PAGE: usize = 4096;
+
+/// Schema metadata key listing, as a JSON array, the requested columns a
+/// lenient read skipped.
+pub const UNKNOWN_COLUMNS_METADATA: &str = "murr.unknown_columns";
+
+/// A registered entity and the tables keyed by it, served by
+/// `GET /api/v1/entity`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntityInfo {
+    #[serde(flatten)]
+    pub entity: Entity,
+    /// Sorted by name.
+    pub tables: Vec<String>,
+}
+
+/// What `purge_key` did, for the caller's own records.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PurgeReport {
+    /// The entity's tables, sorted by name. All of them were compacted.
+    pub tables: Vec<String>,
+    /// How many of them had a row for the key.
+    pub found: usize,
+    pub took_ms: u64,
+}
+
+/// What `provision` found, so declarative callers can tell a no-op from a
+/// conflict.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Provisioned {
+    Created,
+    /// The table already exists with this schema.
+    Unchanged,
+    /// The table exists with another schema and was left as it is.
+    Differs(SchemaDiff),
+    /// The table was altered to match, with `allow_evolution`.
+    Evolved(SchemaDiff),
+}
 
 pub struct MurrService<S: Store> {
     tables: RwLock<HashMap<String, Table<S>>>,
+    /// Alias to table, as in the manifest. Data-plane calls resolve aliases,
+    /// admin calls take table names.
+    aliases: RwLock<HashMap<String, String>>,
+    /// Tables registered from the manifest but not opened yet (`server.lazy_load`).
+    pending: Mutex<HashMap<String, TableSchema>>,
+    /// Serialises on-demand opens so a table is opened once.
+    opening: Mutex<()>,
+    /// Set with `server.background_load`.
+    loader: Option<LoadTracker>,
     store: Arc<RwLock<S>>,
     config: Config,
+    batch_pool: rayon::ThreadPool,
+    limiter: Option<RateLimiter>,
+    /// Set with `server.coalesce_reads`.
+    coalescer: Option<Coalescer>,
+    in_flight: AtomicUsize,
+    clock: Arc<dyn Clock>,
+    /// Sink set with `server.events` or `with_event_sink`.
+    events: Events,
+}
+
+/// Counts a request as in flight until dropped.
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 impl<S: Store> MurrService<S> {
     pub fn new(store: Arc<RwLock<S>>, config: Config) -> Result<Self, MurrError> {
-        let snapshot: Vec<(String, TableSchema)> = {
+        let (snapshot, aliases): (Vec<(String, TableSchema)>, _) = {
             let s = store.read().unwrap_or_else(PoisonError::into_inner);
-            s.manifest()
+            let snapshot = s
+                .manifest()
                 .tables
                 .iter()
                 .map(|(k, v)| (k.clone(), v.clone()))
-                .collect()
+                .collect();
+            (snapshot, s.manifest().aliases.clone())
         };
         let total = snapshot.len();
         info!("Manifest has {} table(s)", total);
 
+        let events = Events::new(config.server.events.as_ref())?;
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+
         let load_start = Instant::now();
         let mut tables: HashMap<String, Table<S>> = HashMap::new();
-        for (name, schema) in snapshot {
-            let column_count = schema.columns.len();
-            match Table::open(store.clone(), name.clone(), schema) {
-                Ok(t) => {
-                    info!("loaded table '{}' ({} columns)", name, column_count);
-                    tables.insert(name, t);
+        let mut pending: HashMap<String, TableSchema> = HashMap::new();
+        if config.server.lazy_load || config.server.background_load {
+            pending.extend(snapshot);
+        } else {
+            let load_pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(config.server.load_threads)
+                .thread_name(|i| format!("murr-load-{i}"))
+                .build()
+                .map_err(|e| MurrError::ConfigParsingError(format!("load pool: {e}")))?;
+            let opened: Vec<(String, Result<Table<S>, MurrError>)> = load_pool.install(|| {
+                snapshot
+                    .into_par_iter()
+                    .map(|(name, schema)| {
+                        let table = open_table(&store, &config, &events, &clock, &name, schema);
+                        (name, table)
+                    })
+                    .collect()
+            });
+            for (name, table) in opened {
+                match table {
+                    Ok(t) => {
+                        tables.insert(name, t);
+                    }
+                    Err(e) => warn!("skipping table '{}': {}", name, e),
                 }
-                Err(e) => warn!("skipping table '{}': {}", name, e),
             }
         }
         info!(
-            "Service ready: {}/{} tables loaded in {} ms",
+            "Service ready: {}/{} tables loaded, {} deferred, in {} ms",
             tables.len(),
             total,
+            pending.len(),
             load_start.elapsed().as_millis()
         );
 
+        let batch_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(config.server.batch_threads)
+            .thread_name(|i| format!("murr-batch-{i}"))
+            .build()
+            .map_err(|e| MurrError::ConfigParsingError(format!("batch pool: {e}")))?;
+
+        let limiter = config.server.rate_limit.as_ref().map(RateLimiter::new);
+        let coalescer = config.server.coalesce_reads.then(Coalescer::default);
+        let loader = config
+            .server
+            .background_load
+            .then(|| LoadTracker::new(pending.len()));
+
         Ok(Self {
             tables: RwLock::new(tables),
+            aliases: RwLock::new(aliases),
+            pending: Mutex::new(pending),
+            opening: Mutex::new(()),
+            loader,
             store,
             config,
+            batch_pool,
+            limiter,
+            coalescer,
+            in_flight: AtomicUsize::new(0),
+            clock,
+            events,
         })
     }
 
+    /// Replaces the wall clock, e.g. with a `ManualClock` in tests. Tables
+    /// opened while constructing the service switch to it too.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        let tables = self
+            .tables
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        *tables = std::mem::take(tables)
+            .into_iter()
+            .map(|(name, table)| (name, table.with_clock(clock.clone())))
+            .collect();
+        self.clock = clock;
+        self
+    }
+
+    /// Sends table events to `sink` instead of the `server.events` one. Tables
+    /// opened while constructing the service have already been reported.
+    pub fn with_event_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.events.set_sink(sink);
+        self
+    }
+
     pub fn config(&self) -> &Config {
         &self.config
     }
 
-    pub fn create(&self, table_name: &str, schema: TableSchema) -> Result<(), MurrError> {
-        let mut tables = self.tables.write().unwrap_or_else(PoisonError::into_inner);
-        if tables.contains_key(table_name) {
-            return Err(MurrError::TableAlreadyExists(table_name.to_string()));
-        }
-        let table = Table::create(self.store.clone(), table_name, schema)?;
-        tables.insert(table_name.to_string(), table);
-        Ok(())
+    /// Takes one token from `client`'s bucket when `server.rate_limit` is set.
+    pub fn admit(&self, client: &str) -> Result<(), MurrError> {
+        match &self.limiter {
+            Some(limiter) => limiter.check(client),
+            None => Ok(()),
+        }
+    }
+
+    /// Requests rejected by the rate limiter since startup.
+    pub fn throttled_requests(&self) -> u64 {
+        self.limiter.as_ref().map_or(0, RateLimiter::throttled)
+    }
+
+    /// Reads served from an identical in-flight read since startup.
+    pub fn coalesced_reads(&self) -> u64 {
+        self.coalescer.as_ref().map_or(0, Coalescer::coalesced)
+    }
+
+    /// Runs `f` on the calling thread for online requests. Batch requests wait
+    /// for one of the `server.batch_threads` workers, which caps how much of
+    /// the machine scans and bulk exports can take from online fetches.
+    ///
+    /// With `server.load_shedding` set, requests are rejected up front with
+    /// `MurrError::Overloaded` once too many are in flight or queued; batch
+    /// requests hit their lower threshold first.
+    pub fn run<T, E>(
+        &self,
+        priority: Priority,
+        f: impl FnOnce() -> Result<T, E> + Send,
+    ) -> Result<T, E>
+    where
+        T: Send,
+        E: From<MurrError> + Send,
+    {
+        let _in_flight = self.enter(priority)?;
+        match priority {
+            Priority::Online => f(),
+            Priority::Batch => self.batch_pool.install(f),
+        }
+    }
+
+    fn enter(&self, priority: Priority) -> Result<InFlight<'_>, MurrError> {
+        let current = self.in_flight.fetch_add(1, Ordering::Relaxed);
+        let guard = InFlight(&self.in_flight);
+        if let Some(shed) = &self.config.server.load_shedding {
+            let limit = match priority {
+                Priority::Online => shed.max_in_flight,
+                Priority::Batch => shed.max_batch_in_flight,
+            };
+            if current >= limit {
+                return Err(MurrError::Overloaded(format!(
+                    "{current} requests in flight, shedding {priority:?} requests"
+                )));
+            }
+        }
+        Ok(guard)
+    }
+
+    /// Opens `table_name` now if it is still deferred by `server.lazy_load`,
+    /// so the first request doesn't pay for it. A no-op for open tables.
+    pub fn warmup(&self, table_name: &str) -> Result<(), MurrError> {
+        let tables = self.tables(&[table_name])?;
+        if !tables.contains_key(table_name) {
+            return Err(MurrError::TableNotFound(table_name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Read access to the open tables, first opening any of `names` still
+    /// deferred, together with the tables their key aliases point at. While
+    /// the background loader is running, deferred tables fail fast with
+    /// `MurrError::TableLoading` instead.
+    fn tables(
+        &self,
+        names: &[&str],
+    ) -> Result<RwLockReadGuard<'_, HashMap<String, Table<S>>>, MurrError> {
+        let deferred: Vec<String> = {
+            let pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+            names
+                .iter()
+                .filter(|name| pending.contains_key(**name))
+                .map(|name| name.to_string())
+                .collect()
+        };
+        if let Some(name) = deferred.first() {
+            match &self.loader {
+                Some(loader) if !loader.is_done() => {
+                    return Err(MurrError::TableLoading(format!(
+                        "{name} ({})",
+                        loader.describe()
+                    )));
+                }
+                _ => self.open_deferred_tables(deferred)?,
+            }
+        }
+        Ok(self.tables.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Opens deferred tables one at a time, following key aliases. Other
+    /// requests keep using already open tables meanwhile.
+    fn open_deferred_tables(&self, mut queue: Vec<String>) -> Result<(), MurrError> {
+        let _opening = self.opening.lock().unwrap_or_else(PoisonError::into_inner);
+        while let Some(name) = queue.pop() {
+            if let Some(alias) = self.open_deferred(&name)? {
+                queue.push(alias);
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves one table from `pending` into the registry, returning the table
+    /// its key alias points at. The table is visible in the registry before
+    /// it leaves `pending`, so lookups always find it in one of the two.
+    fn open_deferred(&self, name: &str) -> Result<Option<String>, MurrError> {
+        let schema = {
+            let pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+            match pending.get(name) {
+                Some(schema) => schema.clone(),
+                None => return Ok(None),
+            }
+        };
+        let alias = schema.key_alias.as_ref().map(|a| a.table.clone());
+        let table = open_table(
+            &self.store,
+            &self.config,
+            &self.events,
+            &self.clock,
+            name,
+            schema,
+        )?;
+        self.tables
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(name.to_string(), table);
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name);
+        Ok(alias)
+    }
+
+    /// Starts opening deferred tables on a background thread when
+    /// `server.background_load` is set, so the APIs can come up first. A
+    /// no-op otherwise.
+    pub fn start_background_load(self: &Arc<Self>) {
+        let Some(loader) = &self.loader else {
+            return;
+        };
+        if loader.is_done() {
+            return;
+        }
+        let service = self.clone();
+        let spawned = std::thread::Builder::new()
+            .name("murr-loader".to_string())
+            .spawn(move || service.load_deferred());
+        if let Err(e) = spawned {
+            warn!("cannot start background loader, tables load on first access: {e}");
+            loader.finish();
+        }
+    }
+
+    fn load_deferred(&self) {
+        let Some(loader) = &self.loader else {
+            return;
+        };
+        let names: Vec<String> = self
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect();
+        let load = || {
+            names.par_iter().for_each(|name| {
+                let result = self.open_deferred(name);
+                if let Err(e) = &result {
+                    warn!("skipping table '{}': {}", name, e);
+                }
+                loader.record(result.is_ok());
+            })
+        };
+        match rayon::ThreadPoolBuilder::new()
+            .num_threads(self.config.server.load_threads)
+            .thread_name(|i| format!("murr-load-{i}"))
+            .build()
+        {
+            Ok(pool) => pool.install(load),
+            Err(e) => {
+                warn!("load pool: {e}, using the global pool");
+                load()
+            }
+        }
+        loader.finish();
+        let progress = loader.progress();
+        info!(
+            "Background load finished: {}/{} tables open, {} failed",
+            progress.loaded, progress.total, progress.failed
+        );
+    }
+
+    /// Starts a thread that pauses background compaction outside
+    /// `server.compaction_window` and resumes it inside, and refreshes
+    /// derived tables whose `refresh_secs` has passed. The thread exits once
+    /// the service is dropped.
+    pub fn start_maintenance(self: &Arc<Self>) {
+        let service = Arc::downgrade(self);
+        let spawned = std::thread::Builder::new()
+            .name("murr-maintenance".to_string())
+            .spawn(move || {
+                let mut applied = None;
+                let mut refreshed = HashMap::new();
+                while let Some(service) = service.upgrade() {
+                    service.apply_compaction_window(&mut applied);
+                    service.refresh_due_tables(&mut refreshed);
+                    drop(service);
+                    std::thread::sleep(MAINTENANCE_TICK);
+                }
+            });
+        if let Err(e) = spawned {
+            warn!(
+                "cannot start maintenance thread, compaction is not windowed and derived tables only refresh on request: {e}"
+            );
+        }
+    }
+
+    /// One maintenance tick. `applied` is whether the window was open at the
+    /// last state pushed to the store. Tables created since inherit that
+    /// state from the store, so it is only pushed again when the window flips.
+    fn apply_compaction_window(&self, applied: &mut Option<bool>) {
+        let Some(window) = self.config.server.compaction_window else {
+            return;
+        };
+        let open = window.contains(TimeOfDay::at(self.clock.now()));
+        if *applied == Some(open) {
+            return;
+        }
+        let store = self.store.read().unwrap_or_else(PoisonError::into_inner);
+        match store.set_auto_compaction(open) {
+            Ok(()) => {
+                info!(
+                    "Compaction window {}",
+                    if open {
+                        "open, background compaction resumed"
+                    } else {
+                        "closed, background compaction paused"
+                    }
+                );
+                *applied = Some(open);
+            }
+            Err(e) => warn!("cannot apply compaction window: {e}"),
+        }
+    }
+
+    /// One maintenance tick for derived tables. `refreshed` holds when each
+    /// table was last refreshed by this thread; tables are refreshed on the
+    /// first tick, then every `refresh_secs`, rounded up to the tick.
+    fn refresh_due_tables(&self, refreshed: &mut HashMap<String, SystemTime>) {
+        if self.config.server.read_only {
+            return;
+        }
+        let now = self.clock.now();
+        let schemas = self.list_tables();
+        refreshed.retain(|name, _| schemas.contains_key(name));
+        for (name, schema) in schemas {
+            let Some(every) = schema.derived.and_then(|d| d.refresh_secs) else {
+                continue;
+            };
+            let due = refreshed.get(&name).is_none_or(|last| {
+                now.duration_since(*last)
+                    .is_ok_and(|age| age >= Duration::from_secs(every))
+            });
+            if !due {
+                continue;
+            }
+            // Failed refreshes wait for the next period too, rather than
+            // retrying and logging every tick.
+            refreshed.insert(name.clone(), now);
+            let start = Instant::now();
+            match self.run(Priority::Batch, || self.refresh(&name)) {
+                Ok(rows) => info!(
+                    "refreshed derived table '{name}': {rows} rows in {} ms",
+                    start.elapsed().as_millis()
+                ),
+                Err(e) => warn!("cannot refresh derived table '{name}': {e}"),
+            }
+        }
+    }
+
+    /// Background load progress. Always ready unless `server.background_load`
+    /// is set and tables are still opening.
+    pub fn load_progress(&self) -> LoadProgress {
+        match &self.loader {
+            Some(loader) => loader.progress(),
+            None => {
+                let deferred = self
+                    .pending
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .len();
+                let loaded = self
+                    .tables
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .len();
+                LoadProgress {
+                    ready: true,
+                    total: loaded + deferred,
+                    loaded,
+                    failed: 0,
+                    eta_ms: None,
+                }
+            }
+        }
+    }
+
+    pub fn create(&self, table_name: &str, schema: TableSchema) -> Result<(), MurrError> {
+        self.check_writable(|| format!("cannot create table '{table_name}'"))?;
+        validate_table_name(table_name)?;
+        if let Some(derivation) = &schema.derived {
+            let source = self.schema_of(&derivation.source).map_err(|e| match e {
+                MurrError::TableNotFound(name) => {
+                    MurrError::TableError(format!("source table '{name}' does not exist"))
+                }
+                e => e,
+            })?;
+            Plan::new(&schema, derivation, &source)?;
+        }
+        if let Some(name) = &schema.entity {
+            self.check_entity(name, &schema)?;
+        }
+        let aliases = self.aliases.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(target) = aliases.get(table_name) {
+            return Err(MurrError::TableError(format!(
+                "'{table_name}' is an alias of '{target}'"
+            )));
+        }
+        let pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut tables = self.tables.write().unwrap_or_else(PoisonError::into_inner);
+        if tables.contains_key(table_name) || pending.contains_key(table_name) {
+            return Err(MurrError::TableAlreadyExists(table_name.to_string()));
+        }
+        let table = with_row_cache(
+            Table::create(self.store.clone(), table_name, schema)?.with_clock(self.clock.clone()),
+            &self.config,
+        );
+        tables.insert(table_name.to_string(), table);
+        self.events.emit(|| Event::TableCreated {
+            table: table_name.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Creates `table_name` unless it exists, for provisioning pipelines that
+    /// apply the same declaration on every run. An existing table is compared
+    /// with `schema` instead, which needs no write, so it also works on a
+    /// read-only server. `allow_evolution` alters an existing table to match
+    /// when that only takes adding nullable columns and dropping columns;
+    /// other differences fail.
+    pub fn provision(
+        &self,
+        table_name: &str,
+        schema: TableSchema,
+        allow_evolution: bool,
+    ) -> Result<Provisioned, MurrError> {
+        let current = match self.schema_of(table_name) {
+            Ok(current) => current,
+            Err(MurrError::TableNotFound(_)) => match self.create(table_name, schema.clone()) {
+                Ok(()) => return Ok(Provisioned::Created),
+                // Lost a race with another create.
+                Err(MurrError::TableAlreadyExists(_)) => self.schema_of(table_name)?,
+                Err(e) => return Err(e),
+            },
+            Err(e) => return Err(e),
+        };
+        let diff = current.diff(&schema);
+        if diff.is_empty() {
+            return Ok(Provisioned::Unchanged);
+        }
+        if !allow_evolution {
+            return Ok(Provisioned::Differs(diff));
+        }
+        let fixed: Vec<&str> = diff
+            .changed
+            .iter()
+            .map(|change| change.column.as_str())
+            .chain(diff.settings.iter().map(String::as_str))
+            .collect();
+        if !fixed.is_empty() {
+            return Err(MurrError::TableError(format!(
+                "cannot evolve '{table_name}': changing {} is not supported, drop and recreate the table instead",
+                fixed.join(", ")
+            )));
+        }
+        let change = SchemaChange {
+            add_columns: diff
+                .added
+                .iter()
+                .map(|name| (name.clone(), schema.columns[name].clone()))
+                .collect(),
+            drop_columns: diff.removed.clone(),
+        };
+        self.alter_table(table_name, &change)?;
+        Ok(Provisioned::Evolved(diff))
+    }
+
+    /// Removes `table_name` with its rows and schema. Refused while another
+    /// table derives from it or resolves key aliases through it, and while
+    /// an alias points at it.
+    pub fn drop_table(&self, table_name: &str) -> Result<(), MurrError> {
+        self.check_writable(|| format!("cannot drop '{table_name}'"))?;
+        self.check_unused(table_name)?;
+        // Opens it if it is still deferred, so the registry has it below.
+        drop(self.tables(&[table_name])?);
+        let aliases = self.aliases.read().unwrap_or_else(PoisonError::into_inner);
+        let mut pointing: Vec<&str> = aliases
+            .iter()
+            .filter(|(_, target)| *target == table_name)
+            .map(|(alias, _)| alias.as_str())
+            .collect();
+        if !pointing.is_empty() {
+            pointing.sort();
+            return Err(MurrError::TableError(format!(
+                "table '{table_name}' is the target of alias {}, point it elsewhere first",
+                pointing.join(", ")
+            )));
+        }
+        let mut tables = self.tables.write().unwrap_or_else(PoisonError::into_inner);
+        if !tables.contains_key(table_name) {
+            return Err(MurrError::TableNotFound(table_name.to_string()));
+        }
+        self.store
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .drop_table(table_name)?;
+        tables.remove(table_name);
+        drop(tables);
+        drop(aliases);
+        info!("dropped table '{table_name}'");
+        self.events.emit(|| Event::TableDropped {
+            table: table_name.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Moves `table_name` to `new_name` without touching its rows, e.g. to
+    /// keep yesterday's build around under a dated name. Aliases pointing at
+    /// it follow it. Refused while another table derives from it or
+    /// resolves key aliases through it.
+    pub fn rename(&self, table_name: &str, new_name: &str) -> Result<(), MurrError> {
+        self.check_writable(|| format!("cannot rename '{table_name}'"))?;
+        validate_table_name(new_name)?;
+        self.check_unused(table_name)?;
+        // Opens it if it is still deferred, so the registry has it below.
+        drop(self.tables(&[table_name])?);
+        let mut aliases = self.aliases.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(target) = aliases.get(new_name) {
+            return Err(MurrError::TableError(format!(
+                "'{new_name}' is an alias of '{target}'"
+            )));
+        }
+        let pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut tables = self.tables.write().unwrap_or_else(PoisonError::into_inner);
+        if tables.contains_key(new_name) || pending.contains_key(new_name) {
+            return Err(MurrError::TableAlreadyExists(new_name.to_string()));
+        }
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        let renamed = with_row_cache(table.rename(new_name)?, &self.config);
+        tables.remove(table_name);
+        tables.insert(new_name.to_string(), renamed);
+        for target in aliases.values_mut() {
+            if target == table_name {
+                *target = new_name.to_string();
+            }
+        }
+        drop(tables);
+        drop(pending);
+        drop(aliases);
+        info!("renamed table '{table_name}' to '{new_name}'");
+        self.events.emit(|| Event::TableRenamed {
+            table: new_name.to_string(),
+            from: table_name.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Points `alias` at `table_name`, creating the alias or moving it in one
+    /// step, so readers switch to a freshly loaded table without a moment in
+    /// which the alias resolves to nothing. Requests already running finish
+    /// on the table they started on. Returns the table the alias pointed at
+    /// before.
+    pub fn set_alias(&self, alias: &str, table_name: &str) -> Result<Option<String>, MurrError> {
+        self.check_writable(|| format!("cannot point alias '{alias}' at '{table_name}'"))?;
+        validate_table_name(alias)?;
+        let mut aliases = self.aliases.write().unwrap_or_else(PoisonError::into_inner);
+        {
+            let pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+            let tables = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+            let exists = |name: &str| tables.contains_key(name) || pending.contains_key(name);
+            if exists(alias) {
+                return Err(MurrError::TableError(format!(
+                    "'{alias}' is a table, an alias cannot replace it"
+                )));
+            }
+            if !exists(table_name) {
+                return Err(MurrError::TableNotFound(table_name.to_string()));
+            }
+        }
+        let previous = self
+            .store
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_alias(alias, Some(table_name))?;
+        aliases.insert(alias.to_string(), table_name.to_string());
+        drop(aliases);
+        info!("alias '{alias}' now points at '{table_name}'");
+        self.events.emit(|| Event::AliasSet {
+            table: table_name.to_string(),
+            alias: alias.to_string(),
+            previous: previous.clone(),
+        });
+        Ok(previous)
+    }
+
+    /// Removes `alias`; the table it pointed at stays. Returns that table.
+    pub fn remove_alias(&self, alias: &str) -> Result<String, MurrError> {
+        self.check_writable(|| format!("cannot remove alias '{alias}'"))?;
+        let mut aliases = self.aliases.write().unwrap_or_else(PoisonError::into_inner);
+        if !aliases.contains_key(alias) {
+            return Err(MurrError::TableNotFound(alias.to_string()));
+        }
+        self.store
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_alias(alias, None)?;
+        let table = aliases.remove(alias).unwrap_or_default();
+        drop(aliases);
+        info!("removed alias '{alias}' of '{table}'");
+        self.events.emit(|| Event::AliasRemoved {
+            table: table.clone(),
+            alias: alias.to_string(),
+        });
+        Ok(table)
+    }
+
+    /// Alias to table.
+    pub fn list_aliases(&self) -> HashMap<String, String> {
+        self.aliases
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// The table `name` stands for: the target of the alias `name`, or `name`
+    /// itself.
+    fn resolve<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self
+            .aliases
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+        {
+            Some(table) => Cow::Owned(table.clone()),
+            None => Cow::Borrowed(name),
+        }
+    }
+
+    /// Refuses when another table derives from `table_name` or resolves key
+    /// aliases through it, since they refer to it by name.
+    fn check_unused(&self, table_name: &str) -> Result<(), MurrError> {
+        let mut users: Vec<String> = self
+            .list_tables()
+            .into_iter()
+            .filter(|(name, schema)| {
+                name != table_name
+                    && (schema
+                        .derived
+                        .as_ref()
+                        .is_some_and(|d| d.source == table_name)
+                        || schema
+                            .key_alias
+                            .as_ref()
+                            .is_some_and(|a| a.table == table_name))
+            })
+            .map(|(name, _)| name)
+            .collect();
+        if !users.is_empty() {
+            users.sort();
+            return Err(MurrError::TableError(format!(
+                "table '{table_name}' is used by {}",
+                users.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Removes every row of `table_name` and keeps its schema, e.g. before
+    /// reloading it from scratch.
+    pub fn truncate(&self, table_name: &str) -> Result<(), MurrError> {
+        self.check_writable(|| format!("cannot truncate '{table_name}'"))?;
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        table.truncate()?;
+        self.events.emit(|| Event::TableTruncated {
+            table: table_name.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Adds nullable columns to `table_name` and drops columns from it,
+    /// rewriting every row once; see `Table::alteration`. The copy runs
+    /// without the tables lock, which is only taken to swap in the altered
+    /// table, so other tables stay available throughout. Refused when a table
+    /// derived from it, or resolving key aliases through it, reads a dropped
+    /// column. Returns the new schema version; the manifest keeps the
+    /// earlier schemas.
+    pub fn alter_table(&self, table_name: &str, change: &SchemaChange) -> Result<u32, MurrError> {
+        self.check_writable(|| format!("cannot alter '{table_name}'"))?;
+        let current = self.schema_of(table_name)?;
+        let schema = current.apply(change)?;
+        if let Some(derivation) = &schema.derived {
+            Plan::new(&schema, derivation, &self.schema_of(&derivation.source)?)?;
+        }
+        for (name, other) in self.list_tables() {
+            if let Some(derivation) = other.derived.as_ref().filter(|d| d.source == table_name) {
+                Plan::new(&other, derivation, &schema).map_err(|e| match e {
+                    MurrError::TableError(msg) => {
+                        MurrError::TableError(format!("table '{name}' derives from it: {msg}"))
+                    }
+                    e => e,
+                })?;
+            }
+            if let Some(alias) = other
+                .key_alias
+                .as_ref()
+                .filter(|a| a.table == table_name && !schema.columns.contains_key(&a.column))
+            {
+                return Err(MurrError::TableError(format!(
+                    "cannot drop column '{}': table '{name}' resolves key aliases through it",
+                    alias.column
+                )));
+            }
+        }
+        let mut alteration = {
+            let tables = self.tables(&[table_name])?;
+            let table = tables
+                .get(table_name)
+                .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+            if *table.schema() != current {
+                return Err(MurrError::TableError(format!(
+                    "'{table_name}' was altered concurrently, try again"
+                )));
+            }
+            table.alteration(schema)?
+        };
+        let copied = alteration.copy();
+        // The store refuses to drop or rename the table until it is
+        // finished, so it is still registered under its name.
+        let mut tables = self.tables.write().unwrap_or_else(PoisonError::into_inner);
+        let altered = with_row_cache(alteration.finish(copied)?, &self.config);
+        tables.insert(table_name.to_string(), altered);
+        drop(tables);
+        let version = self
+            .store
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .manifest()
+            .schema_version(table_name);
+        info!("altered table '{table_name}' to schema version {version}");
+        self.events.emit(|| Event::TableAltered {
+            table: table_name.to_string(),
+            version,
+        });
+        Ok(version)
+    }
+
+    /// Registers an entity tables can declare with `TableSchema.entity`.
+    /// Registering the same definition again is a no-op.
+    pub fn create_entity(&self, name: &str, entity: Entity) -> Result<(), MurrError> {
+        self.check_writable(|| format!("cannot register entity '{name}'"))?;
+        if name.is_empty() || name.chars().any(char::is_control) {
+            return Err(MurrError::TableError(
+                "entity name must be non-empty printable text".to_string(),
+            ));
+        }
+        if !matches!(
+            entity.key_dtype,
+            DTypeName::Utf8 | DTypeName::Int64 | DTypeName::UInt64
+        ) {
+            return Err(MurrError::TableError(format!(
+                "entity '{name}': key dtype must be utf8, int64 or uint64"
+            )));
+        }
+        self.store
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .create_entity(name, &entity)
+    }
+
+    pub fn list_entities(&self) -> HashMap<String, EntityInfo> {
+        let entities = self
+            .store
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .manifest()
+            .entities
+            .clone();
+        let schemas = self.list_tables();
+        entities
+            .into_iter()
+            .map(|(name, entity)| {
+                let mut tables: Vec<String> = schemas
+                    .iter()
+                    .filter(|(_, schema)| schema.entity.as_ref() == Some(&name))
+                    .map(|(table, _)| table.clone())
+                    .collect();
+                tables.sort();
+                (name, EntityInfo { entity, tables })
+            })
+            .collect()
+    }
+
+    /// A table naming an entity must use a registered one, with its key dtype.
+    fn check_entity(&self, name: &str, schema: &TableSchema) -> Result<(), MurrError> {
+        let store = self.store.read().unwrap_or_else(PoisonError::into_inner);
+        let entity =
+            store.manifest().entities.get(name).ok_or_else(|| {
+                MurrError::TableError(format!("entity '{name}' is not registered"))
+            })?;
+        let key_dtype = schema.columns.get(&schema.key).map(|column| column.dtype);
+        if key_dtype != Some(entity.key_dtype) {
+            return Err(MurrError::TableError(format!(
+                "key column '{}' does not have the key dtype of entity '{name}'",
+                schema.key
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn write(&self, table_name: &str, batch: &RecordBatch) -> Result<(), MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        self.check_writable(|| format!("cannot write to '{table_name}'"))?;
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        self.validate_keys(&table.schema().key, batch)?;
+        table.write(batch)?;
+        self.events.emit(|| Event::WriteCommitted {
+            table: table_name.to_string(),
+            rows: batch.num_rows(),
+        });
+        Ok(())
+    }
+
+    /// Deletes the rows for `keys`, returning how many existed. Deleted keys
+    /// read back as missing.
+    pub fn delete(&self, table_name: &str, keys: &[&str]) -> Result<usize, MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        self.check_writable(|| format!("cannot delete from '{table_name}'"))?;
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        let deleted = table.delete(keys)?;
+        self.events.emit(|| Event::KeysDeleted {
+            table: table_name.to_string(),
+            keys: deleted,
+        });
+        Ok(deleted)
+    }
+
+    /// Hides `keys` from reads without dropping their rows, e.g. to suppress
+    /// a user's features while a request about them is pending. A later
+    /// write of a key brings it back with the written values. Returns how
+    /// many of the keys had a row.
+    pub fn deactivate(&self, table_name: &str, keys: &[&str]) -> Result<usize, MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        self.check_writable(|| format!("cannot deactivate keys of '{table_name}'"))?;
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        table.deactivate(keys)
+    }
+
+    /// Undoes `deactivate` for `keys`, restoring their rows as they were.
+    /// Returns how many of the keys were deactivated and are visible again.
+    pub fn reactivate(&self, table_name: &str, keys: &[&str]) -> Result<usize, MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        self.check_writable(|| format!("cannot reactivate keys of '{table_name}'"))?;
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        table.reactivate(keys)
+    }
+
+    /// Erases `key` from every table of `entity`, e.g. for a GDPR erasure
+    /// request: deletes its rows, deactivated ones included, then compacts
+    /// the key's range of each table so the data is gone from disk and not
+    /// only from reads. The compactions run after the tables lock is
+    /// released. Each table emits `KeyPurged` once compacted, and the
+    /// finished purge is logged; neither records the key. A failure leaves
+    /// the tables handled so far purged, and running it again is safe.
+    pub fn purge_key(&self, entity: &str, key: &str) -> Result<PurgeReport, MurrError> {
+        self.check_writable(|| format!("cannot purge a key of entity '{entity}'"))?;
+        let start = Instant::now();
+        let info = self
+            .list_entities()
+            .remove(entity)
+            .ok_or_else(|| MurrError::TableError(format!("entity '{entity}' is not registered")))?;
+        let names: Vec<&str> = info.tables.iter().map(String::as_str).collect();
+        let mut found = 0;
+        let mut compactions = Vec::with_capacity(names.len());
+        {
+            let tables = self.tables(&names)?;
+            for name in &names {
+                let table = tables
+                    .get(*name)
+                    .ok_or_else(|| MurrError::TableNotFound(name.to_string()))?;
+                found += table.delete(&[key])?;
+                compactions.push((*name, table.compaction(Some(key))?));
+            }
+        }
+        for (name, compaction) in compactions {
+            compaction()?;
+            self.events.emit(|| Event::KeyPurged {
+                table: name.to_string(),
+                entity: entity.to_string(),
+                took_ms: start.elapsed().as_millis() as u64,
+            });
+        }
+        let took_ms = start.elapsed().as_millis() as u64;
+        info!(
+            "purged a key of entity '{entity}' from {} table(s), {found} had a row, in {took_ms} ms",
+            names.len()
+        );
+        Ok(PurgeReport {
+            tables: info.tables,
+            found,
+            took_ms,
+        })
+    }
+
+    /// Every mutation checks this first; `server.read_only` turns them all
+    /// into `MurrError::ReadOnly` while reads keep working.
+    fn check_writable(&self, what: impl FnOnce() -> String) -> Result<(), MurrError> {
+        if self.config.server.read_only {
+            return Err(MurrError::ReadOnly(what()));
+        }
+        Ok(())
+    }
+
+    /// Keys end up in the index and in error and log lines, so oversized keys
+    /// and control characters are refused before anything is written. The
+    /// offending key itself is never echoed back.
+    fn validate_keys(&self, key_column: &str, batch: &RecordBatch) -> Result<(), MurrError> {
+        let Some(keys) = batch.column_by_name(key_column) else {
+            // Table::write reports the missing key column.
+            return Ok(());
+        };
+        let Some(keys) = keys.as_any().downcast_ref::<StringArray>() else {
+            return Ok(());
+        };
+        let limit = self.config.server.max_key_size;
+        for (row, key) in keys.iter().enumerate() {
+            let Some(key) = key else { continue };
+            if key.len() > limit {
+                return Err(MurrError::TableError(format!(
+                    "key at row {row} is {} bytes, limit is {limit}",
+                    key.len()
+                )));
+            }
+            if key.chars().any(char::is_control) {
+                return Err(MurrError::TableError(format!(
+                    "key at row {row} contains control characters"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn list_tables(&self) -> HashMap<String, TableSchema> {
+        let mut schemas = self
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let tables = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+        schemas.extend(tables.iter().map(|(k, v)| (k.clone(), v.schema().clone())));
+        schemas
+    }
+
+    /// `server.slo` targets of every table, sorted by name. Tables without a
+    /// target, and every table when `server.slo` is absent, are left out.
+    pub fn slo_targets(&self) -> Vec<TableSlo> {
+        let Some(slo) = &self.config.server.slo else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = self.list_tables().into_keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .filter_map(|table| {
+                let target = slo.target(&table)?;
+                Some(TableSlo {
+                    table,
+                    p99_ms: target.p99_ms,
+                    availability: target.availability,
+                })
+            })
+            .collect()
+    }
+
+    /// The `/metrics` body: SLO targets, then the counters of the features
+    /// that are configured. Empty when there are neither.
+    pub fn metrics_text(&self) -> String {
+        let mut text = prometheus_text(&self.slo_targets());
+        if self.limiter.is_some() {
+            text.push_str(&counter_text(
+                "murr_throttled_requests_total",
+                "Requests rejected by the rate limiter.",
+                self.throttled_requests(),
+            ));
+        }
+        if self.coalescer.is_some() {
+            text.push_str(&counter_text(
+                "murr_coalesced_reads_total",
+                "Reads served from an identical in-flight read.",
+                self.coalesced_reads(),
+            ));
+        }
+        text
+    }
+
+    /// Follows aliases, unlike the admin calls that look schemas up.
+    pub fn get_schema(&self, table_name: &str) -> Result<TableSchema, MurrError> {
+        self.schema_of(&self.resolve(table_name))
+    }
+
+    fn schema_of(&self, table_name: &str) -> Result<TableSchema, MurrError> {
+        let pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(schema) = pending.get(table_name) {
+            return Ok(schema.clone());
+        }
+        drop(pending);
+        let tables = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        Ok(table.schema().clone())
+    }
+
+    pub fn read(
+        &self,
+        table_name: &str,
+        keys: &[&str],
+        columns: &[&str],
+    ) -> Result<RecordBatch, MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        match &self.coalescer {
+            Some(coalescer) => coalescer.read(table_name, keys, columns, || {
+                self.read_uncoalesced(table_name, keys, columns)
+            }),
+            None => self.read_uncoalesced(table_name, keys, columns),
+        }
+    }
+
+    fn read_uncoalesced(
+        &self,
+        table_name: &str,
+        keys: &[&str],
+        columns: &[&str],
+    ) -> Result<RecordBatch, MurrError> {
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        let batch = match Self::resolve_aliases(&tables, table, keys)? {
+            Some(resolved) => {
+                let resolved: Vec<&str> = resolved.iter().map(String::as_str).collect();
+                table.read(&resolved, columns)?
+            }
+            None => table.read(keys, columns)?,
+        };
+        self.check_response_size(&batch)?;
+        Ok(batch)
+    }
+
+    /// Like `read`, but skips requested columns the table can't serve instead
+    /// of failing, and lists them under `UNKNOWN_COLUMNS_METADATA`. Lets
+    /// clients with a newer feature list keep working during a rollout.
+    pub fn read_lenient(
+        &self,
+        table_name: &str,
+        keys: &[&str],
+        columns: &[&str],
+    ) -> Result<RecordBatch, MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        let (known, unknown): (Vec<&str>, Vec<&str>) = {
+            let tables = self.tables(&[table_name])?;
+            let schema = tables
+                .get(table_name)
+                .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?
+                .schema();
+            columns
+                .iter()
+                .copied()
+                .partition(|c| *c != schema.key && schema.columns.contains_key(*c))
+        };
+        let batch = self.read(table_name, keys, &known)?;
+        if unknown.is_empty() {
+            return Ok(batch);
+        }
+        let mut metadata = batch.schema().metadata().clone();
+        metadata.insert(
+            UNKNOWN_COLUMNS_METADATA.to_string(),
+            serde_json::Value::from(unknown).to_string(),
+        );
+        let schema = batch.schema().as_ref().clone().with_metadata(metadata);
+        Ok(batch.with_schema(Arc::new(schema))?)
+    }
+
+    /// What `read` (or `read_lenient`, with `ignore_unknown_columns`) would
+    /// return for `keys` keys, without reading any rows.
+    pub fn estimate_read(
+        &self,
+        table_name: &str,
+        keys: usize,
+        columns: &[&str],
+        ignore_unknown_columns: bool,
+    ) -> Result<ReadEstimate, MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        if !ignore_unknown_columns {
+            return table.estimate_read(keys, columns);
+        }
+        let schema = table.schema();
+        let known: Vec<&str> = columns
+            .iter()
+            .copied()
+            .filter(|c| *c != schema.key && schema.columns.contains_key(*c))
+            .collect();
+        table.estimate_read(keys, &known)
+    }
+
+    /// Reads columns from several tables sharing one key space and returns them
+    /// side by side in a single batch, in the order the tables are listed.
+    pub fn read_join(
+        &self,
+        keys: &[&str],
+        tables: &[(&str, &[&str])],
+    ) -> Result<RecordBatch, MurrError> {
+        let resolved: Vec<Cow<str>> = tables.iter().map(|(name, _)| self.resolve(name)).collect();
+        let names: Vec<&str> = resolved.iter().map(AsRef::as_ref).collect();
+        let registry = self.tables(&names)?;
+        Self::check_same_entity(&registry, &names)?;
+        let mut fields = Vec::new();
+        let mut arrays = Vec::new();
+        for (table_name, (_, columns)) in names.iter().zip(tables) {
+            let table = registry
+                .get(*table_name)
+                .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+            let batch = match Self::resolve_aliases(&registry, table, keys)? {
+                Some(resolved) => {
+                    let resolved: Vec<&str> = resolved.iter().map(String::as_str).collect();
+                    table.read(&resolved, columns)?
+                }
+                None => table.read(keys, columns)?,
+            };
+            for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+                if fields.iter().any(|f: &FieldRef| f.name() == field.name()) {
+                    return Err(MurrError::TableError(format!(
+                        "column '{}' requested from more than one table",
+                        field.name()
+                    )));
+                }
+                fields.push(field.clone());
+                arrays.push(array.clone());
+            }
+        }
+        let schema = Arc::new(Schema::new(fields));
+        let options = RecordBatchOptions::new().with_row_count(Some(keys.len()));
+        let batch = RecordBatch::try_new_with_options(schema, arrays, &options)?;
+        self.check_response_size(&batch)?;
+        Ok(batch)
+    }
+
+    pub fn contains(&self, table_name: &str, keys: &[&str]) -> Result<Vec<bool>, MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        table.contains(keys)
+    }
+
+    /// `keys` as the table's key column, typed like the key, for results
+    /// that carry the keys next to the values.
+    pub fn key_column(
+        &self,
+        table_name: &str,
+        keys: &[&str],
+    ) -> Result<(Field, ArrayRef), MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        table.key_column(keys)
+    }
+
+    pub fn stats(&self, table_name: &str) -> Result<TableStats, MurrError> {
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        table.stats()
+    }
+
+    /// Compacts the table; see `Table::rebuild`. The compaction runs after
+    /// the tables lock is released, so DDL queued behind it can't stall reads.
+    pub fn rebuild(&self, table_name: &str) -> Result<(), MurrError> {
+        self.check_writable(|| format!("cannot rebuild '{table_name}'"))?;
+        let compaction = {
+            let tables = self.tables(&[table_name])?;
+            let table = tables
+                .get(table_name)
+                .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+            table.compaction(None)?
+        };
+        let start = Instant::now();
+        compaction()?;
+        self.events.emit(|| Event::RebuildFinished {
+            table: table_name.to_string(),
+            took_ms: start.elapsed().as_millis() as u64,
+        });
+        Ok(())
+    }
+
+    /// Fills the nulls of `column` with `value` across the whole table; see
+    /// `Table::backfill`. Returns the rows filled, or that would be with
+    /// `dry_run`. The table is looked up again for every page, so DDL can run
+    /// between pages instead of queueing behind the whole walk.
+    pub fn backfill(
+        &self,
+        table_name: &str,
+        column: &str,
+        value: &serde_json::Value,
+        dry_run: bool,
+    ) -> Result<usize, MurrError> {
+        if !dry_run {
+            self.check_writable(|| format!("cannot backfill '{table_name}'"))?;
+        }
+        let mut cursor = None;
+        let mut rows = 0;
+        loop {
+            let tables = self.tables(&[table_name])?;
+            let table = tables
+                .get(table_name)
+                .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+            if column == table.schema().key {
+                return Err(MurrError::TableError(format!(
+                    "cannot backfill key column '{column}'"
+                )));
+            }
+            match table.backfill_page(column, value, dry_run, &mut cursor)? {
+                Some(filled) => rows += filled,
+                None => break,
+            }
+        }
+        if !dry_run && rows > 0 {
+            self.events.emit(|| Event::WriteCommitted {
+                table: table_name.to_string(),
+                rows,
+            });
+        }
+        Ok(rows)
+    }
+
+    /// Recomputes a derived table from its source: every source key is
+    /// written with freshly evaluated columns and keys gone from the source
+    /// are deleted. Returns the rows written. Readers see the table change a
+    /// page at a time, not as one snapshot.
+    pub fn refresh(&self, table_name: &str) -> Result<usize, MurrError> {
+        self.check_writable(|| format!("cannot refresh '{table_name}'"))?;
+        let derivation = self.schema_of(table_name)?.derived.ok_or_else(|| {
+            MurrError::TableError(format!("table '{table_name}' is not a derived table"))
+        })?;
+        let names = [table_name, derivation.source.as_str()];
+
+        // Both walks look the tables up again per page, so the registry's
+        // read guard is never held across a whole scan.
+        let mut rows = 0;
+        let mut cursor: Option<String> = None;
+        loop {
+            let tables = self.tables(&names)?;
+            let [table, source] = Self::get_all(&tables, names)?;
+            let plan = Plan::new(table.schema(), &derivation, source.schema())?;
+            let keys = source.keys(cursor.as_deref(), REFRESH_PAGE)?;
+            let Some(last) = keys.last().cloned() else {
+                break;
+            };
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            let values = source.read(&keys, &plan.inputs())?;
+            table.write(&plan.apply(table.key_column(&keys)?, &values)?)?;
+            rows += keys.len();
+            cursor = Some(last);
+        }
+
+        let mut deleted = 0;
+        let mut cursor: Option<String> = None;
+        loop {
+            let tables = self.tables(&names)?;
+            let [table, source] = Self::get_all(&tables, names)?;
+            let keys = table.keys(cursor.as_deref(), REFRESH_PAGE)?;
+            let Some(last) = keys.last().cloned() else {
+                break;
+            };
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            let stale: Vec<&str> = keys
+                .iter()
+                .zip(source.contains(&keys)?)
+                .filter(|(_, found)| !found)
+                .map(|(key, _)| *key)
+                .collect();
+            if !stale.is_empty() {
+                deleted += table.delete(&stale)?;
+            }
+            cursor = Some(last);
+        }
+
+        self.events.emit(|| Event::WriteCommitted {
+            table: table_name.to_string(),
+            rows,
+        });
+        if deleted > 0 {
+            self.events.emit(|| Event::KeysDeleted {
+                table: table_name.to_string(),
+                keys: deleted,
+            });
+        }
+        Ok(rows)
+    }
+
+    pub fn count(&self, table_name: &str) -> Result<usize, MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        table.count()
+    }
+
+    /// One page of keys in byte order. Pass the last key of the previous page
+    /// as `cursor` to continue.
+    pub fn keys(
+        &self,
+        table_name: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, MurrError> {
+        let resolved = self.resolve(table_name);
+        let table_name = resolved.as_ref();
+        let tables = self.tables(&[table_name])?;
+        let table = tables
+            .get(table_name)
+            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
+        table.keys(cursor, limit)
+    }
+
+    /// Rejects results larger than `server.max_response_size` before an API
+    /// layer spends even more memory encoding them.
+    fn check_response_size(&self, batch: &RecordBatch) -> Result<(), MurrError> {
+        let size = batch.get_array_memory_size();
+        let limit = self.config.server.max_response_size;
+        if size > limit {
+            return Err(MurrError::ResponseTooLarge(format!(
+                "{} rows take {size} bytes, limit is {limit}; request fewer keys or columns",
+                batch.num_rows()
+            )));
+        }
+        Ok(())
+    }
+
+    /// The tables named by `names`, in order.
+    fn get_all<'a, const N: usize>(
+        tables: &'a HashMap<String, Table<S>>,
+        names: [&str; N],
+    ) -> Result<[&'a Table<S>; N], MurrError> {
+        if let Some(name) = names.iter().find(|name| !tables.contains_key(**name)) {
+            return Err(MurrError::TableNotFound(name.to_string()));
+        }
+        Ok(names.map(|name| &tables[name]))
+    }
+
+    /// One key list only makes sense for tables keyed by the same thing, so
+    /// a join across tables that declare different entities is refused.
+    /// Tables without an entity are not checked.
+    fn check_same_entity(
+        tables: &HashMap<String, Table<S>>,
+        names: &[&str],
+    ) -> Result<(), MurrError> {
+        let mut first: Option<(&str, &str)> = None;
+        for name in names {
+            let Some(entity) = tables.get(*name).and_then(|t| t.schema().entity.as_deref()) else {
+                continue;
+            };
+            match first {
+                Some((other, other_entity)) if other_entity != entity => {
+                    return Err(MurrError::TableError(format!(
+                        "cannot join '{other}' ({other_entity}) with '{name}' ({entity}): \
+                         tables hold different entities"
+                    )));
+                }
+                Some(_) => {}
+                None => first = Some((name, entity)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrites keys missing from `table` to their primary keys through the
+    /// table's key alias. Returns `None` when there is nothing to rewrite.
+    fn resolve_aliases(
+        tables: &HashMap<String, Table<S>>,
+        table: &Table<S>,
+        keys: &[&str],
+    ) -> Result<Option<Vec<String>>, MurrError> {
+        let Some(alias) = &table.schema().key_alias else {
+            return Ok(None);
+        };
+        let found = table.contains(keys)?;
+        let missed: Vec<&str> = keys
+            .iter()
+            .zip(&found)
+            .filter(|(_, found)| !**found)
+            .map(|(key, _)| *key)
+            .collect();
+        if missed.is_empty() {
+            return Ok(None);
+        }
+        let alias_table = tables
+            .get(&alias.table)
+            .ok_or_else(|| MurrError::TableNotFound(alias.table.clone()))?;
+        let batch = alias_table.read(&missed, &[alias.column.as_str()])?;
+        let mapped = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .ok_or_else(|| {
+                MurrError::TableError(format!(
+                    "key alias column '{}' of table '{}' must be utf8",
+                    alias.column, alias.table
+                ))
+            })?;
+        let mut mapped = mapped.iter();
+        let resolved = keys
+            .iter()
+            .zip(found)
+            .map(|(key, found)| {
+                let primary = if found { None } else { mapped.next().flatten() };
+                primary.unwrap_or(*key).to_string()
+            })
+            .collect();
+        Ok(Some(resolved))
+    }
+}
+
+/// Opens one table from the manifest, logging how long it took and how big
+/// its index is.
+fn open_table<S: Store>(
+    store: &Arc<RwLock<S>>,
+    config: &Config,
+    events: &Events,
+    clock: &Arc<dyn Clock>,
+    name: &str,
+    schema: TableSchema,
+) -> Result<Table<S>, MurrError> {
+    let column_count = schema.columns.len();
+    let start = Instant::now();
+    let table = match Table::open(store.clone(), name, schema) {
+        Ok(table) => with_row_cache(table.with_clock(clock.clone()), config),
+        Err(e) => {
+            events.table_load_failed(name, &e);
+            return Err(e);
+        }
+    };
+    let stats = table.stats().unwrap_or_default();
+    info!(
+        "loaded table '{}' ({} columns, ~{} keys, {} index bytes, {} disk bytes) in {} ms",
+        name,
+        column_count,
+        stats.estimated_keys,
+        stats.index_bytes,
+        stats.disk_bytes,
+        start.elapsed().as_millis()
+    );
+    events.table_loaded(
+        name,
+        stats.estimated_keys,
+        start.elapsed().as_millis() as u64,
+    );
+    Ok(table)
+}
+
+fn with_row_cache<S: Store>(table: Table<S>, config: &Config) -> Table<S> {
+    match &config.server.row_cache {
+        Some(cache) => table.with_row_cache(cache),
+        None => table,
+    }
+}
+
+/// Table names travel in URL paths, Flight descriptors and log lines, and name
+/// a store namespace, so they are limited to printable characters without
+/// path separators. Anything else is rejected rather than escaped.
+fn validate_table_name(name: &str) -> Result<(), MurrError> {
+    if name.is_empty() || name.len() > MAX_TABLE_NAME_LEN {
+        return Err(MurrError::TableError(format!(
+            "table name must be 1 to {MAX_TABLE_NAME_LEN} bytes long"
+        )));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_control() || matches!(c, '/' | '\\'))
+    {
+        return Err(MurrError::TableError(format!(
+            "table name contains invalid character {c:?}"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conf::{BackendConfig, LoadSheddingConfig, ServerConfig, StorageConfig};
+    use crate::core::{ColumnSchema, DTypeName, Derivation, Entity, KeyAlias, ManualClock};
+    use crate::io::store::faulty::{CompactionGate, FaultyStore};
+    use crate::io::store::memory::MemoryStore;
+    use crate::io::store::rocksdb::RocksDBStore;
+    use crate::io::store::rocksdb::plain::PlainConfig;
+    use arrow::array::{Float32Array, Float64Array};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::{Arc, mpsc};
+    use std::thread;
+    use tempfile::TempDir;
+
+    fn test_config(dir: &TempDir) -> Config {
+        Config {
+            storage: StorageConfig {
+                path: dir.path().to_path_buf(),
+                backend: BackendConfig::Mmap(PlainConfig::default()),
+            },
+            ..Config::default()
+        }
+    }
+
+    fn build_service(config: Config) -> MurrService<RocksDBStore> {
+        let store = Arc::new(RwLock::new(
+            RocksDBStore::open_from_config(&config.storage).unwrap(),
+        ));
+        MurrService::new(store, config).unwrap()
+    }
+
+    /// For tests of service logic that don't need persistence: no RocksDB,
+    /// no temp dir.
+    fn memory_service(server: ServerConfig) -> MurrService<MemoryStore> {
+        let config = Config {
+            server,
+            storage: StorageConfig {
+                path: "unused".into(),
+                backend: BackendConfig::default(),
+            },
+        };
+        MurrService::new(Arc::new(RwLock::new(MemoryStore::new())), config).unwrap()
+    }
+
+    /// A memory service whose compactions wait at the returned gate while
+    /// it is held.
+    fn gated_service() -> (MurrService<FaultyStore<MemoryStore>>, CompactionGate) {
+        let store = FaultyStore::new(MemoryStore::new());
+        let gate = store.compaction_gate();
+        let config = Config {
+            storage: StorageConfig {
+                path: "unused".into(),
+                backend: BackendConfig::default(),
+            },
+            ..Config::default()
+        };
+        let svc = MurrService::new(Arc::new(RwLock::new(store)), config).unwrap();
+        (svc, gate)
+    }
+
+    fn test_schema() -> TableSchema {
+        let mut columns = indexmap::IndexMap::new();
+        columns.insert(
+            "key".to_string(),
+            ColumnSchema {
+                dtype: DTypeName::Utf8,
+                nullable: false,
+                encoding: Default::default(),
+            },
+        );
+        columns.insert(
+            "score".to_string(),
+            ColumnSchema {
+                dtype: DTypeName::Float32,
+                nullable: true,
+                encoding: Default::default(),
+            },
+        );
+        TableSchema {
+            key: "key".to_string(),
+            columns,
+            key_alias: None,
+            derived: None,
+            entity: None,
+        }
+    }
+
+    fn test_batch(keys: &[&str], scores: &[f32]) -> RecordBatch {
+        let arrow_schema = Arc::new(Schema::new(vec![
+            Field::new("key", DataType::Utf8, false),
+            Field::new("score", DataType::Float32, true),
+        ]));
+        let key_array: StringArray = keys.iter().map(|k| Some(*k)).collect();
+        let score_array: Float32Array = scores.iter().map(|v| Some(*v)).collect();
+        RecordBatch::try_new(
+            arrow_schema,
+            vec![Arc::new(key_array), Arc::new(score_array)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_create_write_read_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
+
+        svc.create("users", test_schema()).unwrap();
+
+        let batch = test_batch(&["a", "b", "c"], &[1.0, 2.0, 3.0]);
+        svc.write("users", &batch).unwrap();
+
+        let result = svc.read("users", &["c", "a"], &["score"]).unwrap();
+        assert_eq!(result.num_rows(), 2);
+
+        let vals = result
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        assert_eq!(vals.value(0), 3.0);
+        assert_eq!(vals.value(1), 1.0);
+    }
+
+    #[test]
+    fn test_embedding_column_round_trip() {
+        use arrow::array::{FixedSizeListArray, ListArray};
+        use arrow::datatypes::Float32Type;
+
+        let svc = memory_service(ServerConfig::default());
+        let mut schema = test_schema();
+        schema.columns.insert(
+            "embedding".to_string(),
+            ColumnSchema {
+                dtype: DTypeName::Float32List,
+                nullable: true,
+                encoding: Default::default(),
+            },
+        );
+        svc.create("items", schema).unwrap();
+
+        let embeddings = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+            vec![Some(vec![Some(0.5), Some(-1.0), Some(2.0)]), None],
+            3,
+        );
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("key", DataType::Utf8, false),
+                Field::new("score", DataType::Float32, true),
+                Field::new("embedding", embeddings.data_type().clone(), true),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b"])),
+                Arc::new(Float32Array::from(vec![1.0, 2.0])),
+                Arc::new(embeddings),
+            ],
+        )
+        .unwrap();
+        svc.write("items", &batch).unwrap();
+
+        let result = svc.read("items", &["b", "a"], &["embedding"]).unwrap();
+        let lists = result
+            .column(0)
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+        assert!(lists.is_null(0));
+        let values = lists.value(1);
+        let values = values.as_any().downcast_ref::<Float32Array>().unwrap();
+        assert_eq!(values.values(), &[0.5, -1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_create_duplicate_errors() {
+        let svc = memory_service(ServerConfig::default());
+
+        svc.create("t", test_schema()).unwrap();
+        let err = svc.create("t", test_schema());
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_create_rejects_invalid_table_names() {
+        let svc = memory_service(ServerConfig::default());
+
+        let too_long = "t".repeat(256);
+        for name in ["", "a/b", "a\\b", "line\nbreak", too_long.as_str()] {
+            let err = svc.create(name, test_schema()).unwrap_err();
+            assert!(matches!(err, MurrError::TableError(_)), "{name:?}");
+        }
+        svc.create("features.v2-ü", test_schema()).unwrap();
+    }
+
+    #[test]
+    fn test_read_only_rejects_mutations() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
+        svc.create("users", test_schema()).unwrap();
+        svc.write("users", &test_batch(&["a"], &[1.0])).unwrap();
+        drop(svc);
+
+        let mut config = test_config(&dir);
+        config.server.read_only = true;
+        let svc = build_service(config);
+        let read_only = |r: Result<(), MurrError>| matches!(r, Err(MurrError::ReadOnly(_)));
+        assert!(read_only(svc.create("items", test_schema())));
+        assert!(read_only(svc.write("users", &test_batch(&["b"], &[2.0]))));
+        assert!(read_only(svc.rebuild("users")));
+        assert!(matches!(
+            svc.delete("users", &["a"]),
+            Err(MurrError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            svc.deactivate("users", &["a"]),
+            Err(MurrError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            svc.reactivate("users", &["a"]),
+            Err(MurrError::ReadOnly(_))
+        ));
+        assert!(read_only(svc.truncate("users")));
+        assert!(read_only(svc.drop_table("users")));
+        assert!(matches!(
+            svc.alter_table(
+                "users",
+                &SchemaChange {
+                    drop_columns: vec!["score".to_string()],
+                    ..SchemaChange::default()
+                }
+            ),
+            Err(MurrError::ReadOnly(_))
+        ));
+        assert!(read_only(svc.rename("users", "people")));
+        assert!(matches!(
+            svc.set_alias("prod_users", "users"),
+            Err(MurrError::ReadOnly(_))
+        ));
+        let batch = svc.read("users", &["a", "b"], &["score"]).unwrap();
+        assert_eq!(batch.column(0).null_count(), 1);
+    }
+
+    #[test]
+    fn test_slo_targets_cover_known_tables() {
+        let yaml = "
+default: {p99_ms: 20, availability: 0.999}
+tables:
+  users: {p99_ms: 5, availability: 0.9999}
+  absent: {p99_ms: 1, availability: 0.9}
+";
+        let svc = memory_service(ServerConfig {
+            slo: Some(serde_yaml_ng::from_str(yaml).unwrap()),
+            ..ServerConfig::default()
+        });
+        assert!(svc.slo_targets().is_empty());
+        svc.create("users", test_schema()).unwrap();
+        svc.create("items", test_schema()).unwrap();
+
+        let targets = svc.slo_targets();
+        let tables: Vec<&str> = targets.iter().map(|slo| slo.table.as_str()).collect();
+        assert_eq!(tables, ["items", "users"]);
+        assert_eq!(targets[0].p99_ms, 20.0);
+        assert_eq!(targets[1].availability, 0.9999);
+
+        let svc = memory_service(ServerConfig::default());
+        svc.create("users", test_schema()).unwrap();
+        assert!(svc.slo_targets().is_empty());
+    }
+
+    #[derive(Default)]
+    struct CapturedEvents(Mutex<Vec<Event>>);
+
+    impl EventSink for CapturedEvents {
+        fn send(&self, event: &Event) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_events_follow_table_lifecycle() {
+        let dir = TempDir::new().unwrap();
+        let captured = Arc::new(CapturedEvents::default());
+        let svc = build_service(test_config(&dir)).with_event_sink(captured.clone());
+        svc.create("users", test_schema()).unwrap();
+        svc.write("users", &test_batch(&["a", "b"], &[1.0, 2.0]))
+            .unwrap();
+        assert!(svc.write("missing", &test_batch(&["a"], &[1.0])).is_err());
+        svc.rebuild("users").unwrap();
+        drop(svc);
+
+        let mut config = test_config(&dir);
+        config.server.lazy_load = true;
+        let svc = build_service(config).with_event_sink(captured.clone());
+        svc.warmup("users").unwrap();
+
+        let events = captured.0.lock().unwrap();
+        let names: Vec<&str> = events
+            .iter()
+            .map(|e| match e {
+                Event::TableCreated { .. } => "created",
+                Event::WriteCommitted { rows: 2, .. } => "write",
+                Event::RebuildFinished { .. } => "rebuild",
+                Event::TableLoaded { .. } => "loaded",
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect();
+        assert_eq!(names, ["created", "write", "rebuild", "loaded"]);
+        assert!(events.iter().all(|e| e.table() == "users"));
+        let json = serde_json::to_value(&events[1]).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"event": "write_committed", "table": "users", "rows": 2})
+        );
+    }
+
+    #[test]
+    fn test_repeated_load_failure_is_reported_once() {
+        let captured = Arc::new(CapturedEvents::default());
+        let svc = memory_service(ServerConfig {
+            lazy_load: true,
+            ..ServerConfig::default()
+        })
+        .with_event_sink(captured.clone());
+        let mut broken = test_schema();
+        broken.key = "missing".to_string();
+        svc.pending
+            .lock()
+            .unwrap()
+            .insert("users".to_string(), broken);
+
+        assert!(svc.warmup("users").is_err());
+        assert!(svc.warmup("users").is_err());
+        svc.pending
+            .lock()
+            .unwrap()
+            .insert("users".to_string(), test_schema());
+        svc.warmup("users").unwrap();
+
+        let events = captured.0.lock().unwrap();
+        assert_eq!(events.len(), 2, "{events:?}");
+        assert!(matches!(
+            &events[0],
+            Event::TableLoadFailed { table, error } if table == "users" && error.contains("missing")
+        ));
+        assert!(matches!(&events[1], Event::TableLoaded { .. }));
+    }
+
+    #[test]
+    fn test_deleted_keys_read_as_missing() {
+        let dir = TempDir::new().unwrap();
+        let mut config = test_config(&dir);
+        config.server.row_cache = Some(crate::conf::RowCacheConfig {
+            max_rows: 16,
+            columns: Vec::new(),
+        });
+        let svc = build_service(config);
+        svc.create("users", test_schema()).unwrap();
+        svc.write("users", &test_batch(&["a", "b", "c"], &[1.0, 2.0, 3.0]))
+            .unwrap();
+        // Warm the row cache so the delete has to invalidate it.
+        svc.read("users", &["a", "b"], &["score"]).unwrap();
+
+        assert_eq!(svc.delete("users", &["a", "a", "nobody"]).unwrap(), 1);
+        assert_eq!(svc.contains("users", &["a", "b"]).unwrap(), [false, true]);
+        let result = svc.read("users", &["a", "b"], &["score"]).unwrap();
+        assert!(result.column(0).is_null(0));
+        assert!(result.column(0).is_valid(1));
+        assert_eq!(svc.count("users").unwrap(), 2);
+        assert!(matches!(
+            svc.delete("missing", &["a"]),
+            Err(MurrError::TableNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_backfill_fills_only_nulls() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
+        svc.create("users", test_schema()).unwrap();
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("key", DataType::Utf8, false),
+                Field::new("score", DataType::Float32, true),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b", "c"])),
+                Arc::new(Float32Array::from(vec![Some(1.0), None, None])),
+            ],
+        )
+        .unwrap();
+        svc.write("users", &batch).unwrap();
+
+        let default = serde_json::json!(0.5);
+        assert_eq!(svc.backfill("users", "score", &default, true).unwrap(), 2);
+        assert_eq!(svc.backfill("users", "score", &default, false).unwrap(), 2);
+        assert_eq!(svc.backfill("users", "score", &default, false).unwrap(), 0);
+        let result = svc.read("users", &["a", "b", "c"], &["score"]).unwrap();
+        let scores = result
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        assert_eq!(scores.values(), &[1.0, 0.5, 0.5]);
+
+        let invalid = |r: Result<usize, MurrError>| matches!(r, Err(MurrError::TableError(_)));
+        assert!(invalid(svc.backfill("users", "key", &default, false)));
+        assert!(invalid(svc.backfill("users", "missing", &default, false)));
+        assert!(invalid(svc.backfill(
+            "users",
+            "score",
+            &serde_json::json!("x"),
+            false
+        )));
+        assert!(invalid(svc.backfill(
+            "users",
+            "score",
+            &serde_json::Value::Null,
+            false
+        )));
+    }
+
+    #[test]
+    fn test_refresh_recomputes_derived_table() {
+        let svc = memory_service(ServerConfig::default());
+        svc.create("users", test_schema()).unwrap();
+        svc.write("users", &test_batch(&["a", "b"], &[1.0, 2.5]))
+            .unwrap();
+
+        let mut derived = test_schema();
+        derived.columns.insert(
+            "boosted".to_string(),
+            ColumnSchema {
+                dtype: DTypeName::Float64,
+                nullable: true,
+                encoding: Default::default(),
+            },
+        );
+        derived.derived = Some(Derivation {
+            source: "users".to_string(),
+            columns: [("boosted".to_string(), "(score + 1) * 10".to_string())].into(),
+            refresh_secs: None,
+        });
+        svc.create("boosted_users", derived.clone()).unwrap();
+        assert_eq!(svc.refresh("boosted_users").unwrap(), 2);
+        let result = svc
+            .read("boosted_users", &["a", "b"], &["score", "boosted"])
+            .unwrap();
+        let score = result.column(0).as_any().downcast_ref::<Float32Array>();
+        assert_eq!(score.unwrap().values(), &[1.0, 2.5]);
+        let boosted = result.column(1).as_any().downcast_ref::<Float64Array>();
+        assert_eq!(boosted.unwrap().values(), &[20.0, 35.0]);
+
+        svc.delete("users", &["b"]).unwrap();
+        assert_eq!(svc.refresh("boosted_users").unwrap(), 1);
+        assert_eq!(
+            svc.contains("boosted_users", &["a", "b"]).unwrap(),
+            vec![true, false]
+        );
+
+        let invalid = |derivation: Derivation| {
+            let mut schema = derived.clone();
+            schema.derived = Some(derivation);
+            matches!(svc.create("broken", schema), Err(MurrError::TableError(_)))
+        };
+        let base = derived.derived.clone().unwrap();
+        assert!(invalid(Derivation {
+            source: "missing".to_string(),
+            ..base.clone()
+        }));
+        assert!(invalid(Derivation {
+            columns: [("boosted".to_string(), "clicks * 2".to_string())].into(),
+            ..base.clone()
+        }));
+        assert!(invalid(Derivation {
+            columns: [("boosted".to_string(), "score *".to_string())].into(),
+            ..base
+        }));
+        assert!(matches!(
+            svc.refresh("users"),
+            Err(MurrError::TableError(_))
+        ));
+
+        // Keys keep their dtype, and it must match the source's.
+        let mut ids = test_schema();
+        ids.columns["key"].dtype = DTypeName::Int64;
+        svc.create("ids", ids).unwrap();
+        svc.write("ids", &test_batch(&["1", "2"], &[1.0, 2.0]))
+            .unwrap();
+        let from_ids = Derivation {
+            source: "ids".to_string(),
+            ..derived.derived.clone().unwrap()
+        };
+        assert!(invalid(from_ids.clone()));
+        let mut boosted_ids = derived.clone();
+        boosted_ids.columns["key"].dtype = DTypeName::Int64;
+        boosted_ids.derived = Some(from_ids);
+        svc.create("boosted_ids", boosted_ids).unwrap();
+        assert_eq!(svc.refresh("boosted_ids").unwrap(), 2);
+        assert_eq!(
+            svc.contains("boosted_ids", &["1", "2"]).unwrap(),
+            vec![true, true]
+        );
+    }
+
+    #[test]
+    fn test_write_rejects_invalid_keys() {
+        let svc = memory_service(ServerConfig {
+            max_key_size: 8,
+            ..ServerConfig::default()
+        });
+        svc.create("t", test_schema()).unwrap();
+
+        for key in ["too-long-key", "a\u{1b}[31m"] {
+            let err = svc.write("t", &test_batch(&["ok", key], &[1.0, 2.0]));
+            assert!(matches!(err, Err(MurrError::TableError(_))), "{key:?}");
+        }
+        assert_eq!(svc.count("t").unwrap(), 0);
+        svc.write("t", &test_batch(&["ключ"], &[1.0])).unwrap();
+    }
+
+    #[test]
+    fn test_read_nonexistent_table_errors() {
+        let svc = memory_service(ServerConfig::default());
+
+        let err = svc.read("nope", &["a"], &["score"]);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_read_empty_table_returns_nulls() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
+
+        svc.create("empty", test_schema()).unwrap();
+        let result = svc.read("empty", &["a"], &["score"]).unwrap();
+        assert_eq!(result.num_rows(), 1);
+        let vals = result
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        assert!(vals.is_null(0));
+    }
+
+    #[test]
+    fn test_multiple_writes_accumulate() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
+
+        svc.create("t", test_schema()).unwrap();
+
+        let batch1 = test_batch(&["a", "b"], &[1.0, 2.0]);
+        svc.write("t", &batch1).unwrap();
+
+        let batch2 = test_batch(&["c"], &[3.0]);
+        svc.write("t", &batch2).unwrap();
+
+        let result = svc.read("t", &["a", "b", "c"], &["score"]).unwrap();
+        assert_eq!(result.num_rows(), 3);
+
+        let vals = result
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        assert_eq!(vals.value(0), 1.0);
+        assert_eq!(vals.value(1), 2.0);
+        assert_eq!(vals.value(2), 3.0);
+    }
+
+    #[test]
+    fn test_count_and_keys() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
+
+        svc.create("t", test_schema()).unwrap();
+        svc.write("t", &test_batch(&["c", "a", "b"], &[3.0, 1.0, 2.0]))
+            .unwrap();
+
+        assert_eq!(svc.count("t").unwrap(), 3);
+        assert_eq!(svc.keys("t", None, 2).unwrap(), vec!["a", "b"]);
+        assert_eq!(svc.keys("t", Some("b"), 2).unwrap(), vec!["c"]);
+    }
+
+    #[test]
+    fn test_read_join_combines_tables() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
+
+        let mut other = test_schema();
+        let score = other.columns.shift_remove("score").unwrap();
+        other.columns.insert("clicks".to_string(), score);
+        svc.create("profile", test_schema()).unwrap();
+        svc.create("activity", other).unwrap();
+
+        svc.write("profile", &test_batch(&["a", "b"], &[1.0, 2.0]))
+            .unwrap();
+        let activity = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("key", DataType::Utf8, false),
+                Field::new("clicks", DataType::Float32, true),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["b"])),
+                Arc::new(Float32Array::from(vec![20.0])),
+            ],
+        )
+        .unwrap();
+        svc.write("activity", &activity).unwrap();
+
+        let tables: [(&str, &[&str]); 2] = [("profile", &["score"]), ("activity", &["clicks"])];
+        let result = svc.read_join(&["b", "a"], &tables).unwrap();
+        assert_eq!(result.num_rows(), 2);
+        assert_eq!(result.schema().field(0).name(), "score");
+        assert_eq!(result.schema().field(1).name(), "clicks");
+
+        let scores = result
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        let clicks = result
+            .column(1)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        assert_eq!(scores.value(0), 2.0);
+        assert_eq!(scores.value(1), 1.0);
+        assert_eq!(clicks.value(0), 20.0);
+        assert!(clicks.is_null(1));
+    }
+
+    #[test]
+    fn test_read_lenient_skips_unknown_columns() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
+        svc.create("t", test_schema()).unwrap();
+        svc.write("t", &test_batch(&["a"], &[1.0])).unwrap();
+
+        let result = svc
+            .read_lenient("t", &["a"], &["score", "new_feature", "key"])
+            .unwrap();
+        assert_eq!(result.num_columns(), 1);
+        assert_eq!(result.schema().field(0).name(), "score");
+        assert_eq!(
+            result.schema().metadata()[UNKNOWN_COLUMNS_METADATA],
+            r#"["new_feature","key"]"#
+        );
+
+        let strict = svc.read("t", &["a"], &["score", "new_feature"]);
+        assert!(strict.is_err());
+        let clean = svc.read_lenient("t", &["a"], &["score"]).unwrap();
+        assert!(clean.schema().metadata().is_empty());
     }
 
-    pub fn write(&self, table_name: &str, batch: &RecordBatch) -> Result<(), MurrError> {
-        let tables = self.tables.read().unwrap_or_else(PoisonError::into_inner);
-        let table = tables
-            .get(table_name)
-            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
-        table.write(batch)
-    }
+    #[test]
+    fn test_read_join_rejects_duplicate_columns() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
+        svc.create("a", test_schema()).unwrap();
+        svc.create("b", test_schema()).unwrap();
 
-    pub fn list_tables(&self) -> HashMap<String, TableSchema> {
-        let tables = self.tables.read().unwrap_or_else(PoisonError::into_inner);
-        tables
-            .iter()
-            .map(|(k, v)| (k.clone(), v.schema().clone()))
-            .collect()
+        let tables: [(&str, &[&str]); 2] = [("a", &["score"]), ("b", &["score"])];
+        let err = svc.read_join(&["x"], &tables).unwrap_err();
+        assert!(matches!(err, MurrError::TableError(_)));
     }
 
-    pub fn get_schema(&self, table_name: &str) -> Result<TableSchema, MurrError> {
-        let tables = self.tables.read().unwrap_or_else(PoisonError::into_inner);
-        let table = tables
-            .get(table_name)
-            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
-        Ok(table.schema().clone())
-    }
+    #[test]
+    fn test_entities_gate_creates_and_joins() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
+        let user = Entity {
+            key_dtype: DTypeName::Utf8,
+            description: None,
+        };
+        svc.create_entity("user", user.clone()).unwrap();
+        svc.create_entity("user", user.clone()).unwrap();
+        svc.create_entity("item", user.clone()).unwrap();
+        let changed = Entity {
+            description: Some("someone else".to_string()),
+            ..user.clone()
+        };
+        assert!(matches!(
+            svc.create_entity("user", changed),
+            Err(MurrError::TableError(_))
+        ));
 
-    pub fn read(
-        &self,
-        table_name: &str,
-        keys: &[&str],
-        columns: &[&str],
-    ) -> Result<RecordBatch, MurrError> {
-        let tables = self.tables.read().unwrap_or_else(PoisonError::into_inner);
-        let table = tables
-            .get(table_name)
-            .ok_or_else(|| MurrError::TableNotFound(table_name.to_string()))?;
-        table.read(keys, columns)
+        let keyed_by = |entity: &str| TableSchema {
+            entity: Some(entity.to_string()),
+            ..test_schema()
+        };
+        assert!(matches!(
+            svc.create("orphan", keyed_by("account")),
+            Err(MurrError::TableError(_))
+        ));
+        svc.create("profile", keyed_by("user")).unwrap();
+        svc.create("activity", keyed_by("user")).unwrap();
+        svc.create("catalog", keyed_by("item")).unwrap();
+        svc.create("plain", test_schema()).unwrap();
+
+        let same: [(&str, &[&str]); 2] = [("profile", &["score"]), ("activity", &[])];
+        svc.read_join(&["a"], &same).unwrap();
+        let unchecked: [(&str, &[&str]); 2] = [("catalog", &["score"]), ("plain", &[])];
+        svc.read_join(&["a"], &unchecked).unwrap();
+        let mixed: [(&str, &[&str]); 2] = [("profile", &["score"]), ("catalog", &[])];
+        assert!(matches!(
+            svc.read_join(&["a"], &mixed),
+            Err(MurrError::TableError(_))
+        ));
+
+        drop(svc);
+        let svc = build_service(test_config(&dir));
+        let entities = svc.list_entities();
+        assert_eq!(entities["user"].entity, user);
+        assert_eq!(entities["user"].tables, vec!["activity", "profile"]);
+        assert_eq!(entities["item"].tables, vec!["catalog"]);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::conf::{BackendConfig, StorageConfig};
-    use crate::core::{ColumnSchema, DTypeName};
-    use crate::io::store::rocksdb::RocksDBStore;
-    use crate::io::store::rocksdb::plain::PlainConfig;
-    use arrow::array::{Array, Float32Array, StringArray};
-    use arrow::datatypes::{DataType, Field, Schema};
-    use std::sync::Arc;
-    use tempfile::TempDir;
+    #[test]
+    fn test_purge_key_erases_entity_tables() {
+        let dir = TempDir::new().unwrap();
+        let captured = Arc::new(CapturedEvents::default());
+        let svc = build_service(test_config(&dir)).with_event_sink(captured.clone());
+        let user = Entity {
+            key_dtype: DTypeName::Utf8,
+            description: None,
+        };
+        svc.create_entity("user", user).unwrap();
+        let keyed_by_user = TableSchema {
+            entity: Some("user".to_string()),
+            ..test_schema()
+        };
+        for table in ["profile", "activity"] {
+            svc.create(table, keyed_by_user.clone()).unwrap();
+        }
+        svc.create("plain", test_schema()).unwrap();
+        for table in ["profile", "plain"] {
+            svc.write(table, &test_batch(&["a", "b"], &[1.0, 2.0]))
+                .unwrap();
+        }
+        svc.write("activity", &test_batch(&["a"], &[3.0])).unwrap();
 
-    fn test_config(dir: &TempDir) -> Config {
-        Config {
-            storage: StorageConfig {
-                path: dir.path().to_path_buf(),
-                backend: BackendConfig::Mmap(PlainConfig::default()),
-            },
-            ..Config::default()
+        let report = svc.purge_key("user", "a").unwrap();
+        assert_eq!(report.tables, vec!["activity", "profile"]);
+        assert_eq!(report.found, 2);
+        for table in ["profile", "activity"] {
+            assert_eq!(svc.contains(table, &["a"]).unwrap(), vec![false]);
         }
-    }
+        assert_eq!(svc.contains("profile", &["b"]).unwrap(), vec![true]);
+        assert_eq!(svc.contains("plain", &["a"]).unwrap(), vec![true]);
+        let purged: Vec<String> = captured
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| matches!(e, Event::KeyPurged { .. }))
+            .map(|e| e.table().to_string())
+            .collect();
+        assert_eq!(purged, ["activity", "profile"]);
 
-    fn build_service(config: Config) -> MurrService<RocksDBStore> {
-        let store = Arc::new(RwLock::new(
-            RocksDBStore::open_from_config(&config.storage).unwrap(),
+        assert!(matches!(
+            svc.purge_key("account", "a"),
+            Err(MurrError::TableError(_))
         ));
-        MurrService::new(store, config).unwrap()
     }
 
-    fn test_schema() -> TableSchema {
-        let mut columns = indexmap::IndexMap::new();
-        columns.insert(
-            "key".to_string(),
+    #[test]
+    fn test_rebuild_runs_without_the_tables_lock() {
+        let (svc, gate) = gated_service();
+        svc.create("users", test_schema()).unwrap();
+        gate.hold();
+
+        let svc = &svc;
+        thread::scope(|s| {
+            let rebuild = s.spawn(|| svc.rebuild("users"));
+            while gate.waiting() == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+            let (tx, rx) = mpsc::channel();
+            s.spawn(move || {
+                let created = svc.create("items", test_schema());
+                tx.send(created.and_then(|()| svc.count("users"))).unwrap();
+            });
+            let during = rx.recv_timeout(Duration::from_secs(5));
+            gate.release();
+            assert!(matches!(during, Ok(Ok(0))), "{during:?}");
+            rebuild.join().unwrap().unwrap();
+        });
+    }
+
+    #[test]
+    fn test_alter_copies_without_the_tables_lock() {
+        let (svc, gate) = gated_service();
+        svc.create("users", test_schema()).unwrap();
+        svc.write("users", &test_batch(&["a"], &[1.0])).unwrap();
+        let mut add_clicks = SchemaChange::default();
+        add_clicks.add_columns.insert(
+            "clicks".to_string(),
             ColumnSchema {
-                dtype: DTypeName::Utf8,
-                nullable: false,
+                dtype: DTypeName::Int64,
+                nullable: true,
+                encoding: Default::default(),
             },
         );
-        columns.insert(
-            "score".to_string(),
+        gate.hold();
+
+        let svc = &svc;
+        thread::scope(|s| {
+            let alter = s.spawn(|| svc.alter_table("users", &add_clicks));
+            while gate.waiting() == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+            let (tx, rx) = mpsc::channel();
+            s.spawn(move || {
+                let written = svc
+                    .create("items", test_schema())
+                    .and_then(|()| svc.write("users", &test_batch(&["b"], &[2.0])));
+                tx.send(written.and_then(|()| svc.count("users"))).unwrap();
+            });
+            let during = rx.recv_timeout(Duration::from_secs(5));
+            gate.release();
+            assert!(matches!(during, Ok(Ok(2))), "{during:?}");
+            assert_eq!(alter.join().unwrap().unwrap(), 1);
+        });
+        let batch = svc
+            .read("users", &["a", "b"], &["score", "clicks"])
+            .unwrap();
+        assert_eq!(batch.column(0).null_count(), 0);
+        assert_eq!(batch.column(1).null_count(), 2);
+    }
+
+    #[test]
+    fn test_provision_is_idempotent() {
+        let svc = memory_service(ServerConfig::default());
+        assert_eq!(
+            svc.provision("users", test_schema(), false).unwrap(),
+            Provisioned::Created
+        );
+        assert_eq!(
+            svc.provision("users", test_schema(), false).unwrap(),
+            Provisioned::Unchanged
+        );
+
+        let mut schema = test_schema();
+        schema.columns.shift_remove("score");
+        schema.columns.insert(
+            "clicks".to_string(),
             ColumnSchema {
-                dtype: DTypeName::Float32,
+                dtype: DTypeName::Int64,
                 nullable: true,
+                encoding: Default::default(),
             },
         );
-        TableSchema {
-            key: "key".to_string(),
-            columns,
-        }
-    }
-
-    fn test_batch(keys: &[&str], scores: &[f32]) -> RecordBatch {
-        let arrow_schema = Arc::new(Schema::new(vec![
-            Field::new("key", DataType::Utf8, false),
-            Field::new("score", DataType::Float32, true),
-        ]));
-        let key_array: StringArray = keys.iter().map(|k| Some(*k)).collect();
-        let score_array: Float32Array = scores.iter().map(|v| Some(*v)).collect();
-        RecordBatch::try_new(
-            arrow_schema,
-            vec![Arc::new(key_array), Arc::new(score_array)],
-        )
-        .unwrap()
+        schema.entity = Some("user".to_string());
+        let Provisioned::Differs(diff) = svc.provision("users", schema.clone(), false).unwrap()
+        else {
+            panic!("expected a diff");
+        };
+        assert_eq!(diff.added, ["clicks"]);
+        assert_eq!(diff.removed, ["score"]);
+        assert!(diff.changed.is_empty());
+        assert_eq!(diff.settings, ["entity"]);
+        assert!(matches!(
+            svc.provision("users", schema, true),
+            Err(MurrError::TableError(_))
+        ));
+        assert_eq!(svc.get_schema("users").unwrap(), test_schema());
     }
 
     #[test]
-    fn test_create_write_read_round_trip() {
+    fn test_alter_table_adds_and_drops_columns() {
         let dir = TempDir::new().unwrap();
-        let svc = build_service(test_config(&dir));
-
+        let captured = Arc::new(CapturedEvents::default());
+        let svc = build_service(test_config(&dir)).with_event_sink(captured.clone());
         svc.create("users", test_schema()).unwrap();
+        svc.write("users", &test_batch(&["a", "b"], &[1.0, 2.0]))
+            .unwrap();
+        let mut derived = test_schema();
+        derived.derived = Some(Derivation {
+            source: "users".to_string(),
+            columns: Default::default(),
+            refresh_secs: None,
+        });
+        svc.create("users_copy", derived).unwrap();
 
-        let batch = test_batch(&["a", "b", "c"], &[1.0, 2.0, 3.0]);
-        svc.write("users", &batch).unwrap();
-
-        let result = svc.read("users", &["c", "a"], &["score"]).unwrap();
-        assert_eq!(result.num_rows(), 2);
+        let drop_score = SchemaChange {
+            drop_columns: vec!["score".to_string()],
+            ..SchemaChange::default()
+        };
+        let err = svc.alter_table("users", &drop_score).unwrap_err();
+        assert!(matches!(err, MurrError::TableError(msg) if msg.contains("users_copy")));
+        let clicks = |nullable| ColumnSchema {
+            dtype: DTypeName::Int64,
+            nullable,
+            encoding: Default::default(),
+        };
+        let mut add_clicks = SchemaChange::default();
+        add_clicks
+            .add_columns
+            .insert("clicks".to_string(), clicks(false));
+        assert!(matches!(
+            svc.alter_table("users", &add_clicks),
+            Err(MurrError::TableError(_))
+        ));
+        add_clicks.add_columns["clicks"].nullable = true;
+        assert_eq!(svc.alter_table("users", &add_clicks).unwrap(), 1);
+        let batch = svc.read("users", &["a"], &["score", "clicks"]).unwrap();
+        assert_eq!(batch.column(0).null_count(), 0);
+        assert_eq!(batch.column(1).null_count(), 1);
+        assert!(captured.0.lock().unwrap().contains(&Event::TableAltered {
+            table: "users".to_string(),
+            version: 1,
+        }));
+        drop(svc);
 
-        let vals = result
+        let mut config = test_config(&dir);
+        config.server.lazy_load = true;
+        let svc = build_service(config);
+        let schema = svc.get_schema("users").unwrap();
+        assert_eq!(schema.columns["clicks"], clicks(true));
+        let batch = svc.read("users", &["b"], &["score"]).unwrap();
+        let scores = batch
             .column(0)
             .as_any()
             .downcast_ref::<Float32Array>()
             .unwrap();
-        assert_eq!(vals.value(0), 3.0);
-        assert_eq!(vals.value(1), 1.0);
+        assert_eq!(scores.value(0), 2.0);
+
+        let Provisioned::Evolved(diff) = svc.provision("users", test_schema(), true).unwrap()
+        else {
+            panic!("expected the table to evolve");
+        };
+        assert_eq!(diff.removed, ["clicks"]);
+        assert_eq!(svc.get_schema("users").unwrap(), test_schema());
     }
 
     #[test]
-    fn test_create_duplicate_errors() {
+    fn test_alias_swap_between_builds() {
         let dir = TempDir::new().unwrap();
-        let svc = build_service(test_config(&dir));
+        let captured = Arc::new(CapturedEvents::default());
+        let svc = build_service(test_config(&dir)).with_event_sink(captured.clone());
+        let score = |svc: &MurrService<RocksDBStore>, table: &str| {
+            let batch = svc.read(table, &["a"], &["score"]).unwrap();
+            let scores = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<Float32Array>()
+                .unwrap();
+            scores.value(0)
+        };
+        svc.create("users_v1", test_schema()).unwrap();
+        svc.write("users_v1", &test_batch(&["a"], &[1.0])).unwrap();
+        assert_eq!(svc.set_alias("users", "users_v1").unwrap(), None);
+        assert_eq!(score(&svc, "users"), 1.0);
+        assert_eq!(svc.get_schema("users").unwrap(), test_schema());
 
-        svc.create("t", test_schema()).unwrap();
-        let err = svc.create("t", test_schema());
-        assert!(err.is_err());
+        svc.create("users_v2", test_schema()).unwrap();
+        svc.write("users_v2", &test_batch(&["a"], &[2.0])).unwrap();
+        assert_eq!(
+            svc.set_alias("users", "users_v2").unwrap(),
+            Some("users_v1".to_string())
+        );
+        assert_eq!(score(&svc, "users"), 2.0);
+        let joined = svc.read_join(&["a"], &[("users", &["score"])]).unwrap();
+        assert_eq!(joined.num_rows(), 1);
+
+        // Aliases and tables share one namespace.
+        assert!(svc.create("users", test_schema()).is_err());
+        assert!(svc.set_alias("users_v1", "users_v2").is_err());
+        assert!(matches!(
+            svc.set_alias("other", "missing"),
+            Err(MurrError::TableNotFound(_))
+        ));
+        // Admin calls take table names, so a moving alias is never dropped.
+        assert!(matches!(
+            svc.drop_table("users"),
+            Err(MurrError::TableNotFound(_))
+        ));
+        let err = svc.drop_table("users_v2").unwrap_err();
+        assert!(matches!(err, MurrError::TableError(msg) if msg.contains("alias users")));
+        svc.drop_table("users_v1").unwrap();
+
+        svc.rename("users_v2", "users_2024_05_01").unwrap();
+        assert_eq!(svc.list_aliases()["users"], "users_2024_05_01");
+        assert_eq!(score(&svc, "users"), 2.0);
+        assert!(matches!(
+            svc.count("users_v2"),
+            Err(MurrError::TableNotFound(_))
+        ));
+        svc.create("users_v2", test_schema()).unwrap();
+        assert_eq!(svc.count("users_v2").unwrap(), 0);
+        drop(svc);
+
+        let mut config = test_config(&dir);
+        config.server.lazy_load = true;
+        let svc = build_service(config).with_event_sink(captured.clone());
+        assert_eq!(score(&svc, "users"), 2.0);
+        assert_eq!(svc.remove_alias("users").unwrap(), "users_2024_05_01");
+        assert!(matches!(
+            svc.count("users"),
+            Err(MurrError::TableNotFound(_))
+        ));
+        svc.drop_table("users_2024_05_01").unwrap();
+
+        let kinds: Vec<EventKind> = captured
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(Event::kind)
+            .filter(|kind| {
+                matches!(
+                    kind,
+                    EventKind::AliasSet | EventKind::TableRenamed | EventKind::AliasRemoved
+                )
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::AliasSet,
+                EventKind::AliasSet,
+                EventKind::TableRenamed,
+                EventKind::AliasRemoved
+            ]
+        );
     }
 
     #[test]
-    fn test_read_nonexistent_table_errors() {
-        let dir = TempDir::new().unwrap();
-        let svc = build_service(test_config(&dir));
+    fn test_rename_refuses_dependents_and_taken_names() {
+        let svc = memory_service(ServerConfig::default());
+        svc.create("users", test_schema()).unwrap();
+        svc.create("items", test_schema()).unwrap();
+        let mut derived = test_schema();
+        derived.derived = Some(Derivation {
+            source: "users".to_string(),
+            columns: Default::default(),
+            refresh_secs: None,
+        });
+        svc.create("users_copy", derived).unwrap();
 
-        let err = svc.read("nope", &["a"], &["score"]);
-        assert!(err.is_err());
+        let err = svc.rename("users", "people").unwrap_err();
+        assert!(matches!(err, MurrError::TableError(msg) if msg.contains("users_copy")));
+        assert!(matches!(
+            svc.rename("items", "users"),
+            Err(MurrError::TableAlreadyExists(_))
+        ));
+        assert!(svc.rename("items", "bad/name").is_err());
+        assert!(matches!(
+            svc.rename("missing", "other"),
+            Err(MurrError::TableNotFound(_))
+        ));
+        svc.rename("users_copy", "users_snapshot").unwrap();
+        let schema = svc.get_schema("users_snapshot").unwrap();
+        assert_eq!(schema.derived.unwrap().source, "users");
     }
 
     #[test]
-    fn test_read_empty_table_returns_nulls() {
+    fn test_drop_and_truncate_tables() {
         let dir = TempDir::new().unwrap();
-        let svc = build_service(test_config(&dir));
-
-        svc.create("empty", test_schema()).unwrap();
-        let result = svc.read("empty", &["a"], &["score"]).unwrap();
-        assert_eq!(result.num_rows(), 1);
-        let vals = result
-            .column(0)
-            .as_any()
-            .downcast_ref::<Float32Array>()
+        let captured = Arc::new(CapturedEvents::default());
+        let svc = build_service(test_config(&dir)).with_event_sink(captured.clone());
+        svc.create("users", test_schema()).unwrap();
+        svc.create("items", test_schema()).unwrap();
+        svc.write("users", &test_batch(&["a", "b"], &[1.0, 2.0]))
             .unwrap();
-        assert!(vals.is_null(0));
+        let mut derived = test_schema();
+        derived.derived = Some(Derivation {
+            source: "users".to_string(),
+            columns: Default::default(),
+            refresh_secs: None,
+        });
+        svc.create("users_copy", derived).unwrap();
+
+        let err = svc.drop_table("users").unwrap_err();
+        assert!(matches!(err, MurrError::TableError(msg) if msg.contains("users_copy")));
+        svc.truncate("users").unwrap();
+        assert_eq!(svc.count("users").unwrap(), 0);
+        assert!(svc.get_schema("users").is_ok());
+
+        svc.drop_table("users_copy").unwrap();
+        svc.drop_table("users").unwrap();
+        assert!(matches!(
+            svc.drop_table("users"),
+            Err(MurrError::TableNotFound(_))
+        ));
+        assert!(matches!(
+            svc.truncate("users"),
+            Err(MurrError::TableNotFound(_))
+        ));
+        let kinds: Vec<EventKind> = captured
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(Event::kind)
+            .filter(|kind| matches!(kind, EventKind::TableDropped | EventKind::TableTruncated))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::TableTruncated,
+                EventKind::TableDropped,
+                EventKind::TableDropped
+            ]
+        );
+        drop(svc);
+
+        // Gone after a restart too, lazily loaded tables included.
+        let mut config = test_config(&dir);
+        config.server.lazy_load = true;
+        let svc = build_service(config);
+        assert_eq!(svc.list_tables().keys().collect::<Vec<_>>(), ["items"]);
+        svc.drop_table("items").unwrap();
+        svc.create("users", test_schema()).unwrap();
+        assert_eq!(svc.count("users").unwrap(), 0);
     }
 
     #[test]
-    fn test_multiple_writes_accumulate() {
-        let dir = TempDir::new().unwrap();
-        let svc = build_service(test_config(&dir));
+    fn test_batch_priority_runs_on_batch_pool() {
+        let svc = memory_service(ServerConfig::default());
+
+        let thread_name = || Ok::<_, MurrError>(std::thread::current().name().map(String::from));
+        let online = svc.run(Priority::Online, thread_name).unwrap();
+        let batch = svc.run(Priority::Batch, thread_name).unwrap();
+        assert_ne!(online.as_deref(), Some("murr-batch-0"));
+        assert!(batch.unwrap().starts_with("murr-batch-"));
+        assert!("bulk".parse::<Priority>().is_err());
+    }
+
+    #[test]
+    fn test_load_shedding_rejects_batch_first() {
+        let svc = memory_service(ServerConfig {
+            load_shedding: Some(LoadSheddingConfig {
+                max_in_flight: 2,
+                max_batch_in_flight: 1,
+            }),
+            ..ServerConfig::default()
+        });
+
+        let nested = |outer: Priority, inner: Priority| {
+            svc.run(outer, || svc.run(inner, || Ok::<_, MurrError>(())))
+        };
+        assert!(nested(Priority::Online, Priority::Online).is_ok());
+        assert!(matches!(
+            nested(Priority::Online, Priority::Batch),
+            Err(MurrError::Overloaded(_))
+        ));
+        // Guards release on completion, so a lone batch request still runs.
+        assert!(svc.run(Priority::Batch, || Ok::<_, MurrError>(())).is_ok());
+    }
+
+    #[test]
+    fn test_compaction_window_follows_clock() {
+        let window = "{start: \"01:00\", end: \"02:00\"}";
+        let clock = Arc::new(ManualClock::new(
+            std::time::UNIX_EPOCH + Duration::from_secs(30 * 60),
+        ));
+        let svc = memory_service(ServerConfig {
+            compaction_window: Some(serde_yaml_ng::from_str(window).unwrap()),
+            ..ServerConfig::default()
+        })
+        .with_clock(clock.clone());
+
+        let mut applied = None;
+        svc.apply_compaction_window(&mut applied);
+        assert_eq!(applied, Some(false));
+        clock.advance(Duration::from_secs(60 * 60));
+        svc.apply_compaction_window(&mut applied);
+        assert_eq!(applied, Some(true));
+        clock.advance(Duration::from_secs(60 * 60));
+        svc.apply_compaction_window(&mut applied);
+        assert_eq!(applied, Some(false));
+    }
 
+    #[test]
+    fn test_read_rejects_oversized_response() {
+        // Arrow's buffer sizes vary by version, so measure a one-key read.
+        let one_key = {
+            let svc = memory_service(ServerConfig::default());
+            svc.create("t", test_schema()).unwrap();
+            let batch = svc.read("t", &["k0"], &["score"]).unwrap();
+            batch.get_array_memory_size()
+        };
+        let svc = memory_service(ServerConfig {
+            max_response_size: one_key,
+            ..ServerConfig::default()
+        });
         svc.create("t", test_schema()).unwrap();
 
-        let batch1 = test_batch(&["a", "b"], &[1.0, 2.0]);
-        svc.write("t", &batch1).unwrap();
+        let keys: Vec<String> = (0..100).map(|i| format!("k{i}")).collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let err = svc.read("t", &keys, &["score"]).unwrap_err();
+        assert!(matches!(err, MurrError::ResponseTooLarge(_)));
+        assert!(svc.read("t", &["k0"], &["score"]).is_ok());
+    }
 
-        let batch2 = test_batch(&["c"], &[3.0]);
-        svc.write("t", &batch2).unwrap();
+    #[test]
+    fn test_read_resolves_key_alias() {
+        let dir = TempDir::new().unwrap();
+        let svc = build_service(test_config(&dir));
 
-        let result = svc.read("t", &["a", "b", "c"], &["score"]).unwrap();
-        assert_eq!(result.num_rows(), 3);
+        let mut legacy = test_schema();
+        legacy.columns.shift_remove("score");
+        legacy.columns.insert(
+            "user_id".to_string(),
+            ColumnSchema {
+                dtype: DTypeName::Utf8,
+                nullable: true,
+                encoding: Default::default(),
+            },
+        );
+        let mut users = test_schema();
+        users.key_alias = Some(KeyAlias {
+            table: "legacy_ids".to_string(),
+            column: "user_id".to_string(),
+        });
+        svc.create("legacy_ids", legacy).unwrap();
+        svc.create("users", users).unwrap();
 
-        let vals = result
+        svc.write("users", &test_batch(&["u1", "u2"], &[1.0, 2.0]))
+            .unwrap();
+        let mapping = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("key", DataType::Utf8, false),
+                Field::new("user_id", DataType::Utf8, true),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["old-2"])),
+                Arc::new(StringArray::from(vec!["u2"])),
+            ],
+        )
+        .unwrap();
+        svc.write("legacy_ids", &mapping).unwrap();
+
+        let result = svc
+            .read("users", &["old-2", "u1", "unknown"], &["score"])
+            .unwrap();
+        let scores = result
             .column(0)
             .as_any()
             .downcast_ref::<Float32Array>()
             .unwrap();
-        assert_eq!(vals.value(0), 1.0);
-        assert_eq!(vals.value(1), 2.0);
-        assert_eq!(vals.value(2), 3.0);
+        assert_eq!(scores.value(0), 2.0);
+        assert_eq!(scores.value(1), 1.0);
+        assert!(scores.is_null(2));
     }
 
     #[test]
@@ -282,6 +2949,74 @@ mod tests {
         assert_eq!(vals.value(1), 1.0);
     }
 
+    #[test]
+    fn test_lazy_load_opens_tables_on_first_access() {
+        let dir = TempDir::new().unwrap();
+
+        {
+            let svc = build_service(test_config(&dir));
+            svc.create("users", test_schema()).unwrap();
+            svc.create("items", test_schema()).unwrap();
+            svc.write("users", &test_batch(&["a"], &[1.0])).unwrap();
+        }
+
+        let mut config = test_config(&dir);
+        config.server.lazy_load = true;
+        let svc = build_service(config);
+        assert_eq!(svc.list_tables().len(), 2);
+        assert!(svc.tables.read().unwrap().is_empty());
+
+        let result = svc.read("users", &["a"], &["score"]).unwrap();
+        assert_eq!(result.num_rows(), 1);
+        assert!(svc.tables.read().unwrap().contains_key("users"));
+        assert!(!svc.tables.read().unwrap().contains_key("items"));
+
+        svc.warmup("items").unwrap();
+        assert!(svc.pending.lock().unwrap().is_empty());
+        assert!(matches!(
+            svc.warmup("missing"),
+            Err(MurrError::TableNotFound(_))
+        ));
+        assert!(matches!(
+            svc.create("items", test_schema()),
+            Err(MurrError::TableAlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_background_load_reports_progress() {
+        let dir = TempDir::new().unwrap();
+
+        {
+            let svc = build_service(test_config(&dir));
+            svc.create("users", test_schema()).unwrap();
+            svc.write("users", &test_batch(&["a"], &[1.0])).unwrap();
+        }
+
+        let mut config = test_config(&dir);
+        config.server.background_load = true;
+        let svc = Arc::new(build_service(config));
+        assert!(!svc.load_progress().ready);
+        assert!(svc.get_schema("users").is_ok());
+        assert!(matches!(
+            svc.read("users", &["a"], &["score"]),
+            Err(MurrError::TableLoading(_))
+        ));
+
+        svc.start_background_load();
+        for _ in 0..500 {
+            if svc.load_progress().ready {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let progress = svc.load_progress();
+        assert!(progress.ready);
+        assert_eq!((progress.total, progress.loaded), (1, 1));
+        let result = svc.read("users", &["a"], &["score"]).unwrap();
+        assert_eq!(result.num_rows(), 1);
+    }
+
     #[test]
     fn test_loads_empty_table_on_startup() {
         let dir = TempDir::new().unwrap();
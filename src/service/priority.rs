@@ -0,0 +1,30 @@
+use std::str::FromStr;
+
+use crate::core::MurrError;
+
+/// Request header (HTTP) / metadata key (gRPC) callers use to tag a request.
+pub const PRIORITY_HEADER: &str = "x-murr-priority";
+
+/// Scheduling class of a request. Online requests run on the caller's thread;
+/// batch requests (exports, scans, bulk fetches) queue for the bounded batch
+/// pool so they can't starve online reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Priority {
+    #[default]
+    Online,
+    Batch,
+}
+
+impl FromStr for Priority {
+    type Err = MurrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "online" => Ok(Priority::Online),
+            "batch" => Ok(Priority::Batch),
+            other => Err(MurrError::TableError(format!(
+                "unknown priority '{other}', expected 'online' or 'batch'"
+            ))),
+        }
+    }
+}
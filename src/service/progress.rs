@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Snapshot of background table loading, served by `/ready`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoadProgress {
+    pub ready: bool,
+    pub total: usize,
+    pub loaded: usize,
+    pub failed: usize,
+    /// Estimated time until the remaining tables are open, extrapolated from
+    /// the average load time so far. Absent until the first table is open.
+    pub eta_ms: Option<u64>,
+}
+
+/// Counters the background loader updates as tables open.
+pub(crate) struct LoadTracker {
+    total: usize,
+    loaded: AtomicUsize,
+    failed: AtomicUsize,
+    done: AtomicBool,
+    started: Instant,
+}
+
+impl LoadTracker {
+    pub(crate) fn new(total: usize) -> Self {
+        Self {
+            total,
+            loaded: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            done: AtomicBool::new(total == 0),
+            started: Instant::now(),
+        }
+    }
+
+    pub(crate) fn record(&self, ok: bool) {
+        let counter = if ok { &self.loaded } else { &self.failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn finish(&self) {
+        self.done.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn progress(&self) -> LoadProgress {
+        let loaded = self.loaded.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+        let ready = self.is_done();
+        let remaining = self.total.saturating_sub(loaded + failed);
+        let eta_ms = (!ready && loaded > 0).then(|| {
+            let per_table = self.started.elapsed() / loaded as u32;
+            (per_table * remaining as u32).as_millis() as u64
+        });
+        LoadProgress {
+            ready,
+            total: self.total,
+            loaded,
+            failed,
+            eta_ms,
+        }
+    }
+
+    /// Human-readable ETA for "table is still loading" errors.
+    pub(crate) fn describe(&self) -> String {
+        let p = self.progress();
+        match p.eta_ms {
+            Some(ms) => format!(
+                "{}/{} tables open, about {}s left",
+                p.loaded,
+                p.total,
+                Duration::from_millis(ms).as_secs().max(1)
+            ),
+            None => format!("{}/{} tables open", p.loaded, p.total),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_progress_until_finished() {
+        let tracker = LoadTracker::new(3);
+        assert_eq!(tracker.progress().eta_ms, None);
+        tracker.record(true);
+        tracker.record(false);
+        let p = tracker.progress();
+        assert!(!p.ready);
+        assert_eq!((p.loaded, p.failed), (1, 1));
+        assert!(p.eta_ms.is_some());
+
+        tracker.finish();
+        let p = tracker.progress();
+        assert!(p.ready);
+        assert_eq!(p.eta_ms, None);
+    }
+
+    #[test]
+    fn empty_manifest_is_ready() {
+        assert!(LoadTracker::new(0).progress().ready);
+    }
+}
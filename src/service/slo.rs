@@ -0,0 +1,101 @@
+//! Serving targets from `server.slo`, rendered in the Prometheus text format
+//! for `/metrics`. Every table with a target gets one gauge per target,
+//! labelled with its name, so alert rules can join measured latency and
+//! errors against `murr_slo_*` on `table` instead of hard-coding thresholds
+//! for each table. The service's own counters are appended with
+//! `counter_text`.
+
+use std::fmt::Write;
+
+/// The targets that apply to one table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSlo {
+    pub table: String,
+    pub p99_ms: f64,
+    pub availability: f64,
+}
+
+type Gauge = (&'static str, &'static str, fn(&TableSlo) -> f64);
+
+const GAUGES: [Gauge; 2] = [
+    (
+        "murr_slo_latency_p99_seconds",
+        "Target 99th percentile read latency.",
+        |slo| slo.p99_ms / 1000.0,
+    ),
+    (
+        "murr_slo_availability_ratio",
+        "Target fraction of successful requests.",
+        |slo| slo.availability,
+    ),
+];
+
+/// Prometheus text exposition of `slos`, one family per target. Empty when
+/// there are no targets.
+pub fn prometheus_text(slos: &[TableSlo]) -> String {
+    let mut out = String::new();
+    if slos.is_empty() {
+        return out;
+    }
+    for (name, help, value) in GAUGES {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} gauge");
+        for slo in slos {
+            let _ = writeln!(
+                out,
+                "{name}{{table=\"{}\"}} {}",
+                escape_label(&slo.table),
+                value(slo)
+            );
+        }
+    }
+    out
+}
+
+/// One unlabelled Prometheus counter family.
+pub fn counter_text(name: &str, help: &str, value: u64) -> String {
+    format!("# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}\n")
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_one_gauge_per_target_and_table() {
+        let slos = [
+            TableSlo {
+                table: "items".to_string(),
+                p99_ms: 20.0,
+                availability: 0.999,
+            },
+            TableSlo {
+                table: "us\"ers".to_string(),
+                p99_ms: 5.0,
+                availability: 0.9999,
+            },
+        ];
+        let text = prometheus_text(&slos);
+        assert!(text.contains("# TYPE murr_slo_latency_p99_seconds gauge\n"));
+        assert!(text.contains("murr_slo_latency_p99_seconds{table=\"items\"} 0.02\n"));
+        assert!(text.contains("murr_slo_availability_ratio{table=\"us\\\"ers\"} 0.9999\n"));
+        assert_eq!(
+            text.lines()
+                .filter(|line| line.starts_with("murr_slo_availability_ratio"))
+                .count(),
+            2
+        );
+        assert_eq!(prometheus_text(&[]), "");
+        assert_eq!(
+            counter_text("murr_things_total", "Things.", 3),
+            "# HELP murr_things_total Things.\n# TYPE murr_things_total counter\nmurr_things_total 3\n"
+        );
+    }
+}
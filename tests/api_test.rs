@@ -1,4 +1,5 @@
 use std::io::Cursor;
+use std::net::SocketAddr;
 use std::sync::{Arc, RwLock};
 
 use arrow::array::{Float32Array, StringArray};
@@ -8,6 +9,7 @@ use arrow::ipc::writer::StreamWriter;
 use arrow::record_batch::RecordBatch;
 use axum::Router;
 use axum::body::Body;
+use axum::extract::ConnectInfo;
 use axum::http::{Request, StatusCode};
 use http_body_util::BodyExt;
 use parquet::arrow::arrow_writer::ArrowWriter;
@@ -16,20 +18,27 @@ use tempfile::TempDir;
 use tower::ServiceExt;
 
 use murr::api::MurrHttpService;
-use murr::conf::{BackendConfig, Config, StorageConfig};
+use murr::conf::{
+    AdminConfig, BackendConfig, Config, RateLimitConfig, SloConfig, SloTarget, StorageConfig,
+};
 use murr::io::store::rocksdb::RocksDBStore;
 use murr::io::store::rocksdb::plain::PlainConfig;
 use murr::service::MurrService;
 
 async fn setup() -> (TempDir, Router) {
+    setup_with(|_| {}).await
+}
+
+async fn setup_with(configure: impl FnOnce(&mut Config)) -> (TempDir, Router) {
     let dir = TempDir::new().unwrap();
-    let config = Config {
+    let mut config = Config {
         storage: StorageConfig {
             path: dir.path().to_path_buf(),
             backend: BackendConfig::Mmap(PlainConfig::default()),
         },
         ..Config::default()
     };
+    configure(&mut config);
     let store = Arc::new(RwLock::new(
         RocksDBStore::open_from_config(&config.storage).unwrap(),
     ));
@@ -109,6 +118,52 @@ async fn test_health() {
     assert_eq!(bytes, b"OK");
 }
 
+#[tokio::test]
+async fn test_metrics_export_slo_targets() {
+    let (_dir, router) = setup_with(|config| {
+        let mut slo = SloConfig::default();
+        slo.tables.insert(
+            "features".to_string(),
+            SloTarget {
+                p99_ms: 5.0,
+                availability: 0.999,
+            },
+        );
+        config.server.slo = Some(slo);
+    })
+    .await;
+    let req = Request::get("/metrics").body(Body::empty()).unwrap();
+    let (status, bytes) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(bytes.is_empty());
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let req = Request::get("/metrics").body(Body::empty()).unwrap();
+    let (status, bytes) = body_bytes(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    let text = String::from_utf8(bytes).unwrap();
+    assert!(text.contains("murr_slo_latency_p99_seconds{table=\"features\"} 0.005\n"));
+    assert!(text.contains("murr_slo_availability_ratio{table=\"features\"} 0.999\n"));
+}
+
+#[tokio::test]
+async fn test_metrics_export_coalesced_reads() {
+    let (_dir, router) = setup_with(|config| config.server.coalesce_reads = true).await;
+    let req = Request::get("/metrics").body(Body::empty()).unwrap();
+    let (status, bytes) = body_bytes(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    let text = String::from_utf8(bytes).unwrap();
+    assert!(text.contains("# TYPE murr_coalesced_reads_total counter\n"));
+    assert!(text.contains("murr_coalesced_reads_total 0\n"));
+}
+
 #[tokio::test]
 async fn test_get_nonexistent_table() {
     let (_dir, router) = setup().await;
@@ -120,7 +175,7 @@ async fn test_get_nonexistent_table() {
 }
 
 #[tokio::test]
-async fn test_create_duplicate_table() {
+async fn test_create_table_is_idempotent() {
     let (_dir, router) = setup().await;
     let schema = serde_json::to_vec(&table_schema_json()).unwrap();
 
@@ -135,8 +190,41 @@ async fn test_create_duplicate_table() {
         .header("content-type", "application/json")
         .body(Body::from(schema))
         .unwrap();
-    let (status, _) = body_bytes(router, req).await;
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let mut changed = table_schema_json();
+    changed["columns"]["score"]["dtype"] = json!("float64");
+    changed["columns"]["clicks"] = json!({"dtype": "int64"});
+    let changed = serde_json::to_vec(&changed).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(changed.clone()))
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
     assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(json["diff"]["added"], json!(["clicks"]));
+    assert_eq!(json["diff"]["removed"], json!([]));
+    assert_eq!(json["diff"]["changed"][0]["column"], "score");
+    assert_eq!(json["diff"]["changed"][0]["from"]["dtype"], "float32");
+    assert_eq!(json["diff"]["changed"][0]["to"]["dtype"], "float64");
+
+    let req = Request::put("/api/v1/table/features?allow_evolution=true")
+        .header("content-type", "application/json")
+        .body(Body::from(changed))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let mut added = table_schema_json();
+    added["columns"]["clicks"] = json!({"dtype": "int64"});
+    let req = Request::put("/api/v1/table/features?allow_evolution=true")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&added).unwrap()))
+        .unwrap();
+    let (status, json) = body_json(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["diff"]["added"], json!(["clicks"]));
 }
 
 #[tokio::test]
@@ -303,3 +391,899 @@ async fn test_write_parquet() {
     assert_eq!(scores[1].as_f64().unwrap() as f32, 20.0);
     assert_eq!(scores[2].as_f64().unwrap() as f32, 30.0);
 }
+
+#[tokio::test]
+async fn test_write_empty_parquet() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // A valid file without row groups decodes to no batches at all.
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("score", DataType::Float32, true),
+    ]));
+    let mut buf = Vec::new();
+    ArrowWriter::try_new(&mut buf, schema, None)
+        .unwrap()
+        .close()
+        .unwrap();
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/vnd.apache.parquet")
+        .body(Body::from(buf))
+        .unwrap();
+    let (status, json) = body_json(router, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("empty Parquet file")
+    );
+}
+
+#[tokio::test]
+async fn test_contains() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let ipc_bytes = arrow_ipc_batch(&["a", "b"], &[1.0, 2.0]);
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/vnd.apache.arrow.stream")
+        .body(Body::from(ipc_bytes))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let body = json!({"keys": ["b", "nope", "a"]});
+    let req = Request::post("/api/v1/table/features/contains")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["found"], json!([true, false, true]));
+
+    let req = Request::post("/api/v1/table/nope/contains")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, _) = body_json(router, req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_int64_keys() {
+    let (_dir, router) = setup().await;
+
+    let schema = json!({
+        "key": "id",
+        "columns": {
+            "id": {"dtype": "int64", "nullable": false},
+            "score": {"dtype": "float32", "nullable": true}
+        }
+    });
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&schema).unwrap()))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let body = json!({"columns": {"id": [7, -3], "score": [1.0, 2.0]}});
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Numbers and decimal strings name the same key.
+    let body = json!({"keys": [-3, "7", 8], "columns": ["score"]});
+    let req = Request::post("/api/v1/table/features/fetch")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["columns"]["score"], json!([2.0, 1.0, null]));
+
+    let req = Request::get("/api/v1/table/features/keys")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["keys"], json!(["-3", "7"]));
+
+    let body = json!({"keys": ["seven"], "columns": ["score"]});
+    let req = Request::post("/api/v1/table/features/fetch")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, _) = body_json(router, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_count_and_keys() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let ipc_bytes = arrow_ipc_batch(&["c", "a", "b"], &[3.0, 1.0, 2.0]);
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/vnd.apache.arrow.stream")
+        .body(Body::from(ipc_bytes))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let req = Request::get("/api/v1/table/features/count")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["count"], 3);
+
+    let req = Request::get("/api/v1/table/features/stats")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(json["estimated_keys"].is_u64());
+    assert!(json["index_bytes"].is_u64());
+
+    let req = Request::get("/api/v1/table/features/keys?limit=2")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["keys"], json!(["a", "b"]));
+    assert_eq!(json["next_cursor"], "b");
+
+    let req = Request::get("/api/v1/table/features/keys?limit=2&cursor=b")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["keys"], json!(["c"]));
+    assert!(json["next_cursor"].is_null());
+
+    let req = Request::get("/api/v1/table/features/keys?limit=100001")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = body_json(router, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_rebuild() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let ipc_bytes = arrow_ipc_batch(&["a", "b"], &[1.0, 2.0]);
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/vnd.apache.arrow.stream")
+        .body(Body::from(ipc_bytes))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let req = Request::post("/api/v1/table/features/rebuild?dry_run=true")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(json["estimated_keys"].is_u64());
+    assert!(json["disk_bytes"].is_u64());
+
+    let req = Request::post("/api/v1/table/features/rebuild")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let req = Request::get("/api/v1/table/features/count")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["count"], 2);
+
+    let req = Request::post("/api/v1/table/nope/rebuild")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = body_bytes(router, req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_truncate_and_drop_table() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let ipc_bytes = arrow_ipc_batch(&["a", "b"], &[1.0, 2.0]);
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/vnd.apache.arrow.stream")
+        .body(Body::from(ipc_bytes))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let req = Request::post("/api/v1/table/features/truncate")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let req = Request::get("/api/v1/table/features/count")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["count"], 0);
+
+    let req = Request::delete("/api/v1/table/features?dry_run=true")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(json["estimated_keys"].is_u64());
+
+    let req = Request::delete("/api/v1/table/features")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let req = Request::get("/api/v1/table/features/schema")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let req = Request::delete("/api/v1/table/features")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = body_bytes(router, req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_rename_and_alias_swap() {
+    let (_dir, router) = setup().await;
+    for (table, score) in [("features_v1", 1.0), ("features_v2", 2.0)] {
+        let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+        let req = Request::put(format!("/api/v1/table/{table}"))
+            .header("content-type", "application/json")
+            .body(Body::from(schema))
+            .unwrap();
+        let (status, _) = body_bytes(router.clone(), req).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let req = Request::put(format!("/api/v1/table/{table}/write"))
+            .header("content-type", "application/vnd.apache.arrow.stream")
+            .body(Body::from(arrow_ipc_batch(&["a"], &[score])))
+            .unwrap();
+        let (status, _) = body_bytes(router.clone(), req).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let point = |table: &str| {
+        Request::put("/api/v1/alias/features")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"table": table}).to_string()))
+            .unwrap()
+    };
+    let fetch = || {
+        Request::post("/api/v1/table/features/fetch")
+            .header("content-type", "application/json")
+            .header("accept", "application/json")
+            .body(Body::from(
+                json!({"keys": ["a"], "columns": ["score"]}).to_string(),
+            ))
+            .unwrap()
+    };
+
+    let (status, json) = body_json(router.clone(), point("features_v1")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["previous"], Value::Null);
+    let (status, json) = body_json(router.clone(), fetch()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["columns"]["score"][0], 1.0);
+
+    let (status, json) = body_json(router.clone(), point("features_v2")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["previous"], "features_v1");
+    let (_, json) = body_json(router.clone(), fetch()).await;
+    assert_eq!(json["columns"]["score"][0], 2.0);
+    let (status, _) = body_json(router.clone(), point("missing")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let req = Request::post("/api/v1/table/features_v2/rename")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"to": "features_2024_05_01"}"#))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let req = Request::post("/api/v1/table/features_v1/rename")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"to": "features_2024_05_01"}"#))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let req = Request::get("/api/v1/alias").body(Body::empty()).unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json, json!({"features": "features_2024_05_01"}));
+    let (_, json) = body_json(router.clone(), fetch()).await;
+    assert_eq!(json["columns"]["score"][0], 2.0);
+
+    let req = Request::delete("/api/v1/alias/features")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = body_json(router, fetch()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_alter_table() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let ipc_bytes = arrow_ipc_batch(&["a"], &[1.0]);
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/vnd.apache.arrow.stream")
+        .body(Body::from(ipc_bytes))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let change = json!({"add_columns": {"clicks": {"dtype": "int64"}}, "drop_columns": ["score"]});
+    let req = Request::post("/api/v1/table/features/alter")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&change).unwrap()))
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["version"], 1);
+    assert!(json["schema"]["columns"]["clicks"].is_object());
+    assert!(json["schema"]["columns"].get("score").is_none());
+
+    let req = Request::get("/api/v1/table/features/count")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["count"], 1);
+
+    let req = Request::post("/api/v1/table/features/alter")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"drop_columns": ["id"]}"#))
+        .unwrap();
+    let (status, _) = body_bytes(router, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_delete_keys() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let ipc_bytes = arrow_ipc_batch(&["a", "b"], &[1.0, 2.0]);
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/vnd.apache.arrow.stream")
+        .body(Body::from(ipc_bytes))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let req = Request::delete("/api/v1/table/features/keys")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"keys": ["a", "zzz"]}"#))
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["deleted"], 1);
+
+    let fetch_body = json!({"keys": ["a", "b"], "columns": ["score"]});
+    let req = Request::post("/api/v1/table/features/fetch")
+        .header("content-type", "application/json")
+        .header("accept", "application/json")
+        .body(Body::from(serde_json::to_vec(&fetch_body).unwrap()))
+        .unwrap();
+    let (status, json) = body_json(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["columns"]["score"], json!([null, 2.0]));
+}
+
+#[tokio::test]
+async fn test_deactivate() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let ipc_bytes = arrow_ipc_batch(&["a", "b"], &[1.0, 2.0]);
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/vnd.apache.arrow.stream")
+        .body(Body::from(ipc_bytes))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let req = Request::post("/api/v1/table/features/deactivate")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"keys": ["a", "zzz"]}"#))
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["deactivated"], 1);
+
+    let fetch = || {
+        Request::post("/api/v1/table/features/fetch")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"keys": ["a", "b"], "columns": ["score"]}"#))
+            .unwrap()
+    };
+    let (status, json) = body_json(router.clone(), fetch()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["columns"]["score"], json!([null, 2.0]));
+
+    let ipc_bytes = arrow_ipc_batch(&["a"], &[3.0]);
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/vnd.apache.arrow.stream")
+        .body(Body::from(ipc_bytes))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, json) = body_json(router.clone(), fetch()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["columns"]["score"], json!([3.0, 2.0]));
+
+    let keys_request = |action: &str| {
+        Request::post(format!("/api/v1/table/features/{action}"))
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"keys": ["b", "zzz"]}"#))
+            .unwrap()
+    };
+    let (status, _) = body_json(router.clone(), keys_request("deactivate")).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, json) = body_json(router.clone(), keys_request("reactivate")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["reactivated"], 1);
+
+    let (status, json) = body_json(router, fetch()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["columns"]["score"], json!([3.0, 2.0]));
+}
+
+#[tokio::test]
+async fn test_backfill() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let write_json = json!({"columns": {"id": ["a", "b"], "score": [1.0, null]}});
+    let req = Request::put("/api/v1/table/features/write")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&write_json).unwrap()))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let backfill = |query: &str, body: Value| {
+        Request::post(format!("/api/v1/table/features/backfill{query}"))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    };
+    let fill = json!({"column": "score", "value": 0.0});
+    let (status, json) = body_json(router.clone(), backfill("?dry_run=true", fill.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["rows"], 1);
+    let (status, json) = body_json(router.clone(), backfill("", fill)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["rows"], 1);
+
+    let fetch_body = json!({"keys": ["a", "b"], "columns": ["score"]});
+    let req = Request::post("/api/v1/table/features/fetch")
+        .header("content-type", "application/json")
+        .header("accept", "application/json")
+        .body(Body::from(serde_json::to_vec(&fetch_body).unwrap()))
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["columns"]["score"], json!([1.0, 0.0]));
+
+    let bad = json!({"column": "score", "value": "high"});
+    let (status, _) = body_bytes(router, backfill("", bad)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_fetch_join() {
+    let (_dir, router) = setup().await;
+
+    let profile = table_schema_json();
+    let activity = json!({
+        "key": "id",
+        "columns": {
+            "id": {"dtype": "utf8", "nullable": false},
+            "clicks": {"dtype": "int64", "nullable": true}
+        }
+    });
+    for (name, schema) in [("profile", profile), ("activity", activity)] {
+        let req = Request::put(format!("/api/v1/table/{name}"))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&schema).unwrap()))
+            .unwrap();
+        let (status, _) = body_bytes(router.clone(), req).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let writes = [
+        (
+            "profile",
+            json!({"columns": {"id": ["a", "b"], "score": [1.0, 2.0]}}),
+        ),
+        ("activity", json!({"columns": {"id": ["b"], "clicks": [7]}})),
+    ];
+    for (name, body) in writes {
+        let req = Request::put(format!("/api/v1/table/{name}/write"))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap();
+        let (status, _) = body_bytes(router.clone(), req).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let body = json!({
+        "keys": ["a", "b"],
+        "tables": {"profile": ["score"], "activity": ["clicks"]}
+    });
+    let req = Request::post("/api/v1/fetch")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, json) = body_json(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["columns"]["score"], json!([1.0, 2.0]));
+    assert_eq!(json["columns"]["clicks"], json!([null, 7]));
+}
+
+#[tokio::test]
+async fn test_entities() {
+    let (_dir, router) = setup().await;
+
+    for name in ["user", "item"] {
+        let req = Request::put(format!("/api/v1/entity/{name}"))
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"key_dtype": "utf8"}"#))
+            .unwrap();
+        let (status, _) = body_bytes(router.clone(), req).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+    for (table, entity, expected) in [
+        ("profile", "user", StatusCode::CREATED),
+        ("catalog", "item", StatusCode::CREATED),
+        ("orphan", "account", StatusCode::BAD_REQUEST),
+    ] {
+        let mut schema = table_schema_json();
+        schema["entity"] = json!(entity);
+        let req = Request::put(format!("/api/v1/table/{table}"))
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&schema).unwrap()))
+            .unwrap();
+        let (status, _) = body_bytes(router.clone(), req).await;
+        assert_eq!(status, expected, "{table}");
+    }
+
+    let req = Request::get("/api/v1/entity").body(Body::empty()).unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        json["user"],
+        json!({"key_dtype": "utf8", "tables": ["profile"]})
+    );
+
+    let body = json!({
+        "keys": ["a"],
+        "tables": {"profile": ["score"], "catalog": []}
+    });
+    let req = Request::post("/api/v1/fetch")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let ipc_bytes = arrow_ipc_batch(&["a", "b"], &[1.0, 2.0]);
+    let req = Request::put("/api/v1/table/profile/write")
+        .header("content-type", "application/vnd.apache.arrow.stream")
+        .body(Body::from(ipc_bytes))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let req = Request::post("/api/v1/entity/user/purge")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"key": "a"}"#))
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["tables"], json!(["profile"]));
+    assert_eq!(json["found"], 1);
+
+    let req = Request::post("/api/v1/table/profile/contains")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"keys": ["a", "b"]}"#))
+        .unwrap();
+    let (status, json) = body_json(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["found"], json!([false, true]));
+}
+
+#[tokio::test]
+async fn test_rate_limit_per_client() {
+    let (_dir, router) = setup_with(|config| {
+        config.server.rate_limit = Some(RateLimitConfig {
+            requests_per_second: 0.001,
+            burst: 1,
+        });
+    })
+    .await;
+
+    let list = |ip: [u8; 4], token: &str| {
+        Request::get("/api/v1/table")
+            .header("authorization", token)
+            .extension(ConnectInfo(SocketAddr::from((ip, 40000))))
+            .body(Body::empty())
+            .unwrap()
+    };
+    let (status, _) = body_bytes(router.clone(), list([10, 0, 0, 1], "alice")).await;
+    assert_eq!(status, StatusCode::OK);
+    // A fresh token from the same address doesn't buy a fresh budget.
+    let (status, json) = body_json(router.clone(), list([10, 0, 0, 1], "bob")).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(!json["error"].as_str().unwrap().contains("10.0.0.1"));
+    let (status, _) = body_bytes(router.clone(), list([10, 0, 0, 2], "alice")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let req = Request::get("/health").body(Body::empty()).unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let req = Request::get("/metrics").body(Body::empty()).unwrap();
+    let (status, bytes) = body_bytes(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    let text = String::from_utf8(bytes).unwrap();
+    assert!(text.contains("# TYPE murr_throttled_requests_total counter\n"));
+    assert!(text.contains("murr_throttled_requests_total 1\n"));
+}
+
+#[tokio::test]
+async fn test_fetch_ignore_unknown_columns() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let body = json!({"keys": ["a"], "columns": ["score", "brand_new"]});
+    let req = Request::post("/api/v1/table/features/fetch")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, _) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let body = json!({
+        "keys": ["a"],
+        "columns": ["score", "brand_new"],
+        "ignore_unknown_columns": true
+    });
+    let req = Request::post("/api/v1/table/features/fetch")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, json) = body_json(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["columns"]["score"], json!([null]));
+    assert_eq!(json["unknown_columns"], json!(["brand_new"]));
+}
+
+#[tokio::test]
+async fn test_fetch_debug_timings() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let body = serde_json::to_vec(&json!({"keys": ["a"], "columns": ["score"]})).unwrap();
+    let req = Request::post("/api/v1/table/features/fetch")
+        .header("content-type", "application/json")
+        .body(Body::from(body.clone()))
+        .unwrap();
+    let response = router.clone().oneshot(req).await.unwrap();
+    assert!(response.headers().get("x-murr-timings").is_none());
+
+    let req = Request::post("/api/v1/table/features/fetch?debug=true")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = router.oneshot(req).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let timings: Value =
+        serde_json::from_slice(response.headers()["x-murr-timings"].as_bytes()).unwrap();
+    assert!(timings["lookup_us"].is_u64());
+    assert!(timings["gather_us"]["score"].is_u64());
+    assert!(timings["assembly_us"].is_u64());
+    assert!(timings["encode_us"].is_u64());
+}
+
+#[tokio::test]
+async fn test_fetch_estimate() {
+    let (_dir, router) = setup().await;
+
+    let schema = serde_json::to_vec(&table_schema_json()).unwrap();
+    let req = Request::put("/api/v1/table/features")
+        .header("content-type", "application/json")
+        .body(Body::from(schema))
+        .unwrap();
+    let (status, _) = body_bytes(router.clone(), req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let body = json!({
+        "keys": ["a", "b", "c"],
+        "columns": ["score", "nope"],
+        "ignore_unknown_columns": true
+    });
+    let req = Request::post("/api/v1/table/features/fetch?estimate=true")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json, json!({"rows": 3, "bytes": 3 * 4 + 1}));
+
+    let body = json!({"keys": ["a"], "tables": {"features": ["score"]}});
+    let req = Request::post("/api/v1/fetch?estimate=true")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, json) = body_json(router.clone(), req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json, json!({"rows": 1, "bytes": 4 + 1}));
+
+    let body = json!({"keys": ["a"], "columns": ["nope"]});
+    let req = Request::post("/api/v1/table/features/fetch?estimate=true")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let (status, _) = body_bytes(router, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_admin_routes_move_to_admin_router() {
+    let dir = TempDir::new().unwrap();
+    let mut config = Config {
+        storage: StorageConfig {
+            path: dir.path().to_path_buf(),
+            backend: BackendConfig::Mmap(PlainConfig::default()),
+        },
+        ..Config::default()
+    };
+    config.server.admin = Some(AdminConfig {
+        host: "127.0.0.1".to_string(),
+        port: 8082,
+    });
+    let store = Arc::new(RwLock::new(
+        RocksDBStore::open_from_config(&config.storage).unwrap(),
+    ));
+    let api = MurrHttpService::new(Arc::new(MurrService::new(store, config).unwrap()));
+    let (router, admin) = (api.router(), api.admin_router());
+
+    let create = || {
+        Request::put("/api/v1/table/features")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::to_vec(&table_schema_json()).unwrap(),
+            ))
+            .unwrap()
+    };
+    let (status, _) = body_bytes(router.clone(), create()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = body_bytes(admin.clone(), create()).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let stats = || {
+        Request::get("/api/v1/table/features/stats")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let (status, _) = body_bytes(router.clone(), stats()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = body_bytes(admin.clone(), stats()).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Data-plane routes stay on the main router.
+    let req = Request::get("/api/v1/table/features/schema")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = body_bytes(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    let req = Request::get("/health").body(Body::empty()).unwrap();
+    let (status, _) = body_bytes(admin, req).await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,188 @@
+//! Runs the REST and Flight servers on ephemeral ports against a fresh
+//! service in a temp dir, for tests that need real sockets: connection
+//! metadata, headers, status mapping and anything shared across protocols.
+
+// Each test crate compiles its own copy and uses a different subset.
+#![allow(dead_code)]
+
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+
+use arrow::record_batch::RecordBatch;
+use arrow_flight::Ticket;
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_service_client::FlightServiceClient;
+use axum::body::Bytes;
+use axum::http::{Method, Request, StatusCode};
+use futures::TryStreamExt;
+use http_body_util::{BodyExt, Full};
+use hyper_util::client::legacy::Client;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::rt::TokioExecutor;
+use serde_json::Value;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tonic::Status;
+use tonic::metadata::MetadataValue;
+use tonic::transport::Channel;
+
+use murr::api::{MurrFlightService, MurrHttpService};
+use murr::conf::{BackendConfig, Config, StorageConfig};
+use murr::io::store::rocksdb::RocksDBStore;
+use murr::io::store::rocksdb::plain::PlainConfig;
+use murr::service::MurrService;
+
+pub struct TestServer {
+    pub http_addr: SocketAddr,
+    pub grpc_addr: SocketAddr,
+    http: Client<HttpConnector, Full<Bytes>>,
+    // Dropping the senders stops both servers.
+    _shutdown: [oneshot::Sender<()>; 2],
+    _dir: TempDir,
+}
+
+impl TestServer {
+    pub async fn start() -> Self {
+        Self::start_with(|_| {}).await
+    }
+
+    /// Starts both servers with `configure` applied to the default test
+    /// config. The listen addresses in the config are replaced by the
+    /// ephemeral ones.
+    pub async fn start_with(configure: impl FnOnce(&mut Config)) -> Self {
+        let dir = TempDir::new().unwrap();
+        let mut config = Config {
+            storage: StorageConfig {
+                path: dir.path().to_path_buf(),
+                backend: BackendConfig::Mmap(PlainConfig::default()),
+            },
+            ..Config::default()
+        };
+        configure(&mut config);
+
+        let http_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let grpc_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let http_addr = http_listener.local_addr().unwrap();
+        let grpc_addr = grpc_listener.local_addr().unwrap();
+        config.server.http.host = http_addr.ip().to_string();
+        config.server.http.port = http_addr.port();
+        config.server.grpc.host = grpc_addr.ip().to_string();
+        config.server.grpc.port = grpc_addr.port();
+
+        let store = Arc::new(RwLock::new(
+            RocksDBStore::open_from_config(&config.storage).unwrap(),
+        ));
+        let service = Arc::new(MurrService::new(store, config).unwrap());
+
+        let (http_tx, http_rx) = oneshot::channel::<()>();
+        let app = MurrHttpService::new(service.clone())
+            .router()
+            .into_make_service_with_connect_info::<SocketAddr>();
+        tokio::spawn(async move {
+            axum::serve(http_listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = http_rx.await;
+                })
+                .await
+                .unwrap();
+        });
+
+        let (grpc_tx, grpc_rx) = oneshot::channel::<()>();
+        let flight = MurrFlightService::new(service).router().await;
+        tokio::spawn(async move {
+            flight
+                .serve_with_incoming_shutdown(
+                    tokio_stream::wrappers::TcpListenerStream::new(grpc_listener),
+                    async {
+                        let _ = grpc_rx.await;
+                    },
+                )
+                .await
+                .unwrap();
+        });
+
+        Self {
+            http_addr,
+            grpc_addr,
+            http: Client::builder(TokioExecutor::new()).build_http(),
+            _shutdown: [http_tx, grpc_tx],
+            _dir: dir,
+        }
+    }
+
+    /// Sends one HTTP request; `headers` are added as given.
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: impl Into<Bytes>,
+    ) -> (StatusCode, Bytes) {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(format!("http://{}{path}", self.http_addr));
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let response = self
+            .http
+            .request(builder.body(Full::new(body.into())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, bytes)
+    }
+
+    /// Sends `body` as JSON and parses the response as JSON (`Null` when
+    /// empty).
+    pub async fn json(&self, method: Method, path: &str, body: &Value) -> (StatusCode, Value) {
+        let (status, bytes) = self
+            .request(
+                method,
+                path,
+                &[("content-type", "application/json")],
+                serde_json::to_vec(body).unwrap(),
+            )
+            .await;
+        if bytes.is_empty() {
+            return (status, Value::Null);
+        }
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    pub async fn flight(&self) -> FlightServiceClient<Channel> {
+        let channel = Channel::from_shared(format!("http://{}", self.grpc_addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        FlightServiceClient::new(channel)
+    }
+
+    /// Runs `DoGet` with `ticket` as JSON, sending `authorization` when given.
+    pub async fn do_get(
+        &self,
+        ticket: &Value,
+        authorization: Option<&str>,
+    ) -> Result<Vec<RecordBatch>, Status> {
+        let mut request = tonic::Request::new(Ticket::new(serde_json::to_vec(ticket).unwrap()));
+        if let Some(token) = authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", MetadataValue::try_from(token).unwrap());
+        }
+        let response = self.flight().await.do_get(request).await?;
+        let stream = FlightRecordBatchStream::new_from_flight_data(
+            response
+                .into_inner()
+                .map_err(|e| FlightError::Tonic(Box::new(e))),
+        );
+        stream.try_collect().await.map_err(|e| match e {
+            FlightError::Tonic(status) => *status,
+            other => Status::internal(other.to_string()),
+        })
+    }
+}
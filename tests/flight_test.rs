@@ -1,7 +1,7 @@
 use indexmap::IndexMap;
 use std::sync::{Arc, RwLock};
 
-use arrow::array::{Array, Float32Array, StringArray};
+use arrow::array::{Array, Float32Array, Int64Array, StringArray};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
 use arrow_flight::decode::FlightRecordBatchStream;
@@ -30,6 +30,11 @@ struct TestHarness {
 }
 
 async fn setup() -> TestHarness {
+    setup_with(|_| {}).await
+}
+
+/// Like `setup`, with `prepare` run on the service before the server starts.
+async fn setup_with(prepare: impl FnOnce(&MurrService<RocksDBStore>)) -> TestHarness {
     let dir = TempDir::new().unwrap();
     let config = Config {
         storage: StorageConfig {
@@ -52,6 +57,7 @@ async fn setup() -> TestHarness {
                 ColumnSchema {
                     dtype: DTypeName::Utf8,
                     nullable: false,
+                    encoding: Default::default(),
                 },
             ),
             (
@@ -59,9 +65,13 @@ async fn setup() -> TestHarness {
                 ColumnSchema {
                     dtype: DTypeName::Float32,
                     nullable: true,
+                    encoding: Default::default(),
                 },
             ),
         ]),
+        key_alias: None,
+        derived: None,
+        entity: None,
     };
     service.create("features", schema).unwrap();
 
@@ -73,6 +83,7 @@ async fn setup() -> TestHarness {
     let scores: Float32Array = vec![Some(1.0), Some(2.0), None].into_iter().collect();
     let batch = RecordBatch::try_new(arrow_schema, vec![Arc::new(ids), Arc::new(scores)]).unwrap();
     service.write("features", &batch).unwrap();
+    prepare(&service);
 
     // Start Flight server on OS-assigned port with shutdown signal
     let flight_svc = murr::api::MurrFlightService::new(service);
@@ -143,6 +154,117 @@ async fn test_do_get_round_trip() {
     assert!(scores.is_null(2));
 }
 
+#[tokio::test]
+async fn test_do_get_ticket_options() {
+    let mut harness = setup().await;
+
+    let ticket = serde_json::to_vec(&serde_json::json!({
+        "version": 1,
+        "table": "features",
+        "keys": ["b", "zz", "a"],
+        "columns": ["score"],
+        "include_key": true,
+        "on_missing": "skip"
+    }))
+    .unwrap();
+    let response = harness.client.do_get(Ticket::new(ticket)).await.unwrap();
+    let stream = FlightRecordBatchStream::new_from_flight_data(
+        response
+            .into_inner()
+            .map_err(|e| arrow_flight::error::FlightError::Tonic(Box::new(e))),
+    );
+    let batches: Vec<RecordBatch> = stream.try_collect().await.unwrap();
+    let batch = &batches[0];
+    assert_eq!(batch.schema().field(0).name(), "id");
+    let ids = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(ids.iter().collect::<Vec<_>>(), vec![Some("b"), Some("a")]);
+    let scores = batch
+        .column(1)
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .unwrap();
+    assert_eq!(scores.value(0), 2.0);
+    assert_eq!(scores.value(1), 1.0);
+
+    let ticket = serde_json::to_vec(&serde_json::json!({
+        "table": "features",
+        "keys": ["a", "zz"],
+        "columns": ["score"],
+        "on_missing": "error"
+    }))
+    .unwrap();
+    let err = harness
+        .client
+        .do_get(Ticket::new(ticket))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+
+    let ticket = serde_json::to_vec(&serde_json::json!({
+        "version": 2,
+        "table": "features",
+        "keys": ["a"],
+        "columns": ["score"]
+    }))
+    .unwrap();
+    let err = harness
+        .client
+        .do_get(Ticket::new(ticket))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_do_get_include_key_keeps_int_key_type() {
+    let mut harness = setup_with(|service| {
+        let mut schema = service.get_schema("features").unwrap();
+        schema.columns["id"].dtype = DTypeName::Int64;
+        service.create("counts", schema).unwrap();
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("score", DataType::Float32, true),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![-4, 7])),
+                Arc::new(Float32Array::from(vec![1.0, 2.0])),
+            ],
+        )
+        .unwrap();
+        service.write("counts", &batch).unwrap();
+    })
+    .await;
+
+    let ticket = serde_json::to_vec(&serde_json::json!({
+        "table": "counts",
+        "keys": [7, -4],
+        "key_type": "int",
+        "columns": ["score"],
+        "include_key": true
+    }))
+    .unwrap();
+    let response = harness.client.do_get(Ticket::new(ticket)).await.unwrap();
+    let stream = FlightRecordBatchStream::new_from_flight_data(
+        response
+            .into_inner()
+            .map_err(|e| arrow_flight::error::FlightError::Tonic(Box::new(e))),
+    );
+    let batches: Vec<RecordBatch> = stream.try_collect().await.unwrap();
+    let batch = &batches[0];
+    assert_eq!(batch.schema().field(0).data_type(), &DataType::Int64);
+    let ids = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(ids.values(), &[7, -4]);
+}
+
 #[tokio::test]
 async fn test_do_get_not_found() {
     let mut harness = setup().await;
@@ -213,6 +335,50 @@ async fn test_get_flight_info() {
     assert!(field_names.contains(&"score"));
 }
 
+#[tokio::test]
+async fn test_get_flight_info_estimates_ticket() {
+    let mut harness = setup().await;
+
+    let ticket = serde_json::to_vec(&serde_json::json!({
+        "table": "features",
+        "keys": ["a", "b", "zzz", "d"],
+        "columns": ["score"]
+    }))
+    .unwrap();
+    let info = harness
+        .client
+        .get_flight_info(FlightDescriptor::new_cmd(ticket.clone()))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(info.total_records, 4);
+    // One float32 per key plus the validity bitmap.
+    assert_eq!(info.total_bytes, 4 * 4 + 1);
+
+    // The endpoint's ticket fetches the estimated result.
+    let endpoint_ticket = info.endpoint[0].ticket.clone().unwrap();
+    assert_eq!(endpoint_ticket.ticket, ticket);
+    let response = harness.client.do_get(endpoint_ticket).await.unwrap();
+    let stream = FlightRecordBatchStream::new_from_flight_data(
+        response
+            .into_inner()
+            .map_err(|e| arrow_flight::error::FlightError::Tonic(Box::new(e))),
+    );
+    let batches: Vec<RecordBatch> = stream.try_collect().await.unwrap();
+    assert_eq!(batches[0].num_rows(), 4);
+
+    let missing = serde_json::to_vec(&serde_json::json!({
+        "table": "nope", "keys": ["a"], "columns": ["score"]
+    }))
+    .unwrap();
+    let err = harness
+        .client
+        .get_flight_info(FlightDescriptor::new_cmd(missing))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+}
+
 #[tokio::test]
 async fn test_get_flight_info_has_key_metadata() {
     let mut harness = setup().await;
@@ -248,13 +414,266 @@ async fn test_get_schema() {
 }
 
 #[tokio::test]
-async fn test_do_put_unimplemented() {
+async fn test_do_exchange_streams_lookups() {
     let mut harness = setup().await;
 
-    let result = harness
+    let command = serde_json::to_vec(&serde_json::json!({
+        "table": "features",
+        "columns": ["score"]
+    }))
+    .unwrap();
+    let key_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Utf8, false)]));
+    let key_batch = |keys: Vec<&str>| {
+        RecordBatch::try_new(key_schema.clone(), vec![Arc::new(StringArray::from(keys))]).unwrap()
+    };
+    let input = arrow_flight::encode::FlightDataEncoderBuilder::new()
+        .with_flight_descriptor(Some(FlightDescriptor::new_cmd(command)))
+        .build(futures::stream::iter(vec![
+            Ok(key_batch(vec!["c", "a"])),
+            Ok(key_batch(vec!["b", "zz", "b"])),
+        ]))
+        .try_collect::<Vec<FlightData>>()
+        .await
+        .unwrap();
+
+    let response = harness
         .client
-        .do_put(futures::stream::empty::<FlightData>())
-        .await;
-    assert!(result.is_err());
-    assert_eq!(result.unwrap_err().code(), tonic::Code::Unimplemented);
+        .do_exchange(futures::stream::iter(input))
+        .await
+        .unwrap();
+    let stream = FlightRecordBatchStream::new_from_flight_data(
+        response
+            .into_inner()
+            .map_err(|e| arrow_flight::error::FlightError::Tonic(Box::new(e))),
+    );
+    let batches: Vec<RecordBatch> = stream.try_collect().await.unwrap();
+
+    assert_eq!(batches.len(), 2);
+    let scores = |batch: &RecordBatch| {
+        let col = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap()
+            .clone();
+        col.iter().collect::<Vec<_>>()
+    };
+    assert_eq!(scores(&batches[0]), vec![None, Some(1.0)]);
+    assert_eq!(scores(&batches[1]), vec![Some(2.0), None, Some(2.0)]);
+}
+
+#[tokio::test]
+async fn test_do_exchange_per_batch_commands() {
+    let mut harness = setup().await;
+
+    // No descriptor: each key batch names its own command in app_metadata.
+    let key_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Utf8, false)]));
+    let mut input = Vec::new();
+    for (keys, command) in [
+        (
+            vec!["a"],
+            serde_json::json!({"table": "features", "columns": ["score"]}),
+        ),
+        (
+            vec!["b"],
+            serde_json::json!({"table": "features", "columns": ["score"], "include_key": true}),
+        ),
+    ] {
+        let batch =
+            RecordBatch::try_new(key_schema.clone(), vec![Arc::new(StringArray::from(keys))])
+                .unwrap();
+        let mut messages: Vec<FlightData> = arrow_flight::encode::FlightDataEncoderBuilder::new()
+            .build(futures::stream::iter(vec![Ok(batch)]))
+            .try_collect()
+            .await
+            .unwrap();
+        messages.last_mut().unwrap().app_metadata = serde_json::to_vec(&command).unwrap().into();
+        input.extend(messages);
+    }
+
+    let response = harness
+        .client
+        .do_exchange(futures::stream::iter(input))
+        .await
+        .unwrap();
+    let stream = FlightRecordBatchStream::new_from_flight_data(
+        response
+            .into_inner()
+            .map_err(|e| arrow_flight::error::FlightError::Tonic(Box::new(e))),
+    );
+    let batches: Vec<RecordBatch> = stream.try_collect().await.unwrap();
+
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].num_columns(), 1);
+    assert_eq!(batches[1].num_columns(), 2);
+    assert_eq!(batches[1].schema().field(0).name(), "id");
+}
+
+#[tokio::test]
+async fn test_do_put_streams_writes() {
+    let mut harness = setup().await;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("score", DataType::Float32, true),
+    ]));
+    let batch = |ids: Vec<&str>, scores: Vec<f32>| {
+        RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(ids)),
+                Arc::new(Float32Array::from(scores)),
+            ],
+        )
+        .unwrap()
+    };
+    let input = arrow_flight::encode::FlightDataEncoderBuilder::new()
+        .with_flight_descriptor(Some(FlightDescriptor::new_path(vec![
+            "features".to_string(),
+        ])))
+        .build(futures::stream::iter(vec![
+            Ok(batch(vec!["a", "d"], vec![10.0, 4.0])),
+            Ok(batch(vec!["e"], vec![5.0])),
+        ]))
+        .try_collect::<Vec<FlightData>>()
+        .await
+        .unwrap();
+
+    let response = harness
+        .client
+        .do_put(futures::stream::iter(input))
+        .await
+        .unwrap();
+    let results: Vec<_> = response.into_inner().try_collect().await.unwrap();
+    let rows: Vec<serde_json::Value> = results
+        .iter()
+        .map(|result| serde_json::from_slice(&result.app_metadata).unwrap())
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            serde_json::json!({"rows": 2}),
+            serde_json::json!({"rows": 1})
+        ]
+    );
+
+    let ticket = serde_json::to_vec(&serde_json::json!({
+        "table": "features",
+        "keys": ["a", "d", "e"],
+        "columns": ["score"]
+    }))
+    .unwrap();
+    let response = harness.client.do_get(Ticket::new(ticket)).await.unwrap();
+    let batches: Vec<RecordBatch> = FlightRecordBatchStream::new_from_flight_data(
+        response
+            .into_inner()
+            .map_err(|e| arrow_flight::error::FlightError::Tonic(Box::new(e))),
+    )
+    .try_collect()
+    .await
+    .unwrap();
+    let scores = batches[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .unwrap();
+    assert_eq!(scores.values().to_vec(), vec![10.0, 4.0, 5.0]);
+}
+
+#[tokio::test]
+async fn test_do_put_needs_an_existing_table() {
+    let mut harness = setup().await;
+
+    let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Utf8, false)]));
+    let batch = RecordBatch::try_new(schema, vec![Arc::new(StringArray::from(vec!["a"]))]).unwrap();
+    for (descriptor, code) in [
+        (None, tonic::Code::InvalidArgument),
+        (
+            Some(FlightDescriptor::new_path(vec!["nope".to_string()])),
+            tonic::Code::NotFound,
+        ),
+    ] {
+        let input = arrow_flight::encode::FlightDataEncoderBuilder::new()
+            .with_flight_descriptor(descriptor)
+            .build(futures::stream::iter(vec![Ok(batch.clone())]))
+            .try_collect::<Vec<FlightData>>()
+            .await
+            .unwrap();
+        // The status may arrive with the headers or after them.
+        let err = match harness.client.do_put(futures::stream::iter(input)).await {
+            Ok(response) => response
+                .into_inner()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap_err(),
+            Err(status) => status,
+        };
+        assert_eq!(err.code(), code);
+    }
+}
+
+#[tokio::test]
+async fn test_admin_actions() {
+    let mut harness = setup().await;
+
+    let response = harness
+        .client
+        .list_actions(arrow_flight::Empty {})
+        .await
+        .unwrap();
+    let types: Vec<String> = response
+        .into_inner()
+        .map_ok(|action| action.r#type)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        types,
+        vec![
+            "create_table",
+            "compact",
+            "stats",
+            "drop_table",
+            "truncate_table"
+        ]
+    );
+
+    let action = |r#type: &str, body: serde_json::Value| {
+        arrow_flight::Action::new(r#type, serde_json::to_vec(&body).unwrap())
+    };
+    let create = action(
+        "create_table",
+        serde_json::json!({
+            "table": "items",
+            "schema": {"key": "id", "columns": {"id": {"dtype": "utf8", "nullable": false}}}
+        }),
+    );
+    let response = harness.client.do_action(create.clone()).await.unwrap();
+    let results: Vec<_> = response.into_inner().try_collect().await.unwrap();
+    assert!(results.is_empty());
+    let err = harness.client.do_action(create).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::AlreadyExists);
+
+    let compact = action("compact", serde_json::json!({"table": "features"}));
+    harness.client.do_action(compact).await.unwrap();
+
+    let stats = action("stats", serde_json::json!({"table": "features"}));
+    let response = harness.client.do_action(stats).await.unwrap();
+    let results: Vec<arrow_flight::Result> = response.into_inner().try_collect().await.unwrap();
+    let stats: serde_json::Value = serde_json::from_slice(&results[0].body).unwrap();
+    assert!(stats["estimated_keys"].as_u64().unwrap() > 0);
+
+    let missing = action("stats", serde_json::json!({"table": "nope"}));
+    let err = harness.client.do_action(missing).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+    let unknown = action("flush", serde_json::json!({"table": "features"}));
+    let err = harness.client.do_action(unknown).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unimplemented);
+
+    let truncate = action("truncate_table", serde_json::json!({"table": "items"}));
+    harness.client.do_action(truncate).await.unwrap();
+    let drop = action("drop_table", serde_json::json!({"table": "items"}));
+    harness.client.do_action(drop.clone()).await.unwrap();
+    let err = harness.client.do_action(drop).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
 }
@@ -0,0 +1,120 @@
+//! Wire-format snapshots: the exact JSON fetch body and the Flight result
+//! schema for a fixture table, compared against `tests/fixtures/golden/`.
+//! A diff here means clients may see a change (field order, float
+//! formatting, nullability, metadata keys). If it is intended, rewrite the
+//! files with `MURR_BLESS=1 cargo test --test golden_test` and review the
+//! diff.
+
+mod common;
+
+use std::fmt::Write;
+use std::path::Path;
+
+use arrow::datatypes::Schema;
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+use common::TestServer;
+
+const GOLDEN_DIR: &str = "tests/fixtures/golden";
+
+fn assert_golden(name: &str, actual: &[u8]) {
+    let path = Path::new(GOLDEN_DIR).join(name);
+    if std::env::var_os("MURR_BLESS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read(&path).unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(actual),
+        String::from_utf8_lossy(&expected),
+        "{} changed; rerun with MURR_BLESS=1 if intended",
+        path.display()
+    );
+}
+
+/// One line per field, then the schema metadata in key order.
+fn render_schema(schema: &Schema) -> String {
+    let mut out = String::new();
+    for field in schema.fields() {
+        let null = if field.is_nullable() { "" } else { " not null" };
+        writeln!(out, "{}: {:?}{null}", field.name(), field.data_type()).unwrap();
+    }
+    let mut metadata: Vec<_> = schema.metadata().iter().collect();
+    metadata.sort();
+    for (key, value) in metadata {
+        writeln!(out, "metadata {key} = {value}").unwrap();
+    }
+    out
+}
+
+async fn fixture() -> TestServer {
+    let server = TestServer::start().await;
+    let schema = json!({
+        "key": "id",
+        "columns": {
+            "id": {"dtype": "utf8", "nullable": false},
+            "active": {"dtype": "bool"},
+            "clicks": {"dtype": "int64"},
+            "price": {"dtype": "float64"},
+            "score": {"dtype": "float32"},
+            "tag": {"dtype": "utf8"}
+        }
+    });
+    let (status, _) = server
+        .json(Method::PUT, "/api/v1/table/golden", &schema)
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    // 2^53 + 1 is not representable as f64; 0.1 is not representable as f32.
+    let rows = json!({"columns": {
+        "id": ["a", "b"],
+        "active": [true, false],
+        "clicks": [-1, 9007199254740993i64],
+        "price": [2.5, 0.1],
+        "score": [-3.75, 0.1],
+        "tag": [null, "é\"q"]
+    }});
+    let (status, _) = server
+        .json(Method::PUT, "/api/v1/table/golden/write", &rows)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    server
+}
+
+#[tokio::test]
+async fn test_json_fetch_golden() {
+    let server = fixture().await;
+    let request = json!({
+        "keys": ["b", "a", "zz"],
+        "columns": ["active", "clicks", "price", "score", "tag", "nope"],
+        "ignore_unknown_columns": true
+    });
+    let (status, body) = server
+        .request(
+            Method::POST,
+            "/api/v1/table/golden/fetch",
+            &[("content-type", "application/json")],
+            serde_json::to_vec(&request).unwrap(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_golden("fetch.json", &body);
+}
+
+#[tokio::test]
+async fn test_flight_schema_golden() {
+    let server = fixture().await;
+    let ticket = json!({
+        "table": "golden",
+        "keys": ["b", "a", "zz"],
+        "columns": ["active", "clicks", "price", "score", "tag", "nope"],
+        "include_key": true,
+        "ignore_unknown_columns": true
+    });
+    let batches = server.do_get(&ticket, None).await.unwrap();
+    assert_eq!(batches[0].num_rows(), 3);
+    assert_golden(
+        "flight_schema.txt",
+        render_schema(&batches[0].schema()).as_bytes(),
+    );
+}
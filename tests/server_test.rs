@@ -0,0 +1,194 @@
+mod common;
+
+use arrow::array::{Array, Float32Array, StringArray};
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+use tonic::Code;
+
+use murr::conf::RateLimitConfig;
+
+use common::TestServer;
+
+async fn create_features(server: &TestServer) {
+    let schema = json!({
+        "key": "id",
+        "columns": {
+            "id": {"dtype": "utf8", "nullable": false},
+            "score": {"dtype": "float32", "nullable": true}
+        }
+    });
+    let (status, _) = server
+        .json(Method::PUT, "/api/v1/table/features", &schema)
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let rows = json!({"columns": {"id": ["a", "b"], "score": [1.0, null]}});
+    let (status, _) = server
+        .json(Method::PUT, "/api/v1/table/features/write", &rows)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_rest_write_flight_read() {
+    let server = TestServer::start().await;
+    create_features(&server).await;
+
+    let ticket = json!({"table": "features", "keys": ["b", "a", "zz"], "columns": ["score"]});
+    let batches = server.do_get(&ticket, None).await.unwrap();
+    let scores = batches[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .unwrap();
+    assert!(scores.is_null(0));
+    assert_eq!(scores.value(1), 1.0);
+    assert!(scores.is_null(2));
+}
+
+#[tokio::test]
+async fn test_large_do_get_is_chunked_in_key_order() {
+    let server = TestServer::start_with(|config| config.server.grpc.max_batch_bytes = 4096).await;
+    create_features(&server).await;
+
+    let keys: Vec<String> = (0..5000).map(|i| format!("k{i}")).collect();
+    let ticket =
+        json!({"table": "features", "keys": keys, "columns": ["score"], "include_key": true});
+    let batches = server.do_get(&ticket, None).await.unwrap();
+    assert!(batches.len() > 1, "expected several batches");
+    let returned: Vec<String> = batches
+        .iter()
+        .flat_map(|batch| {
+            let ids = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap();
+            ids.iter()
+                .map(|id| id.unwrap().to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(returned, keys);
+}
+
+#[tokio::test]
+async fn test_large_do_get_streams_chunks() {
+    let server = TestServer::start_with(|config| config.server.grpc.stream_chunk_keys = 100).await;
+    create_features(&server).await;
+
+    let mut keys: Vec<String> = (0..1000).map(|i| format!("k{i}")).collect();
+    keys[500] = "a".to_string();
+    let ticket =
+        json!({"table": "features", "keys": keys, "columns": ["score"], "include_key": true});
+    let batches = server.do_get(&ticket, None).await.unwrap();
+    assert_eq!(batches.len(), 10);
+    let scores = batches[5]
+        .column(1)
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .unwrap();
+    assert_eq!(scores.value(0), 1.0);
+    assert_eq!(scores.null_count(), 99);
+
+    // Missing keys fail the whole request up front, not mid-stream.
+    let ticket =
+        json!({"table": "features", "keys": keys, "columns": ["score"], "on_missing": "error"});
+    let err = server.do_get(&ticket, None).await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_errors_map_to_protocol_statuses() {
+    let server = TestServer::start().await;
+    create_features(&server).await;
+
+    let (status, json) = server
+        .json(
+            Method::POST,
+            "/api/v1/table/missing/fetch",
+            &json!({"keys": ["a"], "columns": ["score"]}),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["error"].is_string());
+
+    let ticket = json!({"table": "missing", "keys": ["a"], "columns": ["score"]});
+    let err = server.do_get(&ticket, None).await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    let ticket = json!({"version": 99, "table": "features", "keys": [], "columns": []});
+    let err = server.do_get(&ticket, None).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_grpc_health_reports_flight_serving() {
+    use tonic_health::pb::HealthCheckRequest;
+    use tonic_health::pb::health_check_response::ServingStatus;
+    use tonic_health::pb::health_client::HealthClient;
+
+    let server = TestServer::start().await;
+    let channel = tonic::transport::Channel::from_shared(format!("http://{}", server.grpc_addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut health = HealthClient::new(channel);
+    for service in ["", "arrow.flight.protocol.FlightService"] {
+        let response = health
+            .check(HealthCheckRequest {
+                service: service.to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.into_inner().status, ServingStatus::Serving as i32);
+    }
+    let err = health
+        .check(HealthCheckRequest {
+            service: "nope".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+}
+
+/// The rate limiter lives in the service, so a client's budget is shared
+/// between REST and Flight.
+#[tokio::test]
+async fn test_rate_limit_spans_protocols() {
+    let server = TestServer::start_with(|config| {
+        config.server.rate_limit = Some(RateLimitConfig {
+            requests_per_second: 0.001,
+            burst: 1,
+        });
+    })
+    .await;
+
+    let (status, _) = server
+        .request(
+            Method::GET,
+            "/api/v1/table",
+            &[("authorization", "alice")],
+            "",
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let ticket = json!({"table": "features", "keys": [], "columns": []});
+    let err = server.do_get(&ticket, Some("alice")).await.unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted);
+
+    // The peer address is the client; a different token doesn't reset it.
+    let (status, _) = server
+        .request(
+            Method::GET,
+            "/api/v1/table",
+            &[("authorization", "bob")],
+            "",
+        )
+        .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let (status, _) = server.request(Method::GET, "/api/v1/table", &[], "").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let (status, _) = server.request(Method::GET, "/health", &[], "").await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,284 @@
+//! Concurrent read/write soak test, ignored by default:
+//!
+//! ```text
+//! cargo test --release --test soak_test -- --ignored --nocapture
+//! ```
+//!
+//! Writers, readers and rebuilds hammer one table for `MURR_SOAK_SECS`
+//! seconds per round (default 10); between rounds the service is dropped and
+//! reopened from disk. Every read is checked against a model of committed
+//! versions, so a stale row cache, a torn row or a lost write fails the run.
+
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use arrow::array::{Array, Int64Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use indexmap::IndexMap;
+use tempfile::TempDir;
+
+use murr::conf::{BackendConfig, Config, RowCacheConfig, StorageConfig};
+use murr::core::{ColumnSchema, DTypeName, TableSchema};
+use murr::io::store::rocksdb::RocksDBStore;
+use murr::io::store::rocksdb::block::BlockConfig;
+use murr::service::MurrService;
+
+const TABLE: &str = "soak";
+const KEYS: usize = 2048;
+const WRITERS: usize = 4;
+const READERS: usize = 4;
+const ROUNDS: usize = 3;
+const BATCH: usize = 64;
+
+/// Last version of each key whose write has returned. Key `i` is only
+/// written by writer `i % WRITERS`, so versions per key only go up.
+struct Model {
+    committed: Vec<AtomicI64>,
+}
+
+impl Model {
+    fn new() -> Self {
+        Self {
+            committed: (0..KEYS).map(|_| AtomicI64::new(0)).collect(),
+        }
+    }
+
+    fn snapshot(&self, keys: &[usize]) -> Vec<i64> {
+        keys.iter()
+            .map(|&k| self.committed[k].load(Ordering::SeqCst))
+            .collect()
+    }
+}
+
+/// xorshift64; good enough to pick keys without pulling in an RNG.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 as usize
+    }
+}
+
+fn key(i: usize) -> String {
+    format!("key-{i:05}")
+}
+
+fn payload(i: usize, version: i64) -> String {
+    format!("{}:{version}", key(i))
+}
+
+fn schema() -> TableSchema {
+    let column = |dtype| ColumnSchema {
+        dtype,
+        nullable: true,
+        encoding: Default::default(),
+    };
+    let mut columns = IndexMap::new();
+    columns.insert(
+        "id".to_string(),
+        ColumnSchema {
+            nullable: false,
+            ..column(DTypeName::Utf8)
+        },
+    );
+    columns.insert("version".to_string(), column(DTypeName::Int64));
+    columns.insert("payload".to_string(), column(DTypeName::Utf8));
+    TableSchema {
+        key: "id".to_string(),
+        columns,
+        key_alias: None,
+        derived: None,
+        entity: None,
+    }
+}
+
+fn open(dir: &TempDir) -> MurrService<RocksDBStore> {
+    let mut config = Config {
+        storage: StorageConfig {
+            path: dir.path().to_path_buf(),
+            backend: BackendConfig::Block(BlockConfig::default()),
+        },
+        ..Config::default()
+    };
+    // The row cache is what concurrent writes are most likely to leave
+    // stale. Read coalescing stays off: a read that joins one already in
+    // flight may legitimately return rows older than its own start.
+    config.server.row_cache = Some(RowCacheConfig {
+        max_rows: KEYS / 4,
+        columns: vec![],
+    });
+    let store = Arc::new(RwLock::new(
+        RocksDBStore::open_from_config(&config.storage).unwrap(),
+    ));
+    let service = MurrService::new(store, config).unwrap();
+    if service.get_schema(TABLE).is_err() {
+        service.create(TABLE, schema()).unwrap();
+    }
+    service
+}
+
+fn write_batch(service: &MurrService<RocksDBStore>, keys: &[usize], versions: &[i64]) {
+    let arrow_schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("version", DataType::Int64, true),
+        Field::new("payload", DataType::Utf8, true),
+    ]));
+    let ids: StringArray = keys.iter().map(|&k| Some(key(k))).collect();
+    let payloads: StringArray = keys
+        .iter()
+        .zip(versions)
+        .map(|(&k, &v)| Some(payload(k, v)))
+        .collect();
+    let batch = RecordBatch::try_new(
+        arrow_schema,
+        vec![
+            Arc::new(ids),
+            Arc::new(Int64Array::from(versions.to_vec())),
+            Arc::new(payloads),
+        ],
+    )
+    .unwrap();
+    service.write(TABLE, &batch).unwrap();
+}
+
+/// Reads `keys` and checks each row against the model: its version lies
+/// between what was committed before and after the read, and both columns
+/// come from the same write.
+fn check_read(service: &MurrService<RocksDBStore>, model: &Model, keys: &[usize]) {
+    let before = model.snapshot(keys);
+    let names: Vec<String> = keys.iter().map(|&k| key(k)).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let batch = service
+        .read(TABLE, &names, &["version", "payload"])
+        .unwrap();
+    let after = model.snapshot(keys);
+
+    let versions = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    let payloads = batch
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    for (row, &k) in keys.iter().enumerate() {
+        if versions.is_null(row) {
+            assert_eq!(before[row], 0, "{} lost its committed write", key(k));
+            assert!(payloads.is_null(row), "{} has a torn row", key(k));
+            continue;
+        }
+        let version = versions.value(row);
+        assert!(
+            (before[row]..=after[row]).contains(&version),
+            "{} read version {version}, committed {}..={}",
+            key(k),
+            before[row],
+            after[row]
+        );
+        assert_eq!(payloads.value(row), payload(k, version), "torn row");
+    }
+}
+
+fn writer(service: &MurrService<RocksDBStore>, model: &Model, id: usize, stop: &AtomicBool) {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15 ^ (id as u64 + 1));
+    let owned = KEYS / WRITERS;
+    while !stop.load(Ordering::Relaxed) {
+        let mut keys: Vec<usize> = (0..BATCH)
+            .map(|_| (rng.next() % owned) * WRITERS + id)
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        let versions: Vec<i64> = keys
+            .iter()
+            .map(|&k| model.committed[k].load(Ordering::SeqCst) + 1)
+            .collect();
+        write_batch(service, &keys, &versions);
+        for (&k, &v) in keys.iter().zip(&versions) {
+            model.committed[k].store(v, Ordering::SeqCst);
+        }
+    }
+}
+
+fn reader(service: &MurrService<RocksDBStore>, model: &Model, id: usize, stop: &AtomicBool) {
+    let mut rng = Rng(0xD1B5_4A32_D192_ED03 ^ (id as u64 + 1));
+    let mut reads = 0u64;
+    while !stop.load(Ordering::Relaxed) {
+        // Hot keys repeat, so most reads go through the row cache.
+        let span = if reads.is_multiple_of(2) {
+            KEYS / 16
+        } else {
+            KEYS
+        };
+        let keys: Vec<usize> = (0..BATCH).map(|_| rng.next() % span).collect();
+        check_read(service, model, &keys);
+        reads += 1;
+    }
+}
+
+#[test]
+#[ignore = "long-running; run with --ignored"]
+fn soak_concurrent_reads_writes_and_rebuilds() {
+    let secs = std::env::var("MURR_SOAK_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(10);
+    let dir = TempDir::new().unwrap();
+    let model = Model::new();
+
+    for round in 0..ROUNDS {
+        let service = open(&dir);
+        let all: Vec<usize> = (0..KEYS).collect();
+        for chunk in all.chunks(BATCH) {
+            // Nothing is writing, so reopened rows must match the model exactly.
+            let committed = model.snapshot(chunk);
+            let names: Vec<String> = chunk.iter().map(|&k| key(k)).collect();
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            let batch = service.read(TABLE, &names, &["version"]).unwrap();
+            let versions = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap();
+            for (row, &k) in chunk.iter().enumerate() {
+                let version = if versions.is_null(row) {
+                    0
+                } else {
+                    versions.value(row)
+                };
+                assert_eq!(version, committed[row], "{} after reopen", key(k));
+            }
+        }
+
+        let stop = AtomicBool::new(false);
+        std::thread::scope(|s| {
+            for id in 0..WRITERS {
+                let (service, model, stop) = (&service, &model, &stop);
+                s.spawn(move || writer(service, model, id, stop));
+            }
+            for id in 0..READERS {
+                let (service, model, stop) = (&service, &model, &stop);
+                s.spawn(move || reader(service, model, id, stop));
+            }
+            s.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    service.rebuild(TABLE).unwrap();
+                    std::thread::sleep(Duration::from_millis(250));
+                }
+            });
+            std::thread::sleep(Duration::from_secs(secs));
+            stop.store(true, Ordering::Relaxed);
+        });
+
+        let written: i64 = (0..KEYS)
+            .map(|k| model.committed[k].load(Ordering::SeqCst))
+            .sum();
+        eprintln!("round {round}: {written} versions committed so far");
+    }
+}